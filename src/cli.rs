@@ -0,0 +1,575 @@
+//! Command line interface for scripting device operations without launching the UI.
+//!
+//! Running `picoforge` without a subcommand starts the graphical application.
+
+use crate::device::fido::{constants::CoseAlgorithm, ssh::SshKeyOptions};
+use crate::device::{
+    export::ExportFormat,
+    inventory, io,
+    ssh::{self, SshSkKeyType},
+    types::GenuineStatus,
+};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "picoforge",
+    version,
+    about = "Commissioning tool for pico-fido security keys"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Manage the device key through the rescue interface
+    #[command(subcommand)]
+    DeviceKey(DeviceKeyCommand),
+    /// Manage the discoverable credentials (passkeys) stored on the key
+    #[command(subcommand)]
+    Passkeys(PasskeysCommand),
+    /// Generate OpenSSH security key backed keys (sk-ecdsa / sk-ed25519)
+    #[command(subcommand)]
+    Ssh(SshCommand),
+    /// Create hmac-secret credentials and derive secrets with them
+    #[command(subcommand)]
+    HmacSecret(HmacSecretCommand),
+    /// Run a makeCredential/getAssertion round trip to check that the key can sign
+    HealthCheck {
+        // With a PIN, user verification is checked too
+        #[command(flatten)]
+        pin: OptionalPinArgs,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Environment variable the FIDO PIN is read from, for scripts
+const PIN_ENV: &str = "PICOFORGE_PIN";
+
+/// For commands that need the FIDO PIN. It is never taken as an argument, where it would end up
+/// in the shell history and the process list.
+#[derive(Args)]
+pub struct PinArgs {
+    /// Read the FIDO PIN from the first line of stdin instead of prompting (or set PICOFORGE_PIN)
+    #[arg(long)]
+    pin_stdin: bool,
+}
+
+impl PinArgs {
+    fn read(&self) -> Result<String, String> {
+        read_pin(self.pin_stdin)
+    }
+}
+
+/// For commands where the FIDO PIN is optional.
+#[derive(Args)]
+pub struct OptionalPinArgs {
+    /// Prompt for the FIDO PIN (implied by --pin-stdin and PICOFORGE_PIN)
+    #[arg(long = "pin")]
+    prompt: bool,
+    /// Read the FIDO PIN from the first line of stdin instead of prompting
+    #[arg(long)]
+    pin_stdin: bool,
+}
+
+impl OptionalPinArgs {
+    fn read(&self) -> Result<Option<String>, String> {
+        if self.prompt || self.pin_stdin || std::env::var_os(PIN_ENV).is_some() {
+            read_pin(self.pin_stdin).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn read_pin(from_stdin: bool) -> Result<String, String> {
+    let pin = if let Ok(pin) = std::env::var(PIN_ENV) {
+        pin
+    } else if from_stdin {
+        let mut line = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|e| format!("Could not read the PIN from stdin: {}", e))?;
+        line.trim_end_matches(['\r', '\n']).to_string()
+    } else {
+        rpassword::prompt_password("FIDO PIN: ")
+            .map_err(|e| format!("Could not read the PIN: {}", e))?
+    };
+    if pin.is_empty() {
+        return Err("no PIN given".into());
+    }
+    Ok(pin)
+}
+
+#[derive(Subcommand)]
+pub enum PasskeysCommand {
+    /// Export the passkey inventory (relying party, user, credential ID, algorithm)
+    Export {
+        #[command(flatten)]
+        pin: PinArgs,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = ExportFormatArg::Json)]
+        format: ExportFormatArg,
+        /// Leave out user IDs and names so the export can be shared
+        #[arg(long)]
+        redact: bool,
+        /// Write the export to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SshCommand {
+    /// Create a credential and write the id_*_sk key handle and .pub file
+    Generate {
+        /// Key type
+        #[arg(short = 't', long = "type", value_enum, default_value_t = SshKeyTypeArg::EcdsaSk)]
+        key_type: SshKeyTypeArg,
+        /// Store the key on the security key so that it can be recovered with `ssh export`
+        #[arg(long)]
+        resident: bool,
+        /// Require the PIN for every signature
+        #[arg(long)]
+        verify_required: bool,
+        /// Do not require a touch for signatures
+        #[arg(long)]
+        no_touch: bool,
+        /// FIDO application (RP ID)
+        #[arg(long, default_value = ssh::SSH_APPLICATION_PREFIX)]
+        application: String,
+        /// User name stored with resident keys
+        #[arg(long)]
+        user: Option<String>,
+        /// Comment for the public key
+        #[arg(short = 'C', long, default_value = "")]
+        comment: String,
+        #[command(flatten)]
+        pin: OptionalPinArgs,
+        /// Key file to write (defaults to ~/.ssh/id_ecdsa_sk or ~/.ssh/id_ed25519_sk)
+        #[arg(short = 'f', long)]
+        out: Option<PathBuf>,
+    },
+    /// List the resident SSH keys stored on the key
+    List {
+        #[command(flatten)]
+        pin: PinArgs,
+    },
+    /// Write key handles for all resident SSH keys, like `ssh-keygen -K`
+    Export {
+        #[command(flatten)]
+        pin: PinArgs,
+        /// Directory to write the id_*_sk_rk files to
+        #[arg(short, long, default_value = ".")]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SshKeyTypeArg {
+    EcdsaSk,
+    Ed25519Sk,
+}
+
+impl From<SshKeyTypeArg> for SshSkKeyType {
+    fn from(key_type: SshKeyTypeArg) -> Self {
+        let algorithm = match key_type {
+            SshKeyTypeArg::EcdsaSk => CoseAlgorithm::ES256,
+            SshKeyTypeArg::Ed25519Sk => CoseAlgorithm::EdDSA,
+        };
+        SshSkKeyType::from_algorithm(algorithm).unwrap_or(SshSkKeyType::EcdsaSk)
+    }
+}
+
+#[derive(Subcommand)]
+pub enum HmacSecretCommand {
+    /// Create a credential with the hmac-secret extension and print its ID
+    Create {
+        /// Relying party ID the credential is bound to
+        #[arg(long)]
+        rp_id: String,
+        #[command(flatten)]
+        pin: OptionalPinArgs,
+        /// Store the credential on the key so that it can be used without its ID
+        #[arg(long)]
+        resident: bool,
+    },
+    /// Run getAssertion with one or two salts and print the derived outputs as hex
+    Get {
+        /// Relying party ID the credential is bound to
+        #[arg(long)]
+        rp_id: String,
+        /// Credential ID as hex. Without it a resident credential for the RP is used
+        #[arg(long)]
+        credential_id: Option<String>,
+        /// First salt: 64 hex characters, or any other text which is hashed with SHA-256
+        #[arg(long)]
+        salt: String,
+        /// Optional second salt, in the same format
+        #[arg(long)]
+        salt2: Option<String>,
+        // The key derives different outputs with and without user verification
+        #[command(flatten)]
+        pin: OptionalPinArgs,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormatArg {
+    Json,
+    Csv,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(format: ExportFormatArg) -> Self {
+        match format {
+            ExportFormatArg::Json => ExportFormat::Json,
+            ExportFormatArg::Csv => ExportFormat::Csv,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum DeviceKeyCommand {
+    /// Print the device public key as PEM
+    PublicKey {
+        /// Write the PEM to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        /// Print the raw uncompressed point as hex instead of PEM
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Sign a challenge with the device key and print the DER signature as hex
+    Sign {
+        /// Challenge as a hex string
+        #[arg(conflicts_with = "file", required_unless_present = "file")]
+        challenge: Option<String>,
+        /// Read the challenge from a file instead
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+    },
+    /// Upload a device certificate (PEM or DER)
+    UploadCert {
+        /// Path to the certificate issued for the device public key
+        cert: PathBuf,
+    },
+    /// Record the device key of the connected device in the local inventory
+    Enroll {
+        /// Optional label stored alongside the serial number
+        #[arg(short, long)]
+        label: Option<String>,
+        /// Inventory file (defaults to the application data directory)
+        #[arg(long)]
+        inventory: Option<PathBuf>,
+        /// Replace the device key already enrolled for this serial number
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check that the connected device is the one enrolled for its serial number
+    Verify {
+        /// Inventory file (defaults to the application data directory)
+        #[arg(long)]
+        inventory: Option<PathBuf>,
+    },
+}
+
+/// Parses the command line. `None` starts the GUI, which is also what happens for arguments
+/// that are not a subcommand, such as file arguments passed by a desktop launcher or `-psn_*`
+/// on macOS. Help, version and errors in a requested subcommand print and exit.
+pub fn parse() -> Option<Command> {
+    match Cli::try_parse() {
+        Ok(cli) => cli.command,
+        Err(e)
+            if subcommand_requested()
+                || matches!(e.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) =>
+        {
+            e.exit()
+        }
+        Err(_) => None,
+    }
+}
+
+fn subcommand_requested() -> bool {
+    std::env::args_os().nth(1).is_some_and(|arg| {
+        Cli::command()
+            .find_subcommand(arg.to_string_lossy().as_ref())
+            .is_some()
+    })
+}
+
+pub fn run(command: Command) -> ExitCode {
+    let result = match command {
+        Command::DeviceKey(cmd) => run_device_key(cmd),
+        Command::Passkeys(cmd) => run_passkeys(cmd),
+        Command::Ssh(cmd) => run_ssh(cmd),
+        Command::HmacSecret(cmd) => run_hmac_secret(cmd),
+        Command::HealthCheck { pin, json } => {
+            pin.read().and_then(|pin| run_health_check(pin, json))
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_device_key(command: DeviceKeyCommand) -> Result<(), String> {
+    match command {
+        DeviceKeyCommand::PublicKey { out, hex } => {
+            let key = io::get_device_public_key().map_err(|e| e.to_string())?;
+            let output = if hex {
+                format!("{}\n", hex::encode_upper(&key))
+            } else {
+                io::device_public_key_pem(&key)
+            };
+            write_output(out, &output)
+        }
+        DeviceKeyCommand::Sign { challenge, file } => {
+            let challenge = match (challenge, file) {
+                (_, Some(path)) => std::fs::read(&path)
+                    .map_err(|e| format!("Could not read {}: {}", path.display(), e))?,
+                (Some(hex_str), None) => {
+                    hex::decode(hex_str.trim()).map_err(|e| format!("Invalid hex: {}", e))?
+                }
+                (None, None) => unreachable!("clap requires a challenge"),
+            };
+            let signature = io::sign_device_challenge(&challenge).map_err(|e| e.to_string())?;
+            println!("{}", hex::encode_upper(signature));
+            Ok(())
+        }
+        DeviceKeyCommand::UploadCert { cert } => {
+            let data = std::fs::read(&cert)
+                .map_err(|e| format!("Could not read {}: {}", cert.display(), e))?;
+            let msg = io::upload_device_certificate(&data).map_err(|e| e.to_string())?;
+            println!("{}", msg);
+            Ok(())
+        }
+        DeviceKeyCommand::Enroll {
+            label,
+            inventory,
+            force,
+        } => {
+            let path = inventory.unwrap_or_else(inventory::default_path);
+            let msg = io::enroll_device(&path, label, force).map_err(|e| e.to_string())?;
+            println!("{}", msg);
+            Ok(())
+        }
+        DeviceKeyCommand::Verify { inventory } => {
+            let path = inventory.unwrap_or_else(inventory::default_path);
+            let result = io::verify_genuine_device(&path).map_err(|e| e.to_string())?;
+            match &result.label {
+                Some(label) => println!("Serial: {} ({})", result.serial, label),
+                None => println!("Serial: {}", result.serial),
+            }
+            println!("Status: {:?}", result.status);
+            println!("{}", result.detail);
+            match result.status {
+                GenuineStatus::Genuine => Ok(()),
+                GenuineStatus::NotEnrolled => Err("device is not enrolled".into()),
+                GenuineStatus::PossibleClone => Err("device failed verification".into()),
+            }
+        }
+    }
+}
+
+fn run_passkeys(command: PasskeysCommand) -> Result<(), String> {
+    match command {
+        PasskeysCommand::Export {
+            pin,
+            format,
+            redact,
+            out,
+        } => {
+            let credentials = io::get_credentials(pin.read()?)?;
+            let mut output = io::export_credentials(&credentials, format.into(), redact)
+                .map_err(|e| e.to_string())?;
+            if !output.ends_with('\n') {
+                output.push('\n');
+            }
+            write_output(out, &output)?;
+            eprintln!("Exported {} credentials", credentials.len());
+            Ok(())
+        }
+    }
+}
+
+fn run_ssh(command: SshCommand) -> Result<(), String> {
+    match command {
+        SshCommand::Generate {
+            key_type,
+            resident,
+            verify_required,
+            no_touch,
+            application,
+            user,
+            comment,
+            pin,
+            out,
+        } => {
+            let key_type = SshSkKeyType::from(key_type);
+            let path = out.unwrap_or_else(|| ssh::default_ssh_dir().join(key_type.file_stem()));
+            ssh::ensure_key_path_free(&path).map_err(|e| e.to_string())?;
+            let pin = pin.read()?;
+
+            eprintln!("Touch your key when it blinks.");
+            let key = io::generate_ssh_key(
+                SshKeyOptions {
+                    key_type,
+                    application,
+                    resident,
+                    verify_required,
+                    no_touch,
+                    user,
+                    comment,
+                },
+                pin,
+            )?;
+            let (private, public) = io::write_ssh_key(&key, &path).map_err(|e| e.to_string())?;
+            eprintln!("Wrote {} and {}", private.display(), public.display());
+            print!("{}", key.public_key_line());
+            Ok(())
+        }
+        SshCommand::List { pin } => {
+            let credentials = io::get_credentials(pin.read()?)?;
+            for key in io::ssh_resident_keys(&credentials) {
+                print!("{}", key.public_key_line());
+            }
+            Ok(())
+        }
+        SshCommand::Export { pin, dir } => {
+            let credentials = io::get_credentials(pin.read()?)?;
+            let keys = io::ssh_resident_keys(&credentials);
+            if keys.is_empty() {
+                return Err("no resident SSH keys found".into());
+            }
+            let mut failed = 0;
+            for key in &keys {
+                let path = dir.join(key.resident_file_stem());
+                match io::write_ssh_key(key, &path) {
+                    Ok((private, _)) => {
+                        eprintln!("Saved {} key to {}", key.application, private.display())
+                    }
+                    Err(e) => {
+                        eprintln!("Could not save {} key: {}", key.application, e);
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(format!("{} of {} keys were not saved", failed, keys.len()));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn run_hmac_secret(command: HmacSecretCommand) -> Result<(), String> {
+    match command {
+        HmacSecretCommand::Create {
+            rp_id,
+            pin,
+            resident,
+        } => {
+            let pin = pin.read()?;
+            eprintln!("Touch your key when it blinks.");
+            let credential = io::create_hmac_secret_credential(rp_id, pin, resident)?;
+            println!("{}", credential.credential_id);
+            Ok(())
+        }
+        HmacSecretCommand::Get {
+            rp_id,
+            credential_id,
+            salt,
+            salt2,
+            pin,
+            json,
+        } => {
+            let credential_id = credential_id
+                .map(|id| hex::decode(id.trim()).map_err(|e| format!("Invalid hex: {}", e)))
+                .transpose()?;
+            let salt1 = io::parse_hmac_salt(&salt)?;
+            let salt2 = salt2.as_deref().map(io::parse_hmac_salt).transpose()?;
+            let pin = pin.read()?;
+
+            eprintln!("Touch your key when it blinks.");
+            let output = io::derive_hmac_secret(rp_id, credential_id, salt1, salt2, pin)?;
+
+            if json {
+                let out = serde_json::to_string_pretty(&output).map_err(|e| e.to_string())?;
+                println!("{}", out);
+            } else {
+                println!("Credential ID: {}", output.credential_id);
+                println!("User verified: {}", output.user_verified);
+                println!("Output 1:      {}", output.output1);
+                if let Some(output2) = &output.output2 {
+                    println!("Output 2:      {}", output2);
+                }
+                if output.matching_credentials > 1 {
+                    eprintln!(
+                        "{} resident credentials match this RP; the first one was used",
+                        output.matching_credentials
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn run_health_check(pin: Option<String>, json: bool) -> Result<(), String> {
+    eprintln!("Touch your key each time it blinks.");
+    let report = io::run_health_check(pin)?;
+
+    if json {
+        let out = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        println!("{}", out);
+    } else {
+        println!("Algorithm: {}", report.algorithm);
+        for step in &report.steps {
+            println!(
+                "[{}] {:<14} {:>5} ms  {}",
+                if step.passed { " OK " } else { "FAIL" },
+                step.name,
+                step.duration_ms,
+                step.detail
+            );
+        }
+        println!(
+            "User present: {}, user verified: {}",
+            report.user_present, report.user_verified
+        );
+    }
+
+    if report.passed {
+        Ok(())
+    } else {
+        Err("health check failed".into())
+    }
+}
+
+fn write_output(path: Option<PathBuf>, contents: &str) -> Result<(), String> {
+    match path {
+        Some(path) => std::fs::write(&path, contents)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e)),
+        None => {
+            print!("{}", contents);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Application IDs and APDU constants for the pico-keys applets reachable over PC/SC.
+#![allow(unused)]
+
+/// FIDO U2F/CTAP over CCID (pico-fido)
+pub const FIDO_AID: &[u8] = &[0xA0, 0x00, 0x00, 0x06, 0x47, 0x2F, 0x00, 0x01];
+/// OpenPGP card (pico-openpgp, also bundled in pico-fido)
+pub const OPENPGP_AID: &[u8] = &[0xD2, 0x76, 0x00, 0x01, 0x24, 0x01];
+/// SmartCard-HSM (pico-hsm)
+pub const SC_HSM_AID: &[u8] = &[
+    0xE8, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x81, 0xC3, 0x1F, 0x02, 0x01,
+];
+/// YKOATH (TOTP/HOTP)
+pub const OATH_AID: &[u8] = &[0xA0, 0x00, 0x00, 0x05, 0x27, 0x21, 0x01];
+/// Yubico OTP slots
+pub const OTP_AID: &[u8] = &[0xA0, 0x00, 0x00, 0x05, 0x27, 0x20, 0x01];
+/// Yubico-compatible management applet
+pub const MANAGEMENT_AID: &[u8] = &[0xA0, 0x00, 0x00, 0x05, 0x27, 0x47, 0x11, 0x17];
+
+/// P2 for SELECT: first or only occurrence
+pub const APDU_P2_FIRST_OCCURRENCE: u8 = 0x00;
+
+/// GET RESPONSE, used when the card answers 61xx
+pub const APDU_INS_GET_RESPONSE: u8 = 0xC0;
+
+/// CLA bit for command chaining
+pub const APDU_CLA_CHAINING: u8 = 0x10;
+
+/// Largest data field sent in one short APDU
+pub const APDU_MAX_DATA_LEN: usize = 255;
+
+pub const SW_SUCCESS: u16 = 0x9000;
+/// SW1 of "more data available", SW2 holds the remaining length
+pub const SW1_BYTES_AVAILABLE: u8 = 0x61;
+pub const SW_FILE_NOT_FOUND: u16 = 0x6A82;
@@ -0,0 +1,112 @@
+//! Access to the pico-keys applets over PC/SC.
+//!
+//! The rescue applet has its own connection code; this module covers the ISO 7816 applets that
+//! pico-fido, pico-hsm and pico-openpgp expose through CCID: selecting them, sending APDUs with
+//! command chaining, and collecting 61xx responses with GET RESPONSE.
+
+pub mod constants;
+pub mod probe;
+pub mod tlv;
+
+use crate::device::applets::constants::*;
+use crate::device::error::PFError;
+use crate::device::reader;
+use crate::device::rescue::constants::{APDU_CLA_ISO, APDU_INS_SELECT, APDU_P1_SELECT_BY_DF_NAME};
+
+/// A connection to the smart card reader of the current key.
+pub struct AppletCard {
+    card: pcsc::Card,
+    /// Instruction used to fetch the rest of a 61xx response
+    remaining_ins: u8,
+}
+
+impl AppletCard {
+    /// Connects to the reader of the current key, the same one the rescue applet uses.
+    pub fn connect() -> Result<Self, PFError> {
+        let card = reader::connect()?;
+        Ok(Self {
+            card,
+            remaining_ins: APDU_INS_GET_RESPONSE,
+        })
+    }
+
+    /// Uses `ins` instead of GET RESPONSE to read the rest of long responses, as YKOATH does.
+    pub fn with_send_remaining(mut self, ins: u8) -> Self {
+        self.remaining_ins = ins;
+        self
+    }
+
+    /// Sends one APDU and returns the response data and status word. Data longer than a short
+    /// APDU is chained, and 61xx responses are read to the end.
+    pub fn transmit(
+        &self,
+        cla: u8,
+        ins: u8,
+        p1: u8,
+        p2: u8,
+        data: &[u8],
+    ) -> Result<(Vec<u8>, u16), PFError> {
+        let mut chunks: Vec<&[u8]> = data.chunks(APDU_MAX_DATA_LEN).collect();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+
+        let mut rx_buf = [0; pcsc::MAX_BUFFER_SIZE];
+        let last = chunks.len() - 1;
+        let mut response = Vec::new();
+        let mut sw = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            let cla = if i < last {
+                cla | APDU_CLA_CHAINING
+            } else {
+                cla
+            };
+            let mut apdu = vec![cla, ins, p1, p2];
+            if !chunk.is_empty() {
+                apdu.push(chunk.len() as u8);
+                apdu.extend_from_slice(chunk);
+            }
+            let rx = self.card.transmit(&apdu, &mut rx_buf)?;
+            (response, sw) = split_status(rx)?;
+            if i < last && sw != SW_SUCCESS {
+                return Ok((response, sw));
+            }
+        }
+
+        while (sw >> 8) as u8 == SW1_BYTES_AVAILABLE {
+            let apdu = [APDU_CLA_ISO, self.remaining_ins, 0x00, 0x00, sw as u8];
+            let rx = self.card.transmit(&apdu, &mut rx_buf)?;
+            let (more, next_sw) = split_status(rx)?;
+            response.extend(more);
+            sw = next_sw;
+        }
+
+        Ok((response, sw))
+    }
+
+    /// Selects an applet by AID and returns its select response.
+    pub fn select(&self, aid: &[u8]) -> Result<Vec<u8>, PFError> {
+        self.try_select(aid)?
+            .ok_or_else(|| PFError::Device(format!("Applet {} not found", hex::encode_upper(aid))))
+    }
+
+    /// Selects an applet by AID. Returns `None` if the card does not have it.
+    pub fn try_select(&self, aid: &[u8]) -> Result<Option<Vec<u8>>, PFError> {
+        let (response, sw) = self.transmit(
+            APDU_CLA_ISO,
+            APDU_INS_SELECT,
+            APDU_P1_SELECT_BY_DF_NAME,
+            APDU_P2_FIRST_OCCURRENCE,
+            aid,
+        )?;
+        Ok((sw == SW_SUCCESS).then_some(response))
+    }
+}
+
+fn split_status(rx: &[u8]) -> Result<(Vec<u8>, u16), PFError> {
+    if rx.len() < 2 {
+        return Err(PFError::Device("Short response from card".into()));
+    }
+    let (data, sw) = rx.split_at(rx.len() - 2);
+    Ok((data.to_vec(), u16::from_be_bytes([sw[0], sw[1]])))
+}
@@ -0,0 +1,81 @@
+//! Detects which pico-keys firmware is running by selecting each known applet.
+
+use super::AppletCard;
+use super::constants::*;
+use crate::device::error::PFError;
+use crate::device::fido::hid;
+use crate::device::rescue::{self, constants::RESCUE_AID};
+use crate::device::types::{DeviceCapabilities, PicoApplet, PicoFirmware};
+
+const APPLETS: &[(PicoApplet, &[u8])] = &[
+    (PicoApplet::Fido, FIDO_AID),
+    (PicoApplet::OpenPgp, OPENPGP_AID),
+    (PicoApplet::SmartCardHsm, SC_HSM_AID),
+    (PicoApplet::Oath, OATH_AID),
+    (PicoApplet::Otp, OTP_AID),
+    (PicoApplet::Management, MANAGEMENT_AID),
+    (PicoApplet::Rescue, RESCUE_AID),
+];
+
+fn firmware(applets: &[PicoApplet], ctaphid: bool) -> PicoFirmware {
+    if applets.contains(&PicoApplet::SmartCardHsm) {
+        PicoFirmware::Hsm
+    } else if ctaphid || applets.contains(&PicoApplet::Fido) {
+        PicoFirmware::Fido
+    } else if applets.contains(&PicoApplet::OpenPgp) {
+        PicoFirmware::OpenPgp
+    } else {
+        PicoFirmware::Unknown
+    }
+}
+
+pub fn probe_capabilities() -> Result<DeviceCapabilities, PFError> {
+    let fido_devices = hid::list_devices().unwrap_or_default();
+    let hid_present = !fido_devices.is_empty();
+
+    let mut applets = Vec::new();
+    let mut serial = None;
+    match AppletCard::connect() {
+        Ok(card) => {
+            for (applet, aid) in APPLETS {
+                match card.try_select(aid) {
+                    Ok(Some(data)) => {
+                        if *applet == PicoApplet::Rescue {
+                            serial = rescue::serial_from_select_data(&data);
+                        }
+                        applets.push(*applet);
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Selecting {} failed: {}", applet.name(), e),
+                }
+            }
+        }
+        Err(PFError::NoDevice) if hid_present => {}
+        Err(e) if hid_present => log::warn!("PC/SC probe failed: {}", e),
+        Err(e) => return Err(e),
+    }
+
+    // CTAPHID only counts when it belongs to the probed key: a FIDO HID device with the same USB
+    // serial number, or any FIDO HID device when there is no pico-keys reader to compare with.
+    let ctaphid = match &serial {
+        Some(serial) => fido_devices.iter().any(|d| {
+            d.serial_number
+                .as_deref()
+                .is_some_and(|s| s.eq_ignore_ascii_case(serial))
+        }),
+        None => hid_present && applets.is_empty(),
+    };
+
+    let firmware = firmware(&applets, ctaphid);
+    log::info!(
+        "Detected {} firmware (CTAPHID: {}, applets: {:?})",
+        firmware.name(),
+        ctaphid,
+        applets
+    );
+    Ok(DeviceCapabilities {
+        firmware,
+        applets,
+        ctaphid,
+    })
+}
@@ -0,0 +1,145 @@
+//! TLV encoding used by the applets: Simple-TLV (one byte tags) for YKOATH and management, and
+//! BER-TLV (multi-byte tags, nested templates) for the OpenPGP card.
+
+use crate::device::error::PFError;
+
+/// Encodes one TLV with a BER length.
+pub fn encode(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    match value.len() {
+        len if len < 0x80 => out.push(len as u8),
+        len if len <= 0xFF => out.extend_from_slice(&[0x81, len as u8]),
+        len => {
+            out.push(0x82);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(value);
+    out
+}
+
+/// Splits `data` into its TLVs, in order. Tags may repeat.
+pub fn parse(mut data: &[u8]) -> Result<Vec<(u8, Vec<u8>)>, PFError> {
+    let truncated = || PFError::Device("Truncated TLV in card response".into());
+    let mut items = Vec::new();
+    while let [tag, rest @ ..] = data {
+        let (len, rest) = match rest {
+            [0x81, len, rest @ ..] => (*len as usize, rest),
+            [0x82, hi, lo, rest @ ..] => (u16::from_be_bytes([*hi, *lo]) as usize, rest),
+            [len, rest @ ..] if *len < 0x80 => (*len as usize, rest),
+            _ => return Err(truncated()),
+        };
+        if rest.len() < len {
+            return Err(truncated());
+        }
+        items.push((*tag, rest[..len].to_vec()));
+        data = &rest[len..];
+    }
+    Ok(items)
+}
+
+/// The value of the first TLV with `tag`.
+pub fn find(items: &[(u8, Vec<u8>)], tag: u8) -> Option<&[u8]> {
+    items
+        .iter()
+        .find(|(t, _)| *t == tag)
+        .map(|(_, v)| v.as_slice())
+}
+
+/// Splits `data` into BER-TLVs with tags of up to two bytes, e.g. `0x5F50`. Constructed values
+/// are returned as-is; parse them again to walk the template.
+pub fn parse_ber(mut data: &[u8]) -> Result<Vec<(u16, Vec<u8>)>, PFError> {
+    let truncated = || PFError::Device("Truncated TLV in card response".into());
+    let mut items = Vec::new();
+    while let [first, rest @ ..] = data {
+        // 00 and FF are padding between objects
+        if *first == 0x00 || *first == 0xFF {
+            data = rest;
+            continue;
+        }
+        let (tag, rest) = if first & 0x1F == 0x1F {
+            let [second, rest @ ..] = rest else {
+                return Err(truncated());
+            };
+            (u16::from_be_bytes([*first, *second]), rest)
+        } else {
+            (*first as u16, rest)
+        };
+        let (len, rest) = match rest {
+            [0x81, len, rest @ ..] => (*len as usize, rest),
+            [0x82, hi, lo, rest @ ..] => (u16::from_be_bytes([*hi, *lo]) as usize, rest),
+            [len, rest @ ..] if *len < 0x80 => (*len as usize, rest),
+            _ => return Err(truncated()),
+        };
+        if rest.len() < len {
+            return Err(truncated());
+        }
+        items.push((tag, rest[..len].to_vec()));
+        data = &rest[len..];
+    }
+    Ok(items)
+}
+
+/// The value of the first BER-TLV with `tag`.
+pub fn find_ber(items: &[(u16, Vec<u8>)], tag: u16) -> Option<&[u8]> {
+    items
+        .iter()
+        .find(|(t, _)| *t == tag)
+        .map(|(_, v)| v.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_uses_short_and_long_lengths() {
+        assert_eq!(encode(0x71, b"ab"), [0x71, 0x02, b'a', b'b']);
+        let long = encode(0x73, &[0u8; 0x90]);
+        assert_eq!(long[..3], [0x73, 0x81, 0x90]);
+        assert_eq!(long.len(), 3 + 0x90);
+        let longer = encode(0x73, &[0u8; 0x123]);
+        assert_eq!(longer[..4], [0x73, 0x82, 0x01, 0x23]);
+    }
+
+    #[test]
+    fn parse_round_trips_encode() {
+        let mut data = encode(0x71, b"name");
+        data.extend(encode(0x75, &[0xAA; 200]));
+        data.extend(encode(0x71, b""));
+        let items = parse(&data).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(find(&items, 0x71), Some(&b"name"[..]));
+        assert_eq!(find(&items, 0x75).map(<[u8]>::len), Some(200));
+        assert_eq!(find(&items, 0x76), None);
+    }
+
+    #[test]
+    fn parse_rejects_truncated_data() {
+        assert!(parse(&[0x71, 0x05, 0x01]).is_err());
+        assert!(parse(&[0x71, 0x81]).is_err());
+        assert!(parse(&[0x71, 0x83, 0x00, 0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn parse_ber_reads_two_byte_tags_and_skips_padding() {
+        let data = [
+            0x00, 0x5F, 0x50, 0x02, b'h', b'i', 0xFF, 0x4F, 0x01, 0xD2, 0x65, 0x03, 0x5B, 0x01,
+            b'x',
+        ];
+        let items = parse_ber(&data).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(find_ber(&items, 0x5F50), Some(&b"hi"[..]));
+        assert_eq!(find_ber(&items, 0x4F), Some(&[0xD2][..]));
+
+        let nested = parse_ber(find_ber(&items, 0x65).unwrap()).unwrap();
+        assert_eq!(find_ber(&nested, 0x5B), Some(&b"x"[..]));
+    }
+
+    #[test]
+    fn parse_ber_rejects_truncated_data() {
+        assert!(parse_ber(&[0x5F]).is_err());
+        assert!(parse_ber(&[0x5F, 0x50, 0x04, 0x00]).is_err());
+        assert!(parse_ber(&[0x4F, 0x82, 0x01]).is_err());
+    }
+}
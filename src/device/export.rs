@@ -0,0 +1,211 @@
+//! Export of the stored passkey inventory for account audits.
+
+use crate::device::{error::PFError, fido::cose, types::StoredCredential};
+use base64::Engine;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// One exported credential. With redaction the user fields are left out entirely.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CredentialRecord {
+    rp_id: String,
+    rp_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_display_name: Option<String>,
+    /// User handle, hex encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_id: Option<String>,
+    credential_id_hex: String,
+    credential_id_base64url: String,
+    algorithm: String,
+    cred_protect: Option<u8>,
+}
+
+const CSV_HEADER: &[&str] = &[
+    "rp_id",
+    "rp_name",
+    "user_name",
+    "user_display_name",
+    "user_id",
+    "credential_id_hex",
+    "credential_id_base64url",
+    "algorithm",
+    "cred_protect",
+];
+
+fn record(cred: &StoredCredential, redact_user: bool) -> Result<CredentialRecord, PFError> {
+    let credential_id = hex::decode(&cred.credential_id)
+        .map_err(|e| PFError::Io(format!("Invalid credential ID: {}", e)))?;
+    let user = |value: &String| (!redact_user).then(|| value.clone());
+
+    Ok(CredentialRecord {
+        rp_id: cred.rp_id.clone(),
+        rp_name: cred.rp_name.clone(),
+        user_name: user(&cred.user_name),
+        user_display_name: user(&cred.user_display_name),
+        user_id: user(&cred.user_id),
+        credential_id_hex: cred.credential_id.clone(),
+        credential_id_base64url: base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(credential_id),
+        algorithm: cose::algorithm_name(cred.public_key.as_ref().and_then(|k| k.algorithm)),
+        cred_protect: cred.cred_protect,
+    })
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A free-text field. RP and user names come from websites, so text a spreadsheet would run as
+/// a formula gets a `'` prefix. Identifiers are left alone: base64url IDs may start with `-`.
+fn csv_text_field(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        csv_field(&format!("'{}", value))
+    } else {
+        csv_field(value)
+    }
+}
+
+pub fn export_credentials(
+    credentials: &[StoredCredential],
+    format: ExportFormat,
+    redact_user: bool,
+) -> Result<String, PFError> {
+    let records = credentials
+        .iter()
+        .map(|cred| record(cred, redact_user))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&records)
+            .map_err(|e| PFError::Io(format!("Could not encode export: {}", e))),
+        ExportFormat::Csv => {
+            let mut out = CSV_HEADER.join(",");
+            out.push_str("\r\n");
+            for r in records {
+                let line = [
+                    csv_text_field(&r.rp_id),
+                    csv_text_field(&r.rp_name),
+                    csv_text_field(&r.user_name.unwrap_or_default()),
+                    csv_text_field(&r.user_display_name.unwrap_or_default()),
+                    csv_field(&r.user_id.unwrap_or_default()),
+                    csv_field(&r.credential_id_hex),
+                    csv_field(&r.credential_id_base64url),
+                    csv_field(&r.algorithm),
+                    csv_field(&r.cred_protect.map(|l| l.to_string()).unwrap_or_default()),
+                ];
+                out.push_str(&line.join(","));
+                out.push_str("\r\n");
+            }
+            Ok(out)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credential() -> StoredCredential {
+        StoredCredential {
+            rp_id: "example.com".into(),
+            rp_name: "Example, Inc.".into(),
+            user_name: "alice".into(),
+            user_display_name: "Alice \"A\" Smith".into(),
+            user_id: "0102".into(),
+            credential_id: "FBFF".into(),
+            public_key: None,
+            cred_protect: Some(2),
+            large_blob_key: None,
+        }
+    }
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn text_fields_neutralize_formulas() {
+        assert_eq!(csv_text_field("=1+1"), "'=1+1");
+        assert_eq!(csv_text_field("+49"), "'+49");
+        assert_eq!(csv_text_field("-x"), "'-x");
+        assert_eq!(csv_text_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_text_field("\tcell"), "'\tcell");
+        assert_eq!(csv_text_field("\rcell"), "\"'\rcell\"");
+        assert_eq!(
+            csv_text_field("=HYPERLINK(\"x\",\"y\")"),
+            "\"'=HYPERLINK(\"\"x\"\",\"\"y\"\")\""
+        );
+        assert_eq!(csv_text_field("a=b"), "a=b");
+    }
+
+    #[test]
+    fn csv_export() {
+        let csv = export_credentials(&[credential()], ExportFormat::Csv, false).unwrap();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert_eq!(
+            lines[1],
+            "example.com,\"Example, Inc.\",alice,\"Alice \"\"A\"\" Smith\",0102,FBFF,-_8,Unspecified,2"
+        );
+        assert_eq!(lines[2], "");
+    }
+
+    #[test]
+    fn identifiers_are_not_treated_as_formulas() {
+        let mut cred = credential();
+        cred.rp_name = "=cmd".into();
+        cred.credential_id = "F8FFFF".into();
+        let csv = export_credentials(&[cred], ExportFormat::Csv, false).unwrap();
+        let line = csv.split("\r\n").nth(1).unwrap();
+        assert!(line.starts_with("example.com,'=cmd,"));
+        assert!(line.contains(",F8FFFF,-P__,"));
+    }
+
+    #[test]
+    fn redaction_drops_user_fields() {
+        let csv = export_credentials(&[credential()], ExportFormat::Csv, true).unwrap();
+        assert!(csv.contains("example.com,\"Example, Inc.\",,,,FBFF,"));
+
+        let json = export_credentials(&[credential()], ExportFormat::Json, true).unwrap();
+        let records: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let record = &records[0];
+        assert_eq!(record["rpId"], "example.com");
+        assert_eq!(record["credentialIdBase64url"], "-_8");
+        assert!(record.get("userName").is_none());
+        assert!(record.get("userId").is_none());
+    }
+
+    #[test]
+    fn invalid_credential_id_is_an_error() {
+        let mut cred = credential();
+        cred.credential_id = "XYZ".into();
+        assert!(export_credentials(&[cred], ExportFormat::Json, false).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Host-side helpers for enterprise attestation provisioning.
+//!
+//! pico-fido can generate a CSR for its enterprise attestation key, but the certificate has to be
+//! issued by the organisation's own CA. These helpers sign that CSR with a local CA key file so the
+//! resulting certificate can be uploaded back to the device.
+
+use crate::device::error::PFError;
+use rand::RngExt;
+use rcgen::{
+    CertificateSigningRequestParams, CustomExtension, DnType, DnValue, IsCa, Issuer, KeyPair,
+    SerialNumber,
+};
+use std::path::Path;
+use time::{Duration, OffsetDateTime};
+
+/// OID of the FIDO `id-fido-gen-ce-aaguid` certificate extension.
+const OID_FIDO_GEN_CE_AAGUID: &[u64] = &[1, 3, 6, 1, 4, 1, 45724, 1, 1, 4];
+
+/// Returns the Common Name of a DER encoded CSR, if it has one.
+pub fn csr_common_name(csr_der: &[u8]) -> Result<Option<String>, PFError> {
+    let csr = CertificateSigningRequestParams::from_der(&csr_der.into())
+        .map_err(|e| PFError::Io(format!("Invalid CSR: {}", e)))?;
+
+    let name = match csr.params.distinguished_name.get(&DnType::CommonName) {
+        Some(DnValue::Utf8String(s)) => Some(s.clone()),
+        Some(DnValue::PrintableString(s)) => Some(s.as_str().to_string()),
+        Some(DnValue::Ia5String(s)) => Some(s.as_str().to_string()),
+        _ => None,
+    };
+
+    Ok(name)
+}
+
+/// Signs a device CSR with the CA private key and certificate stored at the given PEM files.
+///
+/// The issued certificate is a leaf certificate valid for `validity_days` from now. When the
+/// device AAGUID is known it is embedded in the `id-fido-gen-ce-aaguid` extension, as required
+/// for FIDO attestation certificates. Returns the DER encoded certificate.
+pub fn sign_csr(
+    csr_der: &[u8],
+    ca_key_path: &Path,
+    ca_cert_path: &Path,
+    validity_days: u32,
+    aaguid: Option<&[u8]>,
+) -> Result<Vec<u8>, PFError> {
+    let ca_key_pem = std::fs::read_to_string(ca_key_path).map_err(|e| {
+        PFError::Io(format!(
+            "Could not read CA key {}: {}",
+            ca_key_path.display(),
+            e
+        ))
+    })?;
+    let ca_cert_pem = std::fs::read_to_string(ca_cert_path).map_err(|e| {
+        PFError::Io(format!(
+            "Could not read CA certificate {}: {}",
+            ca_cert_path.display(),
+            e
+        ))
+    })?;
+
+    let ca_key = KeyPair::from_pem(&ca_key_pem)
+        .map_err(|e| PFError::Io(format!("Invalid CA private key: {}", e)))?;
+    let issuer = Issuer::from_ca_cert_pem(&ca_cert_pem, ca_key)
+        .map_err(|e| PFError::Io(format!("Invalid CA certificate: {}", e)))?;
+
+    let mut csr = CertificateSigningRequestParams::from_der(&csr_der.into())
+        .map_err(|e| PFError::Io(format!("Invalid CSR: {}", e)))?;
+
+    let now = OffsetDateTime::now_utc();
+    csr.params.not_before = now - Duration::minutes(5);
+    csr.params.not_after = now + Duration::days(validity_days as i64);
+    csr.params.is_ca = IsCa::ExplicitNoCa;
+    csr.params.use_authority_key_identifier_extension = true;
+
+    let mut serial = [0u8; 16];
+    rand::rng().fill(&mut serial);
+    serial[0] &= 0x7F; // Keep the serial number positive
+    csr.params.serial_number = Some(SerialNumber::from_slice(&serial));
+
+    if let Some(aaguid) = aaguid {
+        // Extension value is an OCTET STRING wrapping the 16 byte AAGUID.
+        let mut content = vec![0x04, aaguid.len() as u8];
+        content.extend_from_slice(aaguid);
+        csr.params
+            .custom_extensions
+            .push(CustomExtension::from_oid_content(
+                OID_FIDO_GEN_CE_AAGUID,
+                content,
+            ));
+    }
+
+    let cert = csr
+        .signed_by(&issuer)
+        .map_err(|e| PFError::Io(format!("Failed to sign CSR: {}", e)))?;
+
+    log::info!(
+        "Issued enterprise attestation certificate ({} bytes)",
+        cert.der().len()
+    );
+    Ok(cert.der().to_vec())
+}
@@ -57,6 +57,16 @@ pub enum ClientPinSubCommand {
     GetPinUvAuthTokenUsingPinWithPermissions = 0x08,
 }
 
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientPinResponseKey {
+    KeyAgreement = 0x01,
+    PinUvAuthToken = 0x02,
+    PinRetries = 0x03,
+    PowerCycleState = 0x04,
+    UvRetries = 0x05,
+}
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MakeCredentialParam {
@@ -115,6 +125,81 @@ pub enum ConfigSubCommand {
     VendorPrototype = 0xFF,
 }
 
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredMgmtSubCommand {
+    GetCredsMetadata = 0x01,
+    EnumerateRpsBegin = 0x02,
+    EnumerateRpsGetNextRp = 0x03,
+    EnumerateCredentialsBegin = 0x04,
+    EnumerateCredentialsGetNextCredential = 0x05,
+    DeleteCredential = 0x06,
+    UpdateUserInformation = 0x07,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredMgmtParam {
+    SubCommand = 0x01,
+    SubCommandParams = 0x02,
+    PinUvAuthProtocol = 0x03,
+    PinUvAuthParam = 0x04,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredMgmtSubCommandParam {
+    RpIdHash = 0x01,
+    CredentialId = 0x02,
+    User = 0x03,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredMgmtResponseKey {
+    ExistingResidentCredentialsCount = 0x01,
+    MaxPossibleRemainingResidentCredentialsCount = 0x02,
+    Rp = 0x03,
+    RpIdHash = 0x04,
+    TotalRps = 0x05,
+    User = 0x06,
+    CredentialId = 0x07,
+    PublicKey = 0x08,
+    TotalCredentials = 0x09,
+    CredProtect = 0x0A,
+    LargeBlobKey = 0x0B,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LargeBlobsParam {
+    Get = 0x01,
+    Set = 0x02,
+    Offset = 0x03,
+    Length = 0x04,
+    PinUvAuthParam = 0x05,
+    PinUvAuthProtocol = 0x06,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LargeBlobEntryKey {
+    Ciphertext = 0x01,
+    Nonce = 0x02,
+    OrigSize = 0x03,
+}
+
+/// getInfo response keys not decoded by ctap-hid-fido2 (CTAP 2.2)
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetInfoResponseKey {
+    MaxMsgSize = 0x05,
+    MaxSerializedLargeBlobArray = 0x0B,
+    RemainingDiscoverableCredentials = 0x14,
+    PinComplexityPolicy = 0x1B,
+    PinComplexityPolicyUrl = 0x1C,
+}
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VendorParam {
@@ -210,6 +295,12 @@ pub enum MseSubCommand {
     KeyAgreement = 0x01,
 }
 
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnlockSubCommand {
+    Unlock = 0x01,
+}
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EnterpriseAttestationSubCommand {
@@ -288,6 +379,32 @@ pub enum CoseAlgorithm {
     ESB512 = -268,
 }
 
+impl CoseAlgorithm {
+    /// Helper to convert the raw COSE algorithm identifier back to Enum
+    pub fn from_i32(val: i32) -> Option<Self> {
+        match val {
+            -7 => Some(Self::ES256),
+            -8 => Some(Self::EdDSA),
+            -9 => Some(Self::ESP256),
+            -19 => Some(Self::Ed25519),
+            -25 => Some(Self::EcdhEsHkdf256),
+            -35 => Some(Self::ES384),
+            -36 => Some(Self::ES512),
+            -47 => Some(Self::ES256K),
+            -51 => Some(Self::ESP384),
+            -52 => Some(Self::ESP512),
+            -53 => Some(Self::Ed448),
+            -257 => Some(Self::RS256),
+            -258 => Some(Self::RS384),
+            -259 => Some(Self::RS512),
+            -265 => Some(Self::ESB256),
+            -267 => Some(Self::ESB384),
+            -268 => Some(Self::ESB512),
+            _ => None,
+        }
+    }
+}
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CoseCurve {
@@ -304,6 +421,26 @@ pub enum CoseCurve {
     BP512R1 = 11,
 }
 
+impl CoseCurve {
+    /// Helper to convert the raw COSE curve identifier back to Enum
+    pub fn from_u8(val: u8) -> Option<Self> {
+        match val {
+            1 => Some(Self::P256),
+            2 => Some(Self::P384),
+            3 => Some(Self::P521),
+            4 => Some(Self::X25519),
+            5 => Some(Self::X448),
+            6 => Some(Self::Ed25519),
+            7 => Some(Self::Ed448),
+            8 => Some(Self::P256K1),
+            9 => Some(Self::BP256R1),
+            10 => Some(Self::BP384R1),
+            11 => Some(Self::BP512R1),
+            _ => None,
+        }
+    }
+}
+
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CoseKeyParam {
@@ -380,6 +517,8 @@ pub const MAX_CREDBLOB_LENGTH: usize = 128;
 pub const MAX_MSG_SIZE: usize = 1024;
 pub const MAX_FRAGMENT_LENGTH: usize = MAX_MSG_SIZE - 64;
 pub const MAX_LARGE_BLOB_SIZE: usize = 2048;
+/// Truncated SHA-256 appended to the serialized large-blob array
+pub const LARGE_BLOB_TRAILER_LEN: usize = 16;
 
 pub const AAGUID: [u8; 16] = [
     0x89, 0xFB, 0x94, 0xB7, 0x06, 0xC9, 0x36, 0x73, 0x9B, 0x7E, 0x30, 0x52, 0x6D, 0x96, 0x81, 0x45,
@@ -0,0 +1,272 @@
+//! Decoding of COSE public keys returned by the authenticator, and export as PEM/JWK.
+
+use super::constants::{CoseAlgorithm, CoseCurve, CoseKeyParam};
+use crate::device::{error::PFError, pem, types::CredentialPublicKey};
+use base64::Engine;
+use serde_cbor_2::Value;
+
+/// COSE key types (RFC 9053)
+const COSE_KTY_OKP: i32 = 1;
+const COSE_KTY_EC2: i32 = 2;
+
+/// DER SubjectPublicKeyInfo prefixes, followed by the public key bytes.
+const SPKI_PREFIX_P256: &[u8] = &[
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01, 0x06, 0x08, 0x2A,
+    0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+const SPKI_PREFIX_P384: &[u8] = &[
+    0x30, 0x76, 0x30, 0x10, 0x06, 0x07, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01, 0x06, 0x05, 0x2B,
+    0x81, 0x04, 0x00, 0x22, 0x03, 0x62, 0x00,
+];
+const SPKI_PREFIX_P256K1: &[u8] = &[
+    0x30, 0x56, 0x30, 0x10, 0x06, 0x07, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01, 0x06, 0x05, 0x2B,
+    0x81, 0x04, 0x00, 0x0A, 0x03, 0x42, 0x00,
+];
+const SPKI_PREFIX_ED25519: &[u8] = &[
+    0x30, 0x2A, 0x30, 0x05, 0x06, 0x03, 0x2B, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Decodes a COSE_Key map (credentialManagement `publicKey`, makeCredential attested data).
+pub fn decode_cose_key(value: &Value) -> Result<CredentialPublicKey, PFError> {
+    let Value::Map(map) = value else {
+        return Err(PFError::Io("COSE key is not a CBOR map".into()));
+    };
+
+    let int = |param: CoseKeyParam| match map.get(&Value::Integer(param as i128)) {
+        Some(Value::Integer(i)) => Some(*i),
+        _ => None,
+    };
+    let bytes = |param: CoseKeyParam| match map.get(&Value::Integer(param as i128)) {
+        Some(Value::Bytes(b)) => Some(b.clone()),
+        _ => None,
+    };
+
+    let key_type = int(CoseKeyParam::Kty)
+        .ok_or_else(|| PFError::Io("COSE key has no key type".into()))? as i32;
+    let x = bytes(CoseKeyParam::X)
+        .ok_or_else(|| PFError::Io("COSE key has no public key data".into()))?;
+
+    Ok(CredentialPublicKey {
+        key_type,
+        algorithm: int(CoseKeyParam::Alg).map(|a| a as i32),
+        curve: int(CoseKeyParam::Crv).map(|c| c as u8),
+        x: hex::encode_upper(x),
+        y: bytes(CoseKeyParam::Y).map(hex::encode_upper),
+    })
+}
+
+pub fn algorithm_name(algorithm: Option<i32>) -> String {
+    match algorithm {
+        Some(alg) => match CoseAlgorithm::from_i32(alg) {
+            Some(known) => format!("{:?} ({})", known, alg),
+            None => format!("Unknown ({})", alg),
+        },
+        None => "Unspecified".into(),
+    }
+}
+
+pub fn curve_name(curve: Option<u8>) -> String {
+    match curve.and_then(CoseCurve::from_u8) {
+        Some(CoseCurve::P256) => "P-256".into(),
+        Some(CoseCurve::P384) => "P-384".into(),
+        Some(CoseCurve::P521) => "P-521".into(),
+        Some(CoseCurve::P256K1) => "secp256k1".into(),
+        Some(known) => format!("{:?}", known),
+        None => match curve {
+            Some(crv) => format!("Unknown ({})", crv),
+            None => "Unspecified".into(),
+        },
+    }
+}
+
+fn key_bytes(key: &CredentialPublicKey) -> Result<(Vec<u8>, Option<Vec<u8>>), PFError> {
+    let x = hex::decode(&key.x).map_err(|e| PFError::Io(format!("Invalid key data: {}", e)))?;
+    let y = key
+        .y
+        .as_ref()
+        .map(hex::decode)
+        .transpose()
+        .map_err(|e| PFError::Io(format!("Invalid key data: {}", e)))?;
+    Ok((x, y))
+}
+
+/// Encodes the key as a PEM `PUBLIC KEY` (SubjectPublicKeyInfo) block.
+pub fn to_pem(key: &CredentialPublicKey) -> Result<String, PFError> {
+    let (x, y) = key_bytes(key)?;
+    let curve = key.curve.and_then(CoseCurve::from_u8);
+
+    let mut spki = match (key.key_type, curve) {
+        (COSE_KTY_EC2, Some(CoseCurve::P256)) => SPKI_PREFIX_P256.to_vec(),
+        (COSE_KTY_EC2, Some(CoseCurve::P384)) => SPKI_PREFIX_P384.to_vec(),
+        (COSE_KTY_EC2, Some(CoseCurve::P256K1)) => SPKI_PREFIX_P256K1.to_vec(),
+        (COSE_KTY_OKP, Some(CoseCurve::Ed25519)) => SPKI_PREFIX_ED25519.to_vec(),
+        _ => {
+            return Err(PFError::Io(format!(
+                "PEM export is not supported for {}",
+                curve_name(key.curve)
+            )));
+        }
+    };
+
+    if key.key_type == COSE_KTY_EC2 {
+        // Uncompressed point: 0x04 || x || y
+        spki.push(0x04);
+        spki.extend(x);
+        spki.extend(y.unwrap_or_default());
+    } else {
+        spki.extend(x);
+    }
+
+    Ok(pem::encode("PUBLIC KEY", &spki))
+}
+
+/// Encodes the key as a JSON Web Key (RFC 7517).
+pub fn to_jwk(key: &CredentialPublicKey) -> Result<String, PFError> {
+    let (x, y) = key_bytes(key)?;
+    let b64 = |data: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data);
+
+    let crv = match key.curve.and_then(CoseCurve::from_u8) {
+        Some(CoseCurve::P256) => "P-256",
+        Some(CoseCurve::P384) => "P-384",
+        Some(CoseCurve::P521) => "P-521",
+        Some(CoseCurve::P256K1) => "secp256k1",
+        Some(CoseCurve::Ed25519) => "Ed25519",
+        Some(CoseCurve::Ed448) => "Ed448",
+        _ => {
+            return Err(PFError::Io(format!(
+                "JWK export is not supported for {}",
+                curve_name(key.curve)
+            )));
+        }
+    };
+
+    let mut jwk = serde_json::Map::new();
+    match key.key_type {
+        COSE_KTY_EC2 => {
+            jwk.insert("kty".into(), "EC".into());
+            jwk.insert("crv".into(), crv.into());
+            jwk.insert("x".into(), b64(&x).into());
+            jwk.insert("y".into(), b64(&y.unwrap_or_default()).into());
+        }
+        COSE_KTY_OKP => {
+            jwk.insert("kty".into(), "OKP".into());
+            jwk.insert("crv".into(), crv.into());
+            jwk.insert("x".into(), b64(&x).into());
+        }
+        other => {
+            return Err(PFError::Io(format!(
+                "JWK export is not supported for key type {}",
+                other
+            )));
+        }
+    }
+
+    if let Some(alg) = key.algorithm.and_then(CoseAlgorithm::from_i32) {
+        jwk.insert("alg".into(), format!("{:?}", alg).into());
+    }
+
+    serde_json::to_string_pretty(&serde_json::Value::Object(jwk))
+        .map_err(|e| PFError::Io(format!("Could not encode JWK: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn cose_map(entries: Vec<(CoseKeyParam, Value)>) -> Value {
+        Value::Map(
+            entries
+                .into_iter()
+                .map(|(param, value)| (Value::Integer(param as i128), value))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    fn p256_key() -> CredentialPublicKey {
+        decode_cose_key(&cose_map(vec![
+            (CoseKeyParam::Kty, Value::Integer(COSE_KTY_EC2 as i128)),
+            (
+                CoseKeyParam::Alg,
+                Value::Integer(CoseAlgorithm::ES256 as i128),
+            ),
+            (CoseKeyParam::Crv, Value::Integer(CoseCurve::P256 as i128)),
+            (CoseKeyParam::X, Value::Bytes(vec![0x11; 32])),
+            (CoseKeyParam::Y, Value::Bytes(vec![0x22; 32])),
+        ]))
+        .unwrap()
+    }
+
+    fn ed25519_key() -> CredentialPublicKey {
+        CredentialPublicKey {
+            key_type: COSE_KTY_OKP,
+            algorithm: Some(CoseAlgorithm::EdDSA as i32),
+            curve: Some(CoseCurve::Ed25519 as u8),
+            x: hex::encode_upper([0x33; 32]),
+            y: None,
+        }
+    }
+
+    #[test]
+    fn decodes_cose_key() {
+        let key = p256_key();
+        assert_eq!(key.key_type, COSE_KTY_EC2);
+        assert_eq!(key.algorithm, Some(-7));
+        assert_eq!(key.curve, Some(1));
+        assert_eq!(key.x, "11".repeat(32));
+        assert_eq!(key.y, Some("22".repeat(32)));
+
+        assert!(decode_cose_key(&Value::Integer(1)).is_err());
+        assert!(
+            decode_cose_key(&cose_map(vec![(
+                CoseKeyParam::Kty,
+                Value::Integer(COSE_KTY_EC2 as i128)
+            )]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn p256_pem_is_spki() {
+        let der = pem::decode_or_der(to_pem(&p256_key()).unwrap().as_bytes()).unwrap();
+        assert_eq!(der.len(), SPKI_PREFIX_P256.len() + 65);
+        assert_eq!(der[..SPKI_PREFIX_P256.len()], *SPKI_PREFIX_P256);
+        assert_eq!(der[SPKI_PREFIX_P256.len()], 0x04);
+        assert_eq!(der[SPKI_PREFIX_P256.len() + 1..][..32], [0x11; 32]);
+        assert_eq!(der[SPKI_PREFIX_P256.len() + 33..], [0x22; 32]);
+    }
+
+    #[test]
+    fn ed25519_pem_is_spki() {
+        let der = pem::decode_or_der(to_pem(&ed25519_key()).unwrap().as_bytes()).unwrap();
+        assert_eq!(der[..SPKI_PREFIX_ED25519.len()], *SPKI_PREFIX_ED25519);
+        assert_eq!(der[SPKI_PREFIX_ED25519.len()..], [0x33; 32]);
+    }
+
+    #[test]
+    fn jwk_export() {
+        let jwk: serde_json::Value = serde_json::from_str(&to_jwk(&p256_key()).unwrap()).unwrap();
+        assert_eq!(jwk["kty"], "EC");
+        assert_eq!(jwk["crv"], "P-256");
+        assert_eq!(jwk["alg"], "ES256");
+        let b64 = |data: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data);
+        assert_eq!(jwk["x"], b64(&[0x11; 32]));
+        assert_eq!(jwk["y"], b64(&[0x22; 32]));
+
+        let jwk: serde_json::Value =
+            serde_json::from_str(&to_jwk(&ed25519_key()).unwrap()).unwrap();
+        assert_eq!(jwk["kty"], "OKP");
+        assert_eq!(jwk["crv"], "Ed25519");
+        assert!(jwk.get("y").is_none());
+    }
+
+    #[test]
+    fn unsupported_curves_are_rejected() {
+        let key = CredentialPublicKey {
+            curve: Some(CoseCurve::BP256R1 as u8),
+            ..p256_key()
+        };
+        assert!(to_pem(&key).is_err());
+        assert!(to_jwk(&key).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! pico-fido encryption at rest: enable/disable and unlock over the MSE-secured channel.
+//!
+//! The host secret never travels in the clear. A Manage Security Environment (MSE) key
+//! agreement first establishes a session: the host sends an ephemeral P-256 COSE key, the
+//! device answers with its own, and both sides derive 44 bytes with HKDF-SHA256 over the ECDH
+//! shared secret (no salt, info = host public point). The first 12 bytes are the nonce and the
+//! remaining 32 the ChaCha20-Poly1305 key; the host public point is also the AAD.
+
+use super::constants::*;
+use super::hid::{self, HidTransport};
+use super::{obtain_pin_token, obtain_pin_token_at};
+use crate::device::error::PFError;
+use crate::device::secrets;
+use ctap_hid_fido2::fidokey::pin::Permission;
+use ring::{aead, agreement, hkdf, rand::SystemRandom};
+use serde_cbor_2::{Value, from_slice, to_vec};
+use std::collections::BTreeMap;
+
+const MSE_NONCE_LEN: usize = 12;
+const MSE_KEY_LEN: usize = 32;
+
+struct OkmLen(usize);
+
+impl hkdf::KeyType for OkmLen {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+/// Session keys from an MSE key agreement.
+struct MseSession {
+    key: [u8; MSE_KEY_LEN],
+    nonce: [u8; MSE_NONCE_LEN],
+    host_public: Vec<u8>,
+}
+
+impl MseSession {
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, PFError> {
+        let key = aead::UnboundKey::new(&aead::CHACHA20_POLY1305, &self.key)
+            .map_err(|_| PFError::Io("Invalid MSE session key".into()))?;
+        let mut data = plaintext.to_vec();
+        aead::LessSafeKey::new(key)
+            .seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(self.nonce),
+                aead::Aad::from(self.host_public.as_slice()),
+                &mut data,
+            )
+            .map_err(|_| PFError::Io("Could not encrypt host secret".into()))?;
+        Ok(data)
+    }
+}
+
+/// Sends a vendor CBOR command: `{1: subCommand, 2: subCommandParams}`.
+fn send_vendor_command(
+    transport: &HidTransport,
+    command: VendorCommand,
+    sub_command: u8,
+    sub_params: BTreeMap<Value, Value>,
+) -> Result<Vec<u8>, PFError> {
+    let mut request = BTreeMap::new();
+    request.insert(
+        Value::Integer(VendorParam::VendorCommand as i128),
+        Value::Integer(sub_command as i128),
+    );
+    request.insert(
+        Value::Integer(VendorParam::VendorSubParams as i128),
+        Value::Map(sub_params),
+    );
+
+    let mut payload = vec![command as u8];
+    payload.extend(to_vec(&Value::Map(request)).map_err(|e| PFError::Io(e.to_string()))?);
+
+    transport.send_cbor(CTAP_VENDOR_CBOR_CMD, &payload)
+}
+
+fn key_agreement(transport: &HidTransport) -> Result<MseSession, PFError> {
+    log::debug!("Starting MSE key agreement...");
+
+    let rng = SystemRandom::new();
+    let private_key = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng)
+        .map_err(|_| PFError::Io("Could not generate ephemeral key".into()))?;
+    let host_public = private_key
+        .compute_public_key()
+        .map_err(|_| PFError::Io("Could not compute ephemeral public key".into()))?
+        .as_ref()
+        .to_vec();
+
+    // Uncompressed point: 0x04 || x || y
+    let mut cose_key = BTreeMap::new();
+    cose_key.insert(Value::Integer(CoseKeyParam::Kty as i128), Value::Integer(2));
+    cose_key.insert(
+        Value::Integer(CoseKeyParam::Alg as i128),
+        Value::Integer(CoseAlgorithm::EcdhEsHkdf256 as i128),
+    );
+    cose_key.insert(
+        Value::Integer(CoseKeyParam::Crv as i128),
+        Value::Integer(CoseCurve::P256 as i128),
+    );
+    cose_key.insert(
+        Value::Integer(CoseKeyParam::X as i128),
+        Value::Bytes(host_public[1..33].to_vec()),
+    );
+    cose_key.insert(
+        Value::Integer(CoseKeyParam::Y as i128),
+        Value::Bytes(host_public[33..65].to_vec()),
+    );
+
+    let mut sub_params = BTreeMap::new();
+    sub_params.insert(
+        Value::Integer(VendorSubParam::CoseKey as i128),
+        Value::Map(cose_key),
+    );
+
+    let response = send_vendor_command(
+        transport,
+        VendorCommand::ManageSecurityEnvironment,
+        MseSubCommand::KeyAgreement as u8,
+        sub_params,
+    )
+    .map_err(|e| PFError::Device(format!("MSE key agreement failed: {}", e)))?;
+
+    let device_key = match from_slice(&response) {
+        Ok(Value::Map(map)) => map.get(&Value::Integer(1)).cloned(),
+        _ => None,
+    }
+    .ok_or_else(|| PFError::Device("MSE response has no device key".into()))?;
+    let Value::Map(device_key) = device_key else {
+        return Err(PFError::Device("MSE device key is not a COSE key".into()));
+    };
+    let coordinate = |param: CoseKeyParam| match device_key.get(&Value::Integer(param as i128)) {
+        Some(Value::Bytes(b)) if b.len() == 32 => Ok(b.clone()),
+        _ => Err(PFError::Device("MSE device key is not a P-256 key".into())),
+    };
+
+    let mut device_public = vec![0x04];
+    device_public.extend(coordinate(CoseKeyParam::X)?);
+    device_public.extend(coordinate(CoseKeyParam::Y)?);
+
+    let mut okm = [0u8; MSE_NONCE_LEN + MSE_KEY_LEN];
+    agreement::agree_ephemeral(
+        private_key,
+        &agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, &device_public),
+        |shared| {
+            hkdf::Salt::new(hkdf::HKDF_SHA256, &[])
+                .extract(shared)
+                .expand(&[&host_public], OkmLen(okm.len()))
+                .and_then(|prk| prk.fill(&mut okm))
+        },
+    )
+    .map_err(|_| PFError::Device("MSE key agreement failed".into()))?
+    .map_err(|_| PFError::Io("Could not derive MSE session keys".into()))?;
+
+    let mut nonce = [0u8; MSE_NONCE_LEN];
+    let mut key = [0u8; MSE_KEY_LEN];
+    nonce.copy_from_slice(&okm[..MSE_NONCE_LEN]);
+    key.copy_from_slice(&okm[MSE_NONCE_LEN..]);
+
+    Ok(MseSession {
+        key,
+        nonce,
+        host_public,
+    })
+}
+
+/// HID path of the key with USB serial `serial`. The secret of a key is only ever sent to that
+/// key, whichever other keys are connected or selected.
+fn device_path(serial: &str) -> Result<String, String> {
+    hid::device_path_for_serial(serial).map_err(|e| e.to_string())
+}
+
+fn open_transport(path: &str) -> Result<HidTransport, String> {
+    HidTransport::open_path(path).map_err(|e| format!("Could not open HID transport: {}", e))
+}
+
+/// Enables encryption at rest with the host secret for `serial`, creating it if needed.
+pub(crate) fn enable_encryption(pin: String, serial: String) -> Result<String, String> {
+    log::info!("Enabling encryption at rest...");
+
+    let secret_path = secrets::secret_path(&serial).ok_or(
+        "The serial number of this key could not be read, so no host secret can be tied to it",
+    )?;
+    let path = device_path(&serial)?;
+    let secret = secrets::load_or_create_host_secret(&serial).map_err(|e| e.to_string())?;
+    let pin_token = obtain_pin_token_at(&path, &pin, Permission::AuthenticatorConfiguration)?;
+
+    let transport = open_transport(&path)?;
+    let session = key_agreement(&transport).map_err(|e| e.to_string())?;
+    let wrapped = session.seal(&secret).map_err(|e| e.to_string())?;
+
+    transport
+        .send_vendor_config(
+            &pin_token,
+            VendorConfigCommand::AuthEncryptionEnable,
+            Value::Bytes(wrapped),
+        )
+        .map_err(|e| format!("Failed to enable encryption at rest: {}", e))?;
+
+    Ok(format!(
+        "Encryption at rest enabled. Keep {} safe: without it the key cannot be unlocked",
+        secret_path.display()
+    ))
+}
+
+/// Disables encryption at rest. The device must be unlocked. The host secret is kept so that
+/// re-enabling reuses it.
+pub(crate) fn disable_encryption(pin: String) -> Result<String, String> {
+    log::info!("Disabling encryption at rest...");
+
+    let pin_token = obtain_pin_token(&pin, Permission::AuthenticatorConfiguration)?;
+
+    let transport =
+        HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+    transport
+        .send_vendor_config(
+            &pin_token,
+            VendorConfigCommand::AuthEncryptionDisable,
+            Value::Bytes(Vec::new()),
+        )
+        .map_err(|e| format!("Failed to disable encryption at rest: {}", e))?;
+
+    Ok("Encryption at rest disabled".into())
+}
+
+/// Unlocks an encrypted device after power-up by sending the host secret for `serial`.
+pub(crate) fn unlock_device(serial: String) -> Result<String, String> {
+    log::info!("Unlocking device storage...");
+
+    let secret = secrets::load_host_secret(&serial)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No host secret stored for device {}", serial))?;
+
+    let transport = open_transport(&device_path(&serial)?)?;
+    let session = key_agreement(&transport).map_err(|e| e.to_string())?;
+    let wrapped = session.seal(&secret).map_err(|e| e.to_string())?;
+
+    let mut sub_params = BTreeMap::new();
+    sub_params.insert(
+        Value::Integer(VendorSubParam::VendorParam as i128),
+        Value::Bytes(wrapped),
+    );
+    send_vendor_command(
+        &transport,
+        VendorCommand::Unlock,
+        UnlockSubCommand::Unlock as u8,
+        sub_params,
+    )
+    .map_err(|e| format!("Failed to unlock device: {}", e))?;
+
+    Ok("Device unlocked".into())
+}
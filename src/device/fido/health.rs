@@ -0,0 +1,190 @@
+//! Authenticator self-test: a real makeCredential/getAssertion round trip against a dummy RP.
+//!
+//! A non-resident credential is created, so nothing is stored on the device. The assertion
+//! signature and counter are checked on the host with `ring`.
+
+use super::constants::{AuthenticatorFlags, CoseAlgorithm};
+use super::get_device;
+use crate::device::types::{HealthCheckReport, HealthCheckStep};
+use ctap_hid_fido2::fidokey::{
+    get_assertion::get_assertion_params::GetAssertionArgsBuilder,
+    make_credential::{CredentialSupportedKeyType, MakeCredentialArgsBuilder},
+};
+use rand::RngExt;
+use ring::{digest, signature};
+use std::time::Instant;
+
+const HEALTH_CHECK_RP_ID: &str = "health-check.picoforge.invalid";
+
+/// Offset of the flags byte in authenticator data (after the 32 byte RP ID hash).
+const AUTH_DATA_FLAGS_OFFSET: usize = 32;
+
+fn auth_data_flags(auth_data: &[u8]) -> AuthenticatorFlags {
+    AuthenticatorFlags::from_bits_truncate(
+        auth_data
+            .get(AUTH_DATA_FLAGS_OFFSET)
+            .copied()
+            .unwrap_or_default(),
+    )
+}
+
+fn random_challenge() -> [u8; 32] {
+    let mut challenge = [0u8; 32];
+    rand::rng().fill(&mut challenge);
+    challenge
+}
+
+/// Runs the self-test. With a PIN the credential and assertion are requested with user
+/// verification, so the UV flag is expected to be set; without one it is expected to be clear.
+/// Both steps need a touch on the key.
+pub(crate) fn run_health_check(pin: Option<String>) -> Result<HealthCheckReport, String> {
+    let device = get_device()?;
+
+    let info = device
+        .get_info()
+        .map_err(|e| format!("Error reading device info: {:?}", e))?;
+    let advertises = |alg: CoseAlgorithm| {
+        info.algorithms
+            .iter()
+            .any(|(key, value)| key == "alg" && value == &(alg as i32).to_string())
+    };
+
+    let (key_type, algorithm) = if advertises(CoseAlgorithm::ES256) {
+        (CredentialSupportedKeyType::Ecdsa256, CoseAlgorithm::ES256)
+    } else if advertises(CoseAlgorithm::EdDSA) {
+        (CredentialSupportedKeyType::Ed25519, CoseAlgorithm::EdDSA)
+    } else {
+        return Err("Device does not advertise ES256 or EdDSA".into());
+    };
+
+    log::info!("Running health check with {:?}", algorithm);
+
+    let expect_uv = pin.is_some();
+    let mut steps = Vec::new();
+
+    // 1. makeCredential
+    let challenge = random_challenge();
+    let builder = MakeCredentialArgsBuilder::new(HEALTH_CHECK_RP_ID, &challenge).key_type(key_type);
+    let args = match pin.as_deref() {
+        Some(pin) => builder.pin(pin).build(),
+        None => builder.without_pin_and_uv().build(),
+    };
+
+    let started = Instant::now();
+    let attestation = device
+        .make_credential_with_args(&args)
+        .map_err(|e| format!("makeCredential failed: {:?}", e))?;
+    let make_credential_ms = started.elapsed().as_millis();
+
+    let mc_flags = auth_data_flags(&attestation.auth_data);
+    let rp_id_hash = digest::digest(&digest::SHA256, HEALTH_CHECK_RP_ID.as_bytes());
+    let mc_ok = mc_flags.contains(AuthenticatorFlags::USER_PRESENT)
+        && mc_flags.contains(AuthenticatorFlags::ATTESTED_CREDENTIAL_DATA)
+        && attestation.rpid_hash == rp_id_hash.as_ref()
+        && !attestation.credential_descriptor.id.is_empty();
+    steps.push(HealthCheckStep {
+        name: "makeCredential".into(),
+        duration_ms: make_credential_ms,
+        passed: mc_ok,
+        detail: format!(
+            "Credential ID {} bytes, counter {}",
+            attestation.credential_descriptor.id.len(),
+            attestation.sign_count
+        ),
+    });
+
+    // 2. getAssertion against the new credential
+    let challenge = random_challenge();
+    let builder = GetAssertionArgsBuilder::new(HEALTH_CHECK_RP_ID, &challenge)
+        .credential_id(&attestation.credential_descriptor.id);
+    let args = match pin.as_deref() {
+        Some(pin) => builder.pin(pin).build(),
+        None => builder.without_pin_and_uv().build(),
+    };
+
+    let started = Instant::now();
+    let assertions = device
+        .get_assertion_with_args(&args)
+        .map_err(|e| format!("getAssertion failed: {:?}", e))?;
+    let get_assertion_ms = started.elapsed().as_millis();
+
+    let assertion = assertions
+        .first()
+        .ok_or_else(|| "getAssertion returned no assertion".to_string())?;
+    let ga_flags = auth_data_flags(&assertion.auth_data);
+    steps.push(HealthCheckStep {
+        name: "getAssertion".into(),
+        duration_ms: get_assertion_ms,
+        passed: ga_flags.contains(AuthenticatorFlags::USER_PRESENT)
+            && assertion.rpid_hash == rp_id_hash.as_ref(),
+        detail: format!("Counter {}", assertion.sign_count),
+    });
+
+    // 3. Signature over authData || clientDataHash with the credential public key
+    let client_data_hash = digest::digest(&digest::SHA256, &challenge);
+    let mut signed = assertion.auth_data.clone();
+    signed.extend_from_slice(client_data_hash.as_ref());
+
+    let verify_alg: &dyn signature::VerificationAlgorithm = match algorithm {
+        CoseAlgorithm::EdDSA => &signature::ED25519,
+        _ => &signature::ECDSA_P256_SHA256_ASN1,
+    };
+    let started = Instant::now();
+    let signature_ok =
+        signature::UnparsedPublicKey::new(verify_alg, &attestation.credential_publickey.der)
+            .verify(&signed, &assertion.signature)
+            .is_ok();
+    steps.push(HealthCheckStep {
+        name: "Signature".into(),
+        duration_ms: started.elapsed().as_millis(),
+        passed: signature_ok,
+        detail: if signature_ok {
+            "Assertion signature verified".into()
+        } else {
+            "Assertion signature does not verify against the credential key".into()
+        },
+    });
+
+    // 4. Signature counter must increase (a device without counters reports 0 both times)
+    let counter_ok = assertion.sign_count > attestation.sign_count
+        || (assertion.sign_count == 0 && attestation.sign_count == 0);
+    steps.push(HealthCheckStep {
+        name: "Counter".into(),
+        duration_ms: 0,
+        passed: counter_ok,
+        detail: format!("{} -> {}", attestation.sign_count, assertion.sign_count),
+    });
+
+    // 5. User presence / verification flags
+    let user_present = mc_flags.contains(AuthenticatorFlags::USER_PRESENT)
+        && ga_flags.contains(AuthenticatorFlags::USER_PRESENT);
+    let user_verified = mc_flags.contains(AuthenticatorFlags::USER_VERIFIED)
+        && ga_flags.contains(AuthenticatorFlags::USER_VERIFIED);
+    let uv_consistent = mc_flags.contains(AuthenticatorFlags::USER_VERIFIED)
+        == ga_flags.contains(AuthenticatorFlags::USER_VERIFIED);
+    let flags_ok = user_present && uv_consistent && user_verified == expect_uv;
+    steps.push(HealthCheckStep {
+        name: "UP/UV Flags".into(),
+        duration_ms: 0,
+        passed: flags_ok,
+        detail: format!(
+            "UP {}, UV {} (expected UV {})",
+            user_present, user_verified, expect_uv
+        ),
+    });
+
+    let passed = steps.iter().all(|s| s.passed);
+    if passed {
+        log::info!("Health check passed");
+    } else {
+        log::warn!("Health check failed: {:?}", steps);
+    }
+
+    Ok(HealthCheckReport {
+        algorithm: format!("{:?}", algorithm),
+        steps,
+        user_present,
+        user_verified,
+        passed,
+    })
+}
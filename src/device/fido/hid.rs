@@ -1,10 +1,13 @@
 use rand::RngExt;
 use serde_cbor_2::{Value, to_vec};
 use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::sync::Mutex;
 use std::time::Duration;
 
 use crate::device::error::PFError;
 use crate::device::fido::constants::*;
+use crate::device::types::FidoHidDevice;
 
 // HID Transport Constants
 const HID_REPORT_SIZE: usize = 64;
@@ -12,6 +15,7 @@ const HID_USAGE_PAGE_FIDO: u16 = 0xF1D0;
 const CTAPHID_CID_BROADCAST: u32 = 0xFFFFFFFF;
 const CTAPHID_INIT: u8 = 0x86;
 pub const CTAPHID_CBOR: u8 = 0x90;
+const CTAPHID_CANCEL: u8 = 0x91;
 const CTAPHID_ERROR: u8 = 0xBF;
 const CTAPHID_KEEPALIVE: u8 = 0xBB;
 
@@ -21,12 +25,79 @@ const HID_INIT_READ_TIMEOUT_MS: i32 = 100;
 const HID_RESP_READ_TIMEOUT_MS: i32 = 2000;
 const HID_CONT_READ_TIMEOUT_MS: i32 = 500;
 
+/// HID path of the key chosen with touch-to-select. `None` means the first FIDO device found.
+static SELECTED_DEVICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Binds all later FIDO HID sessions (ours and ctap-hid-fido2's) to the device at `path`.
+pub fn select_device(path: Option<String>) {
+    if let Ok(mut selected) = SELECTED_DEVICE.lock() {
+        *selected = path;
+    }
+}
+
+/// The selected device path, if that device is still connected. A selection whose device has
+/// gone away is dropped so that the next session falls back to the first FIDO device.
+pub fn selected_device() -> Option<String> {
+    let mut selected = SELECTED_DEVICE.lock().ok()?;
+    let path = selected.clone()?;
+    let present = list_devices()
+        .map(|devices| devices.iter().any(|d| d.path == path))
+        .unwrap_or(false);
+    if !present {
+        log::warn!("Selected FIDO device {} is no longer connected", path);
+        *selected = None;
+        return None;
+    }
+    Some(path)
+}
+
+/// All connected FIDO HID devices (usage page 0xF1D0).
+pub fn list_devices() -> Result<Vec<FidoHidDevice>, PFError> {
+    let api = hidapi::HidApi::new()
+        .map_err(|e| PFError::Device(format!("Failed to initialize HidApi: {}", e)))?;
+
+    Ok(api
+        .device_list()
+        .filter(|d| d.usage_page() == HID_USAGE_PAGE_FIDO)
+        .map(|d| FidoHidDevice {
+            path: d.path().to_string_lossy().into_owned(),
+            product_name: d
+                .product_string()
+                .unwrap_or("Unknown FIDO Device")
+                .to_string(),
+            serial_number: d.serial_number().map(str::to_string),
+            vid: d.vendor_id(),
+            pid: d.product_id(),
+        })
+        .collect())
+}
+
+/// HID path of the connected FIDO device whose USB serial number is `serial`.
+pub fn device_path_for_serial(serial: &str) -> Result<String, PFError> {
+    list_devices()?
+        .into_iter()
+        .find(|d| {
+            d.serial_number
+                .as_deref()
+                .is_some_and(|s| s.eq_ignore_ascii_case(serial))
+        })
+        .map(|d| d.path)
+        .ok_or_else(|| {
+            PFError::Device(format!(
+                "No FIDO device with serial {} is connected",
+                serial
+            ))
+        })
+}
+
 pub struct HidTransport {
     device: hidapi::HidDevice,
     cid: u32,
     pub vid: u16,
     pub pid: u16,
     pub product_name: String,
+    /// USB serial number. pico-keys report their rescue serial here.
+    pub serial_number: Option<String>,
 }
 
 impl HidTransport {
@@ -37,10 +108,16 @@ impl HidTransport {
             PFError::Device(format!("Failed to initialize HidApi: {}", e))
         })?;
 
-        // Find device with FIDO Usage Page (0xF1D0)
+        // Find device with FIDO Usage Page (0xF1D0), preferring the one chosen by touch
+        let selected = selected_device();
         let info = api
             .device_list()
-            .find(|d| d.usage_page() == HID_USAGE_PAGE_FIDO)
+            .filter(|d| d.usage_page() == HID_USAGE_PAGE_FIDO)
+            .find(|d| {
+                selected
+                    .as_deref()
+                    .is_none_or(|path| d.path().to_string_lossy() == path)
+            })
             .ok_or_else(|| {
                 log::warn!("No FIDO device found with Usage Page 0xF1D0.");
                 PFError::NoDevice
@@ -58,6 +135,7 @@ impl HidTransport {
             .product_string()
             .unwrap_or("Unknown FIDO Device")
             .to_string();
+        let serial_number = info.serial_number().map(str::to_string);
 
         let device = info.open_device(&api).map_err(|e| {
             log::error!("Failed to open HID device: {}", e);
@@ -77,6 +155,7 @@ impl HidTransport {
             vid,
             pid,
             product_name,
+            serial_number,
         })
     }
 
@@ -146,6 +225,89 @@ impl HidTransport {
         self.read_cbor_response(cmd)
     }
 
+    /// Opens the FIDO device at a specific HID path, ignoring the current selection.
+    pub fn open_path(path: &str) -> Result<Self, PFError> {
+        let api = hidapi::HidApi::new()
+            .map_err(|e| PFError::Device(format!("Failed to initialize HidApi: {}", e)))?;
+        let info = api
+            .device_list()
+            .find(|d| d.usage_page() == HID_USAGE_PAGE_FIDO && d.path().to_string_lossy() == path)
+            .ok_or(PFError::NoDevice)?;
+
+        let c_path = CString::new(path).map_err(|e| PFError::Io(e.to_string()))?;
+        let device = api
+            .open_path(&c_path)
+            .map_err(|e| PFError::Device(format!("Failed to open HID device: {}", e)))?;
+        let cid = Self::init_channel(&device)?;
+
+        Ok(Self {
+            device,
+            cid,
+            vid: info.vendor_id(),
+            pid: info.product_id(),
+            product_name: info
+                .product_string()
+                .unwrap_or("Unknown FIDO Device")
+                .to_string(),
+            serial_number: info.serial_number().map(str::to_string),
+        })
+    }
+
+    /// Sends a request without waiting for the response, so that several devices can be
+    /// driven at once. Pair with `poll_short_response`.
+    pub fn begin_request(&self, cmd: u8, payload: &[u8]) -> Result<(), PFError> {
+        self.write_cbor_request(cmd, payload)
+    }
+
+    /// Waits up to `timeout_ms` for a response that fits into a single packet (such as the
+    /// status-only authenticatorSelection reply). Returns `Ok(None)` if nothing but keepalives
+    /// arrived.
+    pub fn poll_short_response(
+        &self,
+        cmd: u8,
+        timeout_ms: i32,
+    ) -> Result<Option<Vec<u8>>, PFError> {
+        let mut buf = [0u8; HID_REPORT_SIZE];
+        let read = self
+            .device
+            .read_timeout(&mut buf[..], timeout_ms)
+            .map_err(|e| PFError::Io(format!("Failed to read HID packet: {}", e)))?;
+
+        if read == 0 || u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) != self.cid {
+            return Ok(None);
+        }
+        match buf[4] {
+            CTAPHID_KEEPALIVE => Ok(None),
+            CTAPHID_ERROR => Err(PFError::Device(format!(
+                "Device returned CTAP Error: 0x{:02X}",
+                buf[5]
+            ))),
+            c if c == cmd => {
+                let len = u16::from_be_bytes([buf[5], buf[6]]) as usize;
+                if len == 0 || len > HID_REPORT_SIZE - 7 {
+                    return Err(PFError::Device("Unexpected response length".into()));
+                }
+                let status = buf[7];
+                if status != 0x00 {
+                    return Err(PFError::Device(format!(
+                        "FIDO Operation Failed with Status: 0x{:02X}",
+                        status
+                    )));
+                }
+                Ok(Some(buf[8..7 + len].to_vec()))
+            }
+            other => Err(PFError::Device(format!(
+                "Unexpected command response: 0x{:02X}",
+                other
+            ))),
+        }
+    }
+
+    /// Aborts the pending request on this channel (CTAPHID_CANCEL).
+    pub fn send_cancel(&self) -> Result<(), PFError> {
+        self.write_cbor_request(CTAPHID_CANCEL, &[])
+    }
+
     fn write_cbor_request(&self, cmd: u8, payload: &[u8]) -> Result<(), PFError> {
         log::debug!(
             "Sending CBOR Command: 0x{:02X}, Payload Size: {} bytes",
@@ -420,22 +582,43 @@ impl HidTransport {
     /// CBOR map keys out of order (0x01, 0x03, 0x04, 0x02) instead of the required
     /// ascending order (0x01, 0x02, 0x03, 0x04). The pico-fido firmware strictly
     /// enforces canonical CBOR ordering per CTAP2 spec.
+    ///
+    /// `rp_ids` replaces the list of RP IDs allowed to receive the minPinLength extension
+    /// and is omitted when empty. `force_change_pin` makes the authenticator refuse PIN
+    /// tokens until the PIN has been changed.
     pub fn send_config_set_min_pin_length(
         &self,
         pin_token: &[u8],
         new_min_pin_length: u8,
+        rp_ids: &[String],
+        force_change_pin: bool,
     ) -> Result<(), PFError> {
         log::debug!(
-            "Sending setMinPINLength config command (new length: {})...",
-            new_min_pin_length
+            "Sending setMinPINLength config command (new length: {}, {} RP IDs, force change: {})...",
+            new_min_pin_length,
+            rp_ids.len(),
+            force_change_pin
         );
 
-        // Build subCommandParams (Key 0x02): { 0x01: newMinPINLength }
+        // Build subCommandParams (Key 0x02):
+        // { 0x01: newMinPINLength, 0x02: minPinLengthRPIDs, 0x03: forceChangePin }
         let mut sub_params_map = BTreeMap::new();
         sub_params_map.insert(
             Value::Integer(ConfigSubCommandParam::NewMinPinLength as i128),
             Value::Integer(new_min_pin_length as i128),
         );
+        if !rp_ids.is_empty() {
+            sub_params_map.insert(
+                Value::Integer(ConfigSubCommandParam::MinPinLengthRPIDs as i128),
+                Value::Array(rp_ids.iter().cloned().map(Value::Text).collect()),
+            );
+        }
+        if force_change_pin {
+            sub_params_map.insert(
+                Value::Integer(ConfigSubCommandParam::ForceChangePin as i128),
+                Value::Bool(true),
+            );
+        }
         let sub_params = Value::Map(sub_params_map);
         let sub_params_bytes = to_vec(&sub_params).map_err(|e| PFError::Io(e.to_string()))?;
 
@@ -489,7 +672,7 @@ impl HidTransport {
                 // Check for PIN policy violation (0x37) - cannot decrease min PIN length
                 if err_str.contains("0x37") {
                     return Err(PFError::Device(
-                        "Cannot decrease minimum PIN length. The FIDO2 security policy only allows increasing the minimum PIN length, not decreasing it. A device reset is required to lower the minimum.".into()
+                        "Cannot decrease minimum PIN length. The FIDO2 security policy only allows increasing the minimum PIN length, not decreasing it. Use Factory Reset on the Passkeys page to lower the minimum.".into()
                     ));
                 }
 
@@ -498,6 +681,242 @@ impl HidTransport {
         }
     }
 
+    /// Send a standard authenticatorConfig subcommand (e.g. enableEnterpriseAttestation).
+    ///
+    /// Like `send_config_set_min_pin_length`, this builds the request map by hand so that the
+    /// keys are emitted in the canonical ascending order the firmware expects.
+    pub fn send_config_command(
+        &self,
+        pin_token: &[u8],
+        sub_cmd: ConfigSubCommand,
+        sub_params: Option<Value>,
+    ) -> Result<(), PFError> {
+        log::debug!("Sending authenticatorConfig subcommand {:?}...", sub_cmd);
+
+        let sub_params_bytes = match &sub_params {
+            Some(params) => to_vec(params).map_err(|e| PFError::Io(e.to_string()))?,
+            None => Vec::new(),
+        };
+
+        let pin_auth = self.sign_config_command(pin_token, sub_cmd as u8, &sub_params_bytes);
+
+        let mut config_map = BTreeMap::new();
+        config_map.insert(
+            Value::Integer(ConfigParam::SubCommand as i128),
+            Value::Integer(sub_cmd as i128),
+        );
+        if let Some(params) = sub_params {
+            config_map.insert(
+                Value::Integer(ConfigParam::SubCommandParams as i128),
+                params,
+            );
+        }
+        config_map.insert(
+            Value::Integer(ConfigParam::PinUvAuthProtocol as i128),
+            Value::Integer(1),
+        );
+        config_map.insert(
+            Value::Integer(ConfigParam::PinUvAuthParam as i128),
+            Value::Bytes(pin_auth),
+        );
+
+        let config_payload_cbor =
+            to_vec(&Value::Map(config_map)).map_err(|e| PFError::Io(e.to_string()))?;
+
+        let mut payload = vec![CtapCommand::Config as u8];
+        payload.extend(config_payload_cbor);
+
+        self.send_cbor(CTAPHID_CBOR, &payload).map_err(|e| {
+            log::error!("authenticatorConfig {:?} failed: {}", sub_cmd, e);
+            PFError::Device(format!("authenticatorConfig {:?} failed: {}", sub_cmd, e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Send an authenticatorCredentialManagement subcommand and return the decoded response map.
+    ///
+    /// `pin_token` is required for the subcommands that carry a pinUvAuthParam; the
+    /// `...GetNext...` subcommands are sent without one. An empty response yields an empty map.
+    pub fn send_cred_mgmt(
+        &self,
+        pin_token: Option<&[u8]>,
+        sub_cmd: CredMgmtSubCommand,
+        sub_params: Option<Value>,
+    ) -> Result<BTreeMap<Value, Value>, PFError> {
+        log::debug!("Sending credentialManagement subcommand {:?}...", sub_cmd);
+
+        let mut request = BTreeMap::new();
+        request.insert(
+            Value::Integer(CredMgmtParam::SubCommand as i128),
+            Value::Integer(sub_cmd as i128),
+        );
+
+        let sub_params_bytes = match &sub_params {
+            Some(params) => to_vec(params).map_err(|e| PFError::Io(e.to_string()))?,
+            None => Vec::new(),
+        };
+        if let Some(params) = sub_params {
+            request.insert(
+                Value::Integer(CredMgmtParam::SubCommandParams as i128),
+                params,
+            );
+        }
+
+        if let Some(token) = pin_token {
+            // authenticate(pinUvAuthToken, uint8(subCommand) || subCommandParams)
+            let mut message = vec![sub_cmd as u8];
+            message.extend(&sub_params_bytes);
+
+            request.insert(
+                Value::Integer(CredMgmtParam::PinUvAuthProtocol as i128),
+                Value::Integer(1),
+            );
+            request.insert(
+                Value::Integer(CredMgmtParam::PinUvAuthParam as i128),
+                Value::Bytes(Self::pin_uv_auth(token, &message)),
+            );
+        }
+
+        let request_cbor = to_vec(&Value::Map(request)).map_err(|e| PFError::Io(e.to_string()))?;
+        let mut payload = vec![CtapCommand::CredentialMgmt as u8];
+        payload.extend(request_cbor);
+
+        let response = self.send_cbor(CTAPHID_CBOR, &payload)?;
+        if response.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+
+        match serde_cbor_2::from_slice(&response) {
+            Ok(Value::Map(map)) => Ok(map),
+            _ => Err(PFError::Io(
+                "credentialManagement response was not a valid CBOR map".into(),
+            )),
+        }
+    }
+
+    /// Send an authenticatorClientPIN subcommand that needs no parameters (getPINRetries,
+    /// getUVRetries) and return the decoded response map.
+    pub fn send_client_pin(
+        &self,
+        sub_cmd: ClientPinSubCommand,
+    ) -> Result<BTreeMap<Value, Value>, PFError> {
+        let mut request = BTreeMap::new();
+        request.insert(
+            Value::Integer(ClientPinParam::PinUvAuthProtocol as i128),
+            Value::Integer(1),
+        );
+        request.insert(
+            Value::Integer(ClientPinParam::SubCommand as i128),
+            Value::Integer(sub_cmd as i128),
+        );
+
+        let request_cbor = to_vec(&Value::Map(request)).map_err(|e| PFError::Io(e.to_string()))?;
+        let mut payload = vec![CtapCommand::ClientPin as u8];
+        payload.extend(request_cbor);
+
+        let response = self.send_cbor(CTAPHID_CBOR, &payload)?;
+        match serde_cbor_2::from_slice(&response) {
+            Ok(Value::Map(map)) => Ok(map),
+            _ => Err(PFError::Io(
+                "clientPIN response was not a valid CBOR map".into(),
+            )),
+        }
+    }
+
+    /// Send authenticatorGetInfo and return the raw response map, for the keys the
+    /// ctap-hid-fido2 `Info` struct does not decode.
+    pub fn send_get_info(&self) -> Result<BTreeMap<Value, Value>, PFError> {
+        let response = self.send_cbor(CTAPHID_CBOR, &[CtapCommand::GetInfo as u8])?;
+        match serde_cbor_2::from_slice(&response) {
+            Ok(Value::Map(map)) => Ok(map),
+            _ => Err(PFError::Io(
+                "getInfo response was not a valid CBOR map".into(),
+            )),
+        }
+    }
+
+    /// Read up to `count` bytes of the serialized large-blob array starting at `offset`.
+    pub fn send_large_blobs_get(&self, offset: usize, count: usize) -> Result<Vec<u8>, PFError> {
+        let mut request = BTreeMap::new();
+        request.insert(
+            Value::Integer(LargeBlobsParam::Get as i128),
+            Value::Integer(count as i128),
+        );
+        request.insert(
+            Value::Integer(LargeBlobsParam::Offset as i128),
+            Value::Integer(offset as i128),
+        );
+
+        let request_cbor = to_vec(&Value::Map(request)).map_err(|e| PFError::Io(e.to_string()))?;
+        let mut payload = vec![CtapCommand::LargeBlobs as u8];
+        payload.extend(request_cbor);
+
+        let response = self.send_cbor(CTAPHID_CBOR, &payload)?;
+        match serde_cbor_2::from_slice(&response) {
+            Ok(Value::Map(map)) => match map.get(&Value::Integer(0x01)) {
+                Some(Value::Bytes(config)) => Ok(config.clone()),
+                _ => Err(PFError::Io("largeBlobs response has no data".into())),
+            },
+            _ => Err(PFError::Io(
+                "largeBlobs response was not a valid CBOR map".into(),
+            )),
+        }
+    }
+
+    /// Write one fragment of the serialized large-blob array. `total_length` must be given with
+    /// the first fragment (offset 0) only.
+    pub fn send_large_blobs_set(
+        &self,
+        pin_token: &[u8],
+        offset: usize,
+        fragment: &[u8],
+        total_length: Option<usize>,
+    ) -> Result<(), PFError> {
+        use ring::digest;
+
+        // authenticate(pinUvAuthToken, 32x 0xff || h'0c00' || uint32LE(offset) || SHA-256(set))
+        let mut message = vec![0xFF; 32];
+        message.extend([CtapCommand::LargeBlobs as u8, 0x00]);
+        message.extend((offset as u32).to_le_bytes());
+        message.extend(digest::digest(&digest::SHA256, fragment).as_ref());
+
+        let mut request = BTreeMap::new();
+        request.insert(
+            Value::Integer(LargeBlobsParam::Set as i128),
+            Value::Bytes(fragment.to_vec()),
+        );
+        request.insert(
+            Value::Integer(LargeBlobsParam::Offset as i128),
+            Value::Integer(offset as i128),
+        );
+        if let Some(length) = total_length {
+            request.insert(
+                Value::Integer(LargeBlobsParam::Length as i128),
+                Value::Integer(length as i128),
+            );
+        }
+        request.insert(
+            Value::Integer(LargeBlobsParam::PinUvAuthParam as i128),
+            Value::Bytes(Self::pin_uv_auth(pin_token, &message)),
+        );
+        request.insert(
+            Value::Integer(LargeBlobsParam::PinUvAuthProtocol as i128),
+            Value::Integer(1),
+        );
+
+        let request_cbor = to_vec(&Value::Map(request)).map_err(|e| PFError::Io(e.to_string()))?;
+        let mut payload = vec![CtapCommand::LargeBlobs as u8];
+        payload.extend(request_cbor);
+
+        self.send_cbor(CTAPHID_CBOR, &payload).map_err(|e| {
+            log::error!("largeBlobs set at offset {} failed: {}", offset, e);
+            PFError::Device(format!("largeBlobs write failed: {}", e))
+        })?;
+
+        Ok(())
+    }
+
     /// Helper to sign the authenticatorConfig command
     fn sign_config_command(
         &self,
@@ -512,10 +931,14 @@ impl HidTransport {
         message.push(sub_cmd);
         message.extend(sub_params_bytes);
 
-        // Sign using provided PIN token
+        Self::pin_uv_auth(pin_token, &message)
+    }
+
+    /// PIN/UV auth protocol 1 `authenticate`: HMAC-SHA-256 truncated to 16 bytes.
+    fn pin_uv_auth(pin_token: &[u8], message: &[u8]) -> Vec<u8> {
         use ring::hmac;
         let hmac_key = hmac::Key::new(hmac::HMAC_SHA256, pin_token);
-        let sig = hmac::sign(&hmac_key, &message);
+        let sig = hmac::sign(&hmac_key, message);
         sig.as_ref()[0..16].to_vec()
     }
 }
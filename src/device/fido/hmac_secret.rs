@@ -0,0 +1,155 @@
+//! hmac-secret challenge/response for testing disk-unlock and password-manager integrations.
+//!
+//! A credential is created with the hmac-secret extension, then getAssertion is sent with one or
+//! two 32 byte salts. The salts and outputs are encrypted with the PIN protocol shared secret by
+//! ctap-hid-fido2, so only the decrypted outputs are returned here. The key derives different
+//! outputs with and without user verification, so whether UV was set is reported as well.
+
+use super::get_device;
+use crate::device::types::{HmacSecretCredential, HmacSecretOutput};
+use ctap_hid_fido2::fidokey::{
+    get_assertion::get_assertion_params::{
+        Extension as AssertionExtension, GetAssertionArgsBuilder,
+    },
+    make_credential::{Extension as CredentialExtension, MakeCredentialArgsBuilder},
+};
+use ctap_hid_fido2::public_key_credential_user_entity::PublicKeyCredentialUserEntity;
+use rand::RngExt;
+use ring::digest;
+
+pub const HMAC_SALT_LEN: usize = 32;
+
+fn random_challenge() -> [u8; 32] {
+    let mut challenge = [0u8; 32];
+    rand::rng().fill(&mut challenge);
+    challenge
+}
+
+/// Parses a salt given as 64 hex characters.
+pub fn salt_from_hex(input: &str) -> Result<[u8; HMAC_SALT_LEN], String> {
+    let bytes = hex::decode(input.trim()).map_err(|e| format!("Invalid salt hex: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("Salt must be exactly {} bytes", HMAC_SALT_LEN))
+}
+
+/// Derives a salt from a passphrase as SHA-256 of its UTF-8 bytes.
+pub fn salt_from_text(input: &str) -> [u8; HMAC_SALT_LEN] {
+    let hash = digest::digest(&digest::SHA256, input.as_bytes());
+    let mut salt = [0u8; HMAC_SALT_LEN];
+    salt.copy_from_slice(hash.as_ref());
+    salt
+}
+
+/// Creates a credential for `rp_id` with hmac-secret enabled. A resident credential can later be
+/// used without knowing its ID.
+pub(crate) fn create_credential(
+    rp_id: String,
+    pin: Option<String>,
+    resident: bool,
+) -> Result<HmacSecretCredential, String> {
+    let device = get_device()?;
+    log::info!(
+        "Creating {} hmac-secret credential for {}",
+        if resident { "resident" } else { "non-resident" },
+        rp_id
+    );
+
+    let challenge = random_challenge();
+    let user_id = random_challenge();
+    let user = PublicKeyCredentialUserEntity::new(
+        Some(&user_id),
+        Some("hmac-secret"),
+        Some("PicoForge hmac-secret"),
+    );
+    let extensions = [CredentialExtension::HmacSecret(Some(true))];
+
+    let mut builder = MakeCredentialArgsBuilder::new(&rp_id, &challenge)
+        .extensions(&extensions)
+        .user_entity(&user);
+    if resident {
+        builder = builder.resident_key();
+    }
+    let args = match pin.as_deref() {
+        Some(pin) => builder.pin(pin).build(),
+        None => builder.without_pin_and_uv().build(),
+    };
+
+    let attestation = device
+        .make_credential_with_args(&args)
+        .map_err(|e| format!("makeCredential failed: {:?}", e))?;
+
+    let enabled = attestation
+        .extensions
+        .iter()
+        .any(|e| matches!(e, CredentialExtension::HmacSecret(Some(true))));
+    if !enabled {
+        return Err("The key created the credential without hmac-secret".into());
+    }
+
+    Ok(HmacSecretCredential {
+        rp_id,
+        credential_id: hex::encode(&attestation.credential_descriptor.id),
+        resident,
+    })
+}
+
+/// Runs getAssertion with one or two salts and returns the derived outputs. Without a
+/// credential ID the key picks a resident credential for `rp_id`.
+pub(crate) fn derive(
+    rp_id: String,
+    credential_id: Option<Vec<u8>>,
+    salt1: [u8; HMAC_SALT_LEN],
+    salt2: Option<[u8; HMAC_SALT_LEN]>,
+    pin: Option<String>,
+) -> Result<HmacSecretOutput, String> {
+    let device = get_device()?;
+    log::info!("Requesting hmac-secret for {}", rp_id);
+
+    let extensions = [match salt2 {
+        Some(salt2) => AssertionExtension::HmacSecret2(Some((salt1, salt2))),
+        None => AssertionExtension::HmacSecret(Some(salt1)),
+    }];
+
+    let challenge = random_challenge();
+    let mut builder = GetAssertionArgsBuilder::new(&rp_id, &challenge).extensions(&extensions);
+    if let Some(id) = &credential_id {
+        builder = builder.credential_id(id);
+    }
+    let args = match pin.as_deref() {
+        Some(pin) => builder.pin(pin).build(),
+        None => builder.without_pin_and_uv().build(),
+    };
+
+    let assertions = device
+        .get_assertion_with_args(&args)
+        .map_err(|e| format!("getAssertion failed: {:?}", e))?;
+    let assertion = assertions
+        .first()
+        .ok_or_else(|| "getAssertion returned no assertion".to_string())?;
+
+    let (output1, output2) = assertion
+        .extensions
+        .iter()
+        .find_map(|e| match e {
+            AssertionExtension::HmacSecret(Some(out)) => Some((out.to_vec(), None)),
+            AssertionExtension::HmacSecret2(Some((out1, out2))) => {
+                Some((out1.to_vec(), Some(out2.to_vec())))
+            }
+            _ => None,
+        })
+        .ok_or_else(|| "The key returned no hmac-secret output for this credential".to_string())?;
+
+    let credential_id = match credential_id {
+        Some(id) => id,
+        None => assertion.credential_id.clone(),
+    };
+
+    Ok(HmacSecretOutput {
+        credential_id: hex::encode(credential_id),
+        user_verified: assertion.flags.user_verified_result,
+        output1: hex::encode(output1),
+        output2: output2.map(hex::encode),
+        matching_credentials: assertions.len(),
+    })
+}
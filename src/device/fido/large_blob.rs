@@ -0,0 +1,295 @@
+//! Large-blob storage (CTAP 2.1 authenticatorLargeBlobs).
+//!
+//! The authenticator stores one opaque serialized array: a CBOR array of entries followed by the
+//! first 16 bytes of its SHA-256. Each entry is encrypted with the largeBlobKey of the credential
+//! it belongs to (AES-256-GCM over DEFLATE-compressed data), so only entries whose key is known
+//! can be read. Writes always replace the whole array.
+
+use super::constants::*;
+use super::hid::HidTransport;
+use super::obtain_pin_token;
+use crate::device::error::PFError;
+use crate::device::types::{LargeBlobArray, LargeBlobEntry, StoredCredential};
+use ctap_hid_fido2::fidokey::pin::Permission;
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
+use rand::RngExt;
+use ring::{aead, digest};
+use serde_cbor_2::{Value, from_slice, to_vec};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+const NONCE_LEN: usize = 12;
+
+/// Room left in a CTAP message for the largeBlobs request around a fragment.
+const FRAGMENT_OVERHEAD: usize = 64;
+
+fn open_transport() -> Result<HidTransport, String> {
+    HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))
+}
+
+/// Transfer limits the authenticator advertises in getInfo.
+struct Limits {
+    /// maxMsgSize minus [`FRAGMENT_OVERHEAD`]
+    fragment_len: usize,
+    /// maxSerializedLargeBlobArray
+    max_size: usize,
+}
+
+fn read_limits(transport: &HidTransport) -> Result<Limits, PFError> {
+    let info = transport.send_get_info()?;
+    let get = |key: GetInfoResponseKey| match info.get(&Value::Integer(key as i128)) {
+        Some(Value::Integer(n)) => usize::try_from(*n).ok(),
+        _ => None,
+    };
+
+    // maxMsgSize defaults to 1024 bytes when absent.
+    let max_msg_size = get(GetInfoResponseKey::MaxMsgSize).unwrap_or(MAX_MSG_SIZE);
+    let max_size = get(GetInfoResponseKey::MaxSerializedLargeBlobArray).ok_or_else(|| {
+        PFError::Device("The device does not report a large-blob storage size".into())
+    })?;
+
+    Ok(Limits {
+        fragment_len: max_msg_size.saturating_sub(FRAGMENT_OVERHEAD).max(1),
+        max_size,
+    })
+}
+
+/// Reads the serialized array fragment by fragment until a short fragment marks the end.
+fn read_serialized(transport: &HidTransport, limits: &Limits) -> Result<Vec<u8>, PFError> {
+    let mut serialized = Vec::new();
+    loop {
+        let fragment = transport.send_large_blobs_get(serialized.len(), limits.fragment_len)?;
+        let done = fragment.len() < limits.fragment_len;
+        serialized.extend(fragment);
+        if done || serialized.len() > limits.max_size {
+            return Ok(serialized);
+        }
+    }
+}
+
+/// Checks the SHA-256 trailer and decodes the CBOR array in front of it. An array that fails the
+/// check is treated as the initial empty array, as CTAP 2.1 §6.10 requires; the flag reports it.
+fn decode_serialized(serialized: &[u8]) -> (Vec<Value>, bool) {
+    let entries = (serialized.len() > LARGE_BLOB_TRAILER_LEN)
+        .then(|| serialized.split_at(serialized.len() - LARGE_BLOB_TRAILER_LEN))
+        .filter(|(array, trailer)| {
+            &digest::digest(&digest::SHA256, array).as_ref()[..LARGE_BLOB_TRAILER_LEN] == *trailer
+        })
+        .and_then(|(array, _)| match from_slice(array) {
+            Ok(Value::Array(entries)) => Some(entries),
+            _ => None,
+        });
+
+    match entries {
+        Some(entries) => (entries, false),
+        None => {
+            log::warn!("Large blob array failed its integrity check, treating it as empty");
+            (Vec::new(), true)
+        }
+    }
+}
+
+fn encode_serialized(entries: Vec<Value>) -> Result<Vec<u8>, PFError> {
+    let mut serialized = to_vec(&Value::Array(entries)).map_err(|e| PFError::Io(e.to_string()))?;
+    let hash = digest::digest(&digest::SHA256, &serialized);
+    serialized.extend(&hash.as_ref()[..LARGE_BLOB_TRAILER_LEN]);
+    Ok(serialized)
+}
+
+fn entry_field(entry: &Value, key: LargeBlobEntryKey) -> Option<&Value> {
+    match entry {
+        Value::Map(map) => map.get(&Value::Integer(key as i128)),
+        _ => None,
+    }
+}
+
+/// Associated data: "blob" || uint64LE(origSize)
+fn entry_aad(orig_size: u64) -> Vec<u8> {
+    let mut aad = b"blob".to_vec();
+    aad.extend(orig_size.to_le_bytes());
+    aad
+}
+
+fn aead_key(large_blob_key: &[u8]) -> Option<aead::LessSafeKey> {
+    aead::UnboundKey::new(&aead::AES_256_GCM, large_blob_key)
+        .ok()
+        .map(aead::LessSafeKey::new)
+}
+
+/// Decrypts and inflates an entry. Returns `None` if the entry was not made with this key.
+fn decrypt_entry(entry: &Value, large_blob_key: &[u8]) -> Option<Vec<u8>> {
+    let Some(Value::Bytes(ciphertext)) = entry_field(entry, LargeBlobEntryKey::Ciphertext) else {
+        return None;
+    };
+    let Some(Value::Bytes(nonce)) = entry_field(entry, LargeBlobEntryKey::Nonce) else {
+        return None;
+    };
+    let Some(Value::Integer(orig_size)) = entry_field(entry, LargeBlobEntryKey::OrigSize) else {
+        return None;
+    };
+
+    let key = aead_key(large_blob_key)?;
+    let nonce = aead::Nonce::try_assume_unique_for_key(nonce).ok()?;
+    let mut in_out = ciphertext.clone();
+    let compressed = key
+        .open_in_place(
+            nonce,
+            aead::Aad::from(entry_aad(*orig_size as u64)),
+            &mut in_out,
+        )
+        .ok()?;
+
+    let mut data = Vec::new();
+    DeflateDecoder::new(&compressed[..])
+        .read_to_end(&mut data)
+        .ok()?;
+    (data.len() as i128 == *orig_size).then_some(data)
+}
+
+fn encrypt_entry(data: &[u8], large_blob_key: &[u8]) -> Result<Value, PFError> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .map_err(|e| PFError::Io(e.to_string()))?;
+    let mut in_out = encoder.finish().map_err(|e| PFError::Io(e.to_string()))?;
+
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill(&mut nonce);
+
+    let key = aead_key(large_blob_key)
+        .ok_or_else(|| PFError::Io("largeBlobKey must be 32 bytes".into()))?;
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::from(entry_aad(data.len() as u64)),
+        &mut in_out,
+    )
+    .map_err(|_| PFError::Io("Could not encrypt large blob".into()))?;
+
+    let mut entry = BTreeMap::new();
+    entry.insert(
+        Value::Integer(LargeBlobEntryKey::Ciphertext as i128),
+        Value::Bytes(in_out),
+    );
+    entry.insert(
+        Value::Integer(LargeBlobEntryKey::Nonce as i128),
+        Value::Bytes(nonce.to_vec()),
+    );
+    entry.insert(
+        Value::Integer(LargeBlobEntryKey::OrigSize as i128),
+        Value::Integer(data.len() as i128),
+    );
+    Ok(Value::Map(entry))
+}
+
+/// Writes the whole array in fragments with a token carrying the largeBlobWrite permission.
+fn write_serialized(pin: &str, entries: Vec<Value>, limits: &Limits) -> Result<(), String> {
+    let serialized = encode_serialized(entries).map_err(|e| e.to_string())?;
+    if serialized.len() > limits.max_size {
+        return Err(format!(
+            "Large blob storage is full ({} of {} bytes needed)",
+            serialized.len(),
+            limits.max_size
+        ));
+    }
+
+    let pin_token = obtain_pin_token(pin, Permission::LargeBlobWrite)?;
+    let transport = open_transport()?;
+
+    for (i, fragment) in serialized.chunks(limits.fragment_len).enumerate() {
+        let offset = i * limits.fragment_len;
+        let total_length = (offset == 0).then_some(serialized.len());
+        transport
+            .send_large_blobs_set(&pin_token, offset, fragment, total_length)
+            .map_err(|e| e.to_string())?;
+    }
+
+    log::info!("Wrote large blob array ({} bytes)", serialized.len());
+    Ok(())
+}
+
+fn read_entries() -> Result<(Vec<Value>, Limits), String> {
+    let transport = open_transport()?;
+    let limits = read_limits(&transport).map_err(|e| e.to_string())?;
+    let serialized = read_serialized(&transport, &limits).map_err(|e| e.to_string())?;
+    let (entries, _) = decode_serialized(&serialized);
+    Ok((entries, limits))
+}
+
+/// Reads the large-blob array and decrypts the entries belonging to the given credentials.
+/// Reading does not need a PIN.
+pub(crate) fn read_large_blobs(credentials: &[StoredCredential]) -> Result<LargeBlobArray, String> {
+    let transport = open_transport()?;
+    let limits = read_limits(&transport).map_err(|e| e.to_string())?;
+    let serialized = read_serialized(&transport, &limits).map_err(|e| e.to_string())?;
+    let (entries, integrity_failed) = decode_serialized(&serialized);
+
+    let keys: Vec<(&str, Vec<u8>)> = credentials
+        .iter()
+        .filter_map(|c| {
+            let key = hex::decode(c.large_blob_key.as_ref()?).ok()?;
+            Some((c.credential_id.as_str(), key))
+        })
+        .collect();
+
+    let entries = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let decrypted = keys
+                .iter()
+                .find_map(|(id, key)| decrypt_entry(entry, key).map(|data| (*id, data)));
+            LargeBlobEntry {
+                index,
+                stored_size: to_vec(entry).map(|v| v.len()).unwrap_or(0),
+                credential_id: decrypted.as_ref().map(|(id, _)| id.to_string()),
+                data: decrypted.map(|(_, data)| data),
+            }
+        })
+        .collect();
+
+    Ok(LargeBlobArray {
+        entries,
+        serialized_size: serialized.len(),
+        max_size: limits.max_size,
+        integrity_failed,
+    })
+}
+
+/// Replaces the blob of the credential owning `large_blob_key_hex`, or deletes it when `data`
+/// is `None`. Entries of other credentials are kept as they are.
+pub(crate) fn write_large_blob(
+    pin: String,
+    large_blob_key_hex: String,
+    data: Option<Vec<u8>>,
+) -> Result<String, String> {
+    let key = hex::decode(&large_blob_key_hex).map_err(|_| "Invalid largeBlobKey".to_string())?;
+
+    let (mut entries, limits) = read_entries()?;
+    let before = entries.len();
+    entries.retain(|entry| decrypt_entry(entry, &key).is_none());
+    let removed = before - entries.len();
+
+    let message = match data {
+        Some(data) => {
+            entries.push(encrypt_entry(&data, &key).map_err(|e| e.to_string())?);
+            format!("Large blob saved ({} bytes)", data.len())
+        }
+        None if removed == 0 => return Err("This credential has no large blob".into()),
+        None => "Large blob deleted".to_string(),
+    };
+
+    write_serialized(&pin, entries, &limits)?;
+    Ok(message)
+}
+
+/// Removes an entry by position, e.g. one left behind by a deleted credential.
+pub(crate) fn delete_large_blob_entry(pin: String, index: usize) -> Result<String, String> {
+    let (mut entries, limits) = read_entries()?;
+    if index >= entries.len() {
+        return Err("Large blob entry no longer exists".into());
+    }
+    entries.remove(index);
+
+    write_serialized(&pin, entries, &limits)?;
+    Ok("Large blob entry deleted".into())
+}
@@ -1,16 +1,25 @@
+pub mod attestation;
 pub mod constants;
+pub mod cose;
+pub mod encryption;
+pub mod health;
 pub mod hid;
+pub mod hmac_secret;
+pub mod large_blob;
+pub mod reset;
+pub mod selection;
+pub mod ssh;
 
 use crate::{
     device::error::PFError,
     device::types::{
-        AppConfig, AppConfigInput, DeviceInfo, DeviceMethod, FidoDeviceInfo, FullDeviceStatus,
-        StoredCredential,
+        AppConfig, AppConfigInput, CredentialCapacity, CredentialDeleteResult, DeviceInfo,
+        DeviceMethod, FidoDeviceInfo, FullDeviceStatus, PinRetries, StoredCredential,
     },
 };
 use constants::*;
 use ctap_hid_fido2::{
-    Cfg, FidoKeyHidFactory,
+    Cfg, FidoKeyHidFactory, HidParam,
     fidokey::{FidoKeyHid, pin::Permission},
     public_key_credential_descriptor::PublicKeyCredentialDescriptor,
 };
@@ -21,8 +30,17 @@ use std::collections::{BTreeMap, HashMap};
 // Fido functions that require pin: ( Uses ctap_hid_fido2 crate)
 
 fn get_device() -> Result<FidoKeyHid, String> {
+    open_device(hid::selected_device())
+}
+
+/// Opens the device at `path`, or the first FIDO device without one.
+fn open_device(path: Option<String>) -> Result<FidoKeyHid, String> {
     let cfg = Cfg::init();
-    FidoKeyHidFactory::create(&cfg).map_err(|e| {
+    match path {
+        Some(path) => FidoKeyHidFactory::create_by_params(&[HidParam::Path(path)], &cfg),
+        None => FidoKeyHidFactory::create(&cfg),
+    }
+    .map_err(|e| {
         format!(
             "Could not connect to FIDO device. Is it plugged in? Error: {:?}",
             e
@@ -39,6 +57,13 @@ pub(crate) fn get_fido_info() -> Result<FidoDeviceInfo, String> {
 
     let options_map: HashMap<String, bool> = info.options.into_iter().collect();
 
+    // The library handle must be closed before the raw transport is opened.
+    drop(device);
+    let raw = read_raw_info().unwrap_or_else(|e| {
+        log::debug!("Could not read raw getInfo: {}", e);
+        RawInfo::default()
+    });
+
     Ok(FidoDeviceInfo {
         versions: info.versions,
         extensions: info.extensions,
@@ -46,7 +71,18 @@ pub(crate) fn get_fido_info() -> Result<FidoDeviceInfo, String> {
         options: options_map,
         max_msg_size: info.max_msg_size,
         pin_protocols: info.pin_uv_auth_protocols,
+        algorithms: info
+            .algorithms
+            .iter()
+            .filter(|(key, _)| key == "alg")
+            .filter_map(|(_, alg)| alg.parse().ok())
+            .collect(),
+        remaining_disc_creds: raw.remaining_disc_creds,
         min_pin_length: info.min_pin_length,
+        force_pin_change: info.force_pin_change,
+        max_rpids_for_min_pin_length: info.max_rpids_for_set_min_pin_length,
+        pin_complexity_policy: raw.pin_complexity_policy,
+        pin_complexity_policy_url: raw.pin_complexity_policy_url,
         firmware_version: format!(
             "{}.{}",
             (info.firmware_version >> 8) & 0xFF,
@@ -55,6 +91,46 @@ pub(crate) fn get_fido_info() -> Result<FidoDeviceInfo, String> {
     })
 }
 
+/// getInfo fields that ctap-hid-fido2 drops or cannot tell apart from a missing value.
+#[derive(Default)]
+struct RawInfo {
+    pin_complexity_policy: Option<bool>,
+    pin_complexity_policy_url: Option<String>,
+    remaining_disc_creds: Option<u32>,
+}
+
+/// Reads the [`RawInfo`] fields from a raw getInfo response.
+fn read_raw_info() -> Result<RawInfo, PFError> {
+    let transport = HidTransport::open()?;
+    let info = transport.send_get_info()?;
+
+    let policy = match info.get(&Value::Integer(
+        GetInfoResponseKey::PinComplexityPolicy as i128,
+    )) {
+        Some(Value::Bool(enabled)) => Some(*enabled),
+        _ => None,
+    };
+    let url = match info.get(&Value::Integer(
+        GetInfoResponseKey::PinComplexityPolicyUrl as i128,
+    )) {
+        Some(Value::Bytes(url)) => Some(String::from_utf8_lossy(url).into_owned()),
+        Some(Value::Text(url)) => Some(url.clone()),
+        _ => None,
+    };
+    let remaining = match info.get(&Value::Integer(
+        GetInfoResponseKey::RemainingDiscoverableCredentials as i128,
+    )) {
+        Some(Value::Integer(n)) => u32::try_from(*n).ok(),
+        _ => None,
+    };
+
+    Ok(RawInfo {
+        pin_complexity_policy: policy,
+        pin_complexity_policy_url: url,
+        remaining_disc_creds: remaining,
+    })
+}
+
 pub(crate) fn change_fido_pin(
     current_pin: Option<String>,
     new_pin: String,
@@ -80,6 +156,8 @@ pub(crate) fn change_fido_pin(
 pub(crate) fn set_min_pin_length(
     current_pin: String,
     min_pin_length: u8,
+    rp_ids: Vec<String>,
+    force_change_pin: bool,
 ) -> Result<String, String> {
     log::info!("Starting set_min_pin_length (custom implementation)...");
 
@@ -109,58 +187,242 @@ pub(crate) fn set_min_pin_length(
         HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
 
     transport
-        .send_config_set_min_pin_length(&pin_token, min_pin_length)
+        .send_config_set_min_pin_length(&pin_token, min_pin_length, &rp_ids, force_change_pin)
         .map_err(|e| format!("Failed to set minimum PIN length: {}", e))?;
 
-    Ok(format!(
-        "Minimum PIN length successfully set to {}",
-        min_pin_length
-    ))
+    Ok(if force_change_pin {
+        format!(
+            "Minimum PIN length set to {}. The PIN must be changed before next use",
+            min_pin_length
+        )
+    } else {
+        format!("Minimum PIN length successfully set to {}", min_pin_length)
+    })
+}
+
+/// Reads the remaining PIN attempts and, when the key has built-in UV, the remaining UV attempts.
+/// Neither needs a PIN.
+pub(crate) fn get_pin_retries() -> Result<PinRetries, String> {
+    let transport =
+        HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+    let int = |map: &BTreeMap<Value, Value>, key: ClientPinResponseKey| match map
+        .get(&Value::Integer(key as i128))
+    {
+        Some(Value::Integer(i)) => Some(*i as u32),
+        _ => None,
+    };
+
+    let pin = transport
+        .send_client_pin(ClientPinSubCommand::GetPinRetries)
+        .map_err(|e| format!("Failed to read PIN retries: {}", e))?;
+    let pin_retries = int(&pin, ClientPinResponseKey::PinRetries)
+        .ok_or_else(|| "getPINRetries response has no retry count".to_string())?;
+    let power_cycle_required = matches!(
+        pin.get(&Value::Integer(
+            ClientPinResponseKey::PowerCycleState as i128
+        )),
+        Some(Value::Bool(true))
+    );
+
+    let uv_retries = transport
+        .send_client_pin(ClientPinSubCommand::GetUvRetries)
+        .ok()
+        .and_then(|uv| int(&uv, ClientPinResponseKey::UvRetries));
+
+    Ok(PinRetries {
+        pin_retries,
+        power_cycle_required,
+        uv_retries,
+    })
+}
+
+/// Reads the number of stored and remaining discoverable credential slots (getCredsMetadata).
+pub(crate) fn get_creds_metadata(pin: String) -> Result<CredentialCapacity, String> {
+    let pin_token = obtain_pin_token(&pin, Permission::CredentialManagement)?;
+
+    let transport =
+        HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+    let metadata = transport
+        .send_cred_mgmt(Some(&pin_token), CredMgmtSubCommand::GetCredsMetadata, None)
+        .map_err(|e| format!("Failed to read credential metadata: {}", e))?;
+
+    Ok(CredentialCapacity {
+        existing: Some(
+            cbor_uint(
+                &metadata,
+                CredMgmtResponseKey::ExistingResidentCredentialsCount as u8,
+            )
+            .unwrap_or(0),
+        ),
+        remaining: cbor_uint(
+            &metadata,
+            CredMgmtResponseKey::MaxPossibleRemainingResidentCredentialsCount as u8,
+        )
+        .unwrap_or(0),
+    })
+}
+
+/// Slot usage without a PIN: only getInfo's remainingDiscoverableCredentials is known, and only
+/// if the device reports it.
+pub(crate) fn estimate_capacity(info: &FidoDeviceInfo) -> Option<CredentialCapacity> {
+    info.remaining_disc_creds
+        .map(|remaining| CredentialCapacity {
+            existing: None,
+            remaining,
+        })
 }
 
 pub(crate) fn get_credentials(pin: String) -> Result<Vec<StoredCredential>, String> {
-    let device = get_device()?;
+    // Enumerate with raw credentialManagement commands: the library drops the COSE algorithm and
+    // curve of the public key as well as the largeBlobKey.
+    let pin_token = obtain_pin_token(&pin, Permission::CredentialManagement)?;
 
-    let rps = match device.credential_management_enumerate_rps(Some(&pin)) {
-        Ok(rps) => rps,
+    let transport =
+        HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+    let first_rp = match transport.send_cred_mgmt(
+        Some(&pin_token),
+        CredMgmtSubCommand::EnumerateRpsBegin,
+        None,
+    ) {
+        Ok(rp) => rp,
         Err(e) => {
             // CTAP2_ERR_NO_CREDENTIALS (0x2E) means no credentials exist - return empty list
-            let err_str = format!("{:?}", e);
+            let err_str = e.to_string();
             if err_str.contains("0x2E") || err_str.contains("NO_CREDENTIALS") {
                 log::info!("No credentials stored on device (CTAP2_ERR_NO_CREDENTIALS)");
                 return Ok(Vec::new());
             }
-            return Err(format!("Failed to enumerate Relying Parties: {:?}", e));
+            return Err(format!("Failed to enumerate Relying Parties: {}", e));
         }
     };
 
+    let total_rps = cbor_uint(&first_rp, CredMgmtResponseKey::TotalRps as u8).unwrap_or(1);
+    let mut rps = vec![first_rp];
+    for _ in 1..total_rps {
+        let rp = transport
+            .send_cred_mgmt(None, CredMgmtSubCommand::EnumerateRpsGetNextRp, None)
+            .map_err(|e| format!("Failed to enumerate Relying Parties: {}", e))?;
+        rps.push(rp);
+    }
+
     let mut all_credentials = Vec::new();
 
     for rp in rps {
-        let creds = device
-            .credential_management_enumerate_credentials(Some(&pin), &rp.rpid_hash)
-            .map_err(|e| {
-                format!(
-                    "Failed to enumerate credentials for RP {}: {:?}",
-                    rp.public_key_credential_rp_entity.id, e
+        let rp_entity = rp.get(&Value::Integer(CredMgmtResponseKey::Rp as i128));
+        let rp_id = cbor_text_field(rp_entity, "id");
+        let rp_name = cbor_text_field(rp_entity, "name");
+
+        let Some(Value::Bytes(rp_id_hash)) =
+            rp.get(&Value::Integer(CredMgmtResponseKey::RpIdHash as i128))
+        else {
+            return Err(format!("Missing rpIDHash for RP {}", rp_id));
+        };
+
+        let mut params = BTreeMap::new();
+        params.insert(
+            Value::Integer(CredMgmtSubCommandParam::RpIdHash as i128),
+            Value::Bytes(rp_id_hash.clone()),
+        );
+
+        let first_cred = transport
+            .send_cred_mgmt(
+                Some(&pin_token),
+                CredMgmtSubCommand::EnumerateCredentialsBegin,
+                Some(Value::Map(params)),
+            )
+            .map_err(|e| format!("Failed to enumerate credentials for RP {}: {}", rp_id, e))?;
+
+        let total_creds =
+            cbor_uint(&first_cred, CredMgmtResponseKey::TotalCredentials as u8).unwrap_or(1);
+        let mut creds = vec![first_cred];
+        for _ in 1..total_creds {
+            let cred = transport
+                .send_cred_mgmt(
+                    None,
+                    CredMgmtSubCommand::EnumerateCredentialsGetNextCredential,
+                    None,
                 )
-            })?;
+                .map_err(|e| format!("Failed to enumerate credentials for RP {}: {}", rp_id, e))?;
+            creds.push(cred);
+        }
 
         for cred in creds {
-            all_credentials.push(StoredCredential {
-                credential_id: hex::encode(&cred.public_key_credential_descriptor.id),
-                rp_id: rp.public_key_credential_rp_entity.id.clone(),
-                rp_name: rp.public_key_credential_rp_entity.name.clone(),
-                user_name: cred.public_key_credential_user_entity.name.clone(),
-                user_display_name: cred.public_key_credential_user_entity.display_name.clone(),
-                user_id: hex::encode(&cred.public_key_credential_user_entity.id).clone(),
-            });
+            all_credentials.push(parse_stored_credential(&cred, &rp_id, &rp_name));
         }
     }
 
     Ok(all_credentials)
 }
 
+fn parse_stored_credential(
+    cred: &BTreeMap<Value, Value>,
+    rp_id: &str,
+    rp_name: &str,
+) -> StoredCredential {
+    let get = |key: CredMgmtResponseKey| cred.get(&Value::Integer(key as i128));
+
+    let user = get(CredMgmtResponseKey::User);
+    let user_id = match cbor_field(user, "id") {
+        Some(Value::Bytes(id)) => hex::encode(id),
+        _ => String::new(),
+    };
+    let credential_id = match cbor_field(get(CredMgmtResponseKey::CredentialId), "id") {
+        Some(Value::Bytes(id)) => hex::encode(id),
+        _ => String::new(),
+    };
+
+    let public_key = get(CredMgmtResponseKey::PublicKey).and_then(|key| {
+        cose::decode_cose_key(key)
+            .inspect_err(|e| log::warn!("Could not decode credential public key: {}", e))
+            .ok()
+    });
+
+    let cred_protect = match get(CredMgmtResponseKey::CredProtect) {
+        Some(Value::Integer(level)) => Some(*level as u8),
+        _ => None,
+    };
+    let large_blob_key = match get(CredMgmtResponseKey::LargeBlobKey) {
+        Some(Value::Bytes(key)) => Some(hex::encode(key)),
+        _ => None,
+    };
+
+    StoredCredential {
+        credential_id,
+        rp_id: rp_id.to_string(),
+        rp_name: rp_name.to_string(),
+        user_name: cbor_text_field(user, "name"),
+        user_display_name: cbor_text_field(user, "displayName"),
+        user_id,
+        public_key,
+        cred_protect,
+        large_blob_key,
+    }
+}
+
+fn cbor_uint(map: &BTreeMap<Value, Value>, key: u8) -> Option<u32> {
+    match map.get(&Value::Integer(key as i128)) {
+        Some(Value::Integer(i)) => u32::try_from(*i).ok(),
+        _ => None,
+    }
+}
+
+fn cbor_field<'a>(entity: Option<&'a Value>, name: &str) -> Option<&'a Value> {
+    match entity {
+        Some(Value::Map(m)) => m.get(&Value::Text(name.to_string())),
+        _ => None,
+    }
+}
+
+fn cbor_text_field(entity: Option<&Value>, name: &str) -> String {
+    match cbor_field(entity, name) {
+        Some(Value::Text(t)) => t.clone(),
+        _ => String::new(),
+    }
+}
+
 pub(crate) fn delete_credential(pin: String, credential_id_hex: String) -> Result<String, String> {
     let device = get_device()?;
 
@@ -179,8 +441,225 @@ pub(crate) fn delete_credential(pin: String, credential_id_hex: String) -> Resul
     Ok("Credential deleted successfully".into())
 }
 
+/// Deletes several credentials with a single PIN token. A failure on one credential does not
+/// stop the batch; every credential gets its own result.
+pub(crate) fn delete_credentials(
+    pin: String,
+    credential_ids_hex: Vec<String>,
+) -> Result<Vec<CredentialDeleteResult>, String> {
+    log::info!("Deleting {} credentials...", credential_ids_hex.len());
+
+    let pin_token = obtain_pin_token(&pin, Permission::CredentialManagement)?;
+
+    let transport =
+        HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+    let results = credential_ids_hex
+        .into_iter()
+        .map(|credential_id| {
+            let error = hex::decode(&credential_id)
+                .map_err(|_| "Invalid Credential ID Hex string".to_string())
+                .and_then(|id| {
+                    let mut descriptor = BTreeMap::new();
+                    descriptor.insert(Value::Text("id".into()), Value::Bytes(id));
+                    descriptor.insert(Value::Text("type".into()), Value::Text("public-key".into()));
+
+                    let mut params = BTreeMap::new();
+                    params.insert(
+                        Value::Integer(CredMgmtSubCommandParam::CredentialId as i128),
+                        Value::Map(descriptor),
+                    );
+
+                    transport
+                        .send_cred_mgmt(
+                            Some(&pin_token),
+                            CredMgmtSubCommand::DeleteCredential,
+                            Some(Value::Map(params)),
+                        )
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .err();
+
+            if let Some(e) = &error {
+                log::warn!("Failed to delete credential {}: {}", credential_id, e);
+            }
+            CredentialDeleteResult {
+                credential_id,
+                error,
+            }
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Updates the user name and display name stored with a discoverable credential
+/// (credentialManagement `updateUserInformation`). The user ID must match the stored one.
+/// Empty fields are omitted, which removes them from the credential.
+pub(crate) fn update_user_information(
+    pin: String,
+    credential_id_hex: String,
+    user_id_hex: String,
+    user_name: String,
+    display_name: String,
+) -> Result<String, String> {
+    let cred_id_bytes = hex::decode(&credential_id_hex)
+        .map_err(|_| "Invalid Credential ID Hex string".to_string())?;
+    let user_id_bytes =
+        hex::decode(&user_id_hex).map_err(|_| "Invalid User ID Hex string".to_string())?;
+
+    let pin_token = obtain_pin_token(&pin, Permission::CredentialManagement)?;
+
+    let transport =
+        HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+    let mut descriptor = BTreeMap::new();
+    descriptor.insert(Value::Text("id".into()), Value::Bytes(cred_id_bytes));
+    descriptor.insert(Value::Text("type".into()), Value::Text("public-key".into()));
+
+    let mut user = BTreeMap::new();
+    user.insert(Value::Text("id".into()), Value::Bytes(user_id_bytes));
+    if !user_name.is_empty() {
+        user.insert(Value::Text("name".into()), Value::Text(user_name));
+    }
+    if !display_name.is_empty() {
+        user.insert(Value::Text("displayName".into()), Value::Text(display_name));
+    }
+
+    let mut params = BTreeMap::new();
+    params.insert(
+        Value::Integer(CredMgmtSubCommandParam::CredentialId as i128),
+        Value::Map(descriptor),
+    );
+    params.insert(
+        Value::Integer(CredMgmtSubCommandParam::User as i128),
+        Value::Map(user),
+    );
+
+    transport
+        .send_cred_mgmt(
+            Some(&pin_token),
+            CredMgmtSubCommand::UpdateUserInformation,
+            Some(Value::Map(params)),
+        )
+        .map_err(|e| format!("Failed to update user information: {}", e))?;
+
+    Ok("Credential user information updated".into())
+}
+
+/// Obtain a PIN/UV auth token with the given permission through the library handle.
+///
+/// The library handle is dropped before returning so that the caller can open its own
+/// `HidTransport` session afterwards.
+fn obtain_pin_token(pin: &str, permission: Permission) -> Result<Vec<u8>, String> {
+    pin_token(get_device()?, pin, permission)
+}
+
+/// Like `obtain_pin_token`, for the device at `path` instead of the selected one.
+fn obtain_pin_token_at(path: &str, pin: &str, permission: Permission) -> Result<Vec<u8>, String> {
+    pin_token(open_device(Some(path.to_string()))?, pin, permission)
+}
+
+fn pin_token(device: FidoKeyHid, pin: &str, permission: Permission) -> Result<Vec<u8>, String> {
+    match device.get_pinuv_auth_token_with_permission(pin, permission) {
+        Ok(token) => {
+            log::debug!("Successfully obtained PIN token.");
+            Ok(token.key)
+        }
+        Err(e) => {
+            log::error!("Failed to get PIN token: {:?}", e);
+            Err(format!("Failed to obtain PIN token: {:?}", e))
+        }
+    }
+}
+
+pub(crate) fn upload_ea_certificate(pin: String, cert_der: Vec<u8>) -> Result<String, String> {
+    log::info!(
+        "Uploading enterprise attestation certificate ({} bytes)...",
+        cert_der.len()
+    );
+
+    let pin_token = obtain_pin_token(&pin, Permission::AuthenticatorConfiguration)?;
+
+    let transport =
+        HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+    transport
+        .send_vendor_config(
+            &pin_token,
+            VendorConfigCommand::EnterpriseAttestationUpload,
+            Value::Bytes(cert_der),
+        )
+        .map_err(|e| format!("Failed to upload attestation certificate: {}", e))?;
+
+    Ok("Enterprise attestation certificate uploaded".into())
+}
+
+pub(crate) fn enable_enterprise_attestation(pin: String) -> Result<String, String> {
+    log::info!("Enabling enterprise attestation...");
+
+    let pin_token = obtain_pin_token(&pin, Permission::AuthenticatorConfiguration)?;
+
+    let transport =
+        HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+    transport
+        .send_config_command(
+            &pin_token,
+            ConfigSubCommand::EnableEnterpriseAttestation,
+            None,
+        )
+        .map_err(|e| format!("Failed to enable enterprise attestation: {}", e))?;
+
+    Ok("Enterprise attestation enabled".into())
+}
+
+/// Flips the alwaysUv option and returns its new state as reported by getInfo.
+pub(crate) fn toggle_always_uv(pin: String) -> Result<bool, String> {
+    log::info!("Toggling alwaysUv...");
+
+    let pin_token = obtain_pin_token(&pin, Permission::AuthenticatorConfiguration)?;
+
+    {
+        let transport =
+            HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+        transport
+            .send_config_command(&pin_token, ConfigSubCommand::ToggleAlwaysUv, None)
+            .map_err(|e| format!("Failed to toggle alwaysUv: {}", e))?;
+    }
+
+    let info = get_fido_info()?;
+    Ok(info.options.get("alwaysUv").copied().unwrap_or(false))
+}
+
 // Custom Fido functions ( works only with pico-fido firmware )
 
+/// Turns pico-fido's PIN complexity enforcement on or off and returns the state reported by
+/// getInfo afterwards.
+pub(crate) fn set_pin_complexity_policy(pin: String, enabled: bool) -> Result<bool, String> {
+    log::info!("Setting PIN complexity policy to {}...", enabled);
+
+    let pin_token = obtain_pin_token(&pin, Permission::AuthenticatorConfiguration)?;
+
+    {
+        let transport =
+            HidTransport::open().map_err(|e| format!("Could not open HID transport: {}", e))?;
+
+        transport
+            .send_vendor_config(
+                &pin_token,
+                VendorConfigCommand::PinComplexityPolicy,
+                Value::Integer(enabled as i128),
+            )
+            .map_err(|e| format!("Failed to set PIN complexity policy: {}", e))?;
+    }
+
+    let raw = read_raw_info().map_err(|e| e.to_string())?;
+    Ok(raw.pin_complexity_policy.unwrap_or(enabled))
+}
+
 pub fn read_device_details() -> Result<FullDeviceStatus, PFError> {
     log::info!("Starting FIDO device details read...");
 
@@ -214,7 +693,12 @@ pub fn read_device_details() -> Result<FullDeviceStatus, PFError> {
 
     Ok(FullDeviceStatus {
         info: DeviceInfo {
-            serial: "?".to_string(), // Serial number is not available through fido
+            // Only the USB serial is available through FIDO. It matches the rescue serial.
+            serial: transport
+                .serial_number
+                .clone()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "?".to_string()),
             flash_used: used / 1024,
             flash_total: total / 1024,
             firmware_version: fw_version,
@@ -281,6 +765,45 @@ fn read_device_info(transport: &HidTransport) -> Result<(String, String), PFErro
     Ok((aaguid_str, fw_version))
 }
 
+/// Asks the device to generate a CSR for its enterprise attestation key.
+pub fn generate_ea_csr() -> Result<Vec<u8>, PFError> {
+    log::info!("Requesting enterprise attestation CSR...");
+
+    let transport = HidTransport::open()?;
+
+    let mut ea_req = BTreeMap::new();
+    ea_req.insert(
+        Value::Integer(1), // Sub-command key
+        Value::Integer(EnterpriseAttestationSubCommand::GenerateCsr as i128),
+    );
+
+    let ea_cbor = to_vec(&Value::Map(ea_req)).map_err(|e| {
+        log::error!("Failed to encode CSR request CBOR: {}", e);
+        PFError::Io(format!("CBOR encode error: {}", e))
+    })?;
+
+    let mut ea_payload = vec![VendorCommand::EnterpriseAttestation as u8];
+    ea_payload.extend(ea_cbor);
+
+    let ea_res = transport
+        .send_cbor(CTAP_VENDOR_CBOR_CMD, &ea_payload)
+        .map_err(|e| {
+            log::error!("Failed to generate CSR (Vendor Cmd): {}", e);
+            PFError::Device(format!("Failed to generate CSR: {}", e))
+        })?;
+
+    match from_slice(&ea_res) {
+        Ok(Value::Map(m)) => match m.get(&Value::Integer(1)) {
+            Some(Value::Bytes(csr)) => {
+                log::info!("Received CSR ({} bytes)", csr.len());
+                Ok(csr.clone())
+            }
+            _ => Err(PFError::Device("CSR missing from device response".into())),
+        },
+        _ => Err(PFError::Io("CSR response was not a valid CBOR map".into())),
+    }
+}
+
 fn read_memory_stats(transport: &HidTransport) -> Result<(u32, u32), PFError> {
     log::debug!("Preparing Memory Stats vendor command...");
 
@@ -0,0 +1,142 @@
+//! Guided authenticatorReset.
+//!
+//! The firmware only accepts Reset within a few seconds of power-up, so the user unplugs and
+//! replugs the key while the host polls for it to disappear and re-enumerate, and the command is
+//! sent as soon as the key is back. The key then waits for a touch before wiping its FIDO data.
+//!
+//! Other FIDO keys may stay connected throughout, so the managed key is tracked by its HID path
+//! and the replugged key is the one whose path was not there after the unplug.
+
+use super::constants::{Ctap2Error, CtapCommand};
+use super::hid::{self, CTAPHID_CBOR, HidTransport};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long the freshly plugged key is retried while the OS finishes enumerating it.
+const OPEN_RETRY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Per-read timeout while waiting for the reset reply, so that a cancel is noticed quickly.
+const RESPONSE_POLL_MS: i32 = 100;
+
+/// Upper bound for the touch; the firmware itself gives up well before this.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn connected_paths() -> Vec<String> {
+    hid::list_devices()
+        .map(|devices| devices.into_iter().map(|d| d.path).collect())
+        .unwrap_or_default()
+}
+
+/// Polls `check` until it yields a value, `timeout` passes or `cancel` is set.
+fn wait_until<T>(
+    timeout: Duration,
+    cancel: &AtomicBool,
+    timeout_message: &str,
+    mut check: impl FnMut() -> Option<T>,
+) -> Result<T, String> {
+    let started = Instant::now();
+    loop {
+        if let Some(value) = check() {
+            return Ok(value);
+        }
+        if cancel.load(Ordering::Relaxed) {
+            return Err("The reset was cancelled. Nothing was erased.".into());
+        }
+        if started.elapsed() > timeout {
+            return Err(timeout_message.into());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Blocks until the managed key is unplugged. Returns the paths of the FIDO keys that are still
+/// connected, so that the replugged key can be told apart from them.
+pub(crate) fn wait_for_removal(
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<Vec<String>, String> {
+    let path = hid::selected_device()
+        .or_else(|| connected_paths().into_iter().next())
+        .ok_or("No FIDO device found")?;
+
+    log::info!("Waiting for the key at {} to be unplugged...", path);
+    wait_until(timeout, cancel, "The key was not unplugged", || {
+        let paths = connected_paths();
+        (!paths.contains(&path)).then_some(paths)
+    })
+}
+
+/// Blocks until a FIDO key that is not one of `others` is connected and returns its path.
+pub(crate) fn wait_for_arrival(
+    others: &[String],
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<String, String> {
+    log::info!("Waiting for the key to be plugged in...");
+    wait_until(timeout, cancel, "The key was not plugged back in", || {
+        connected_paths().into_iter().find(|p| !others.contains(p))
+    })
+}
+
+fn reset_error(err: String) -> String {
+    let status = |code: Ctap2Error| err.contains(&format!("0x{:02X}", code as u8));
+    if status(Ctap2Error::NotAllowed) {
+        "The key refused the reset because too much time passed since it was plugged in. Try again and touch the key as soon as it blinks.".to_string()
+    } else if status(Ctap2Error::UserActionTimeout) {
+        "The key was not touched in time. Nothing was erased.".to_string()
+    } else if status(Ctap2Error::OperationDenied) || status(Ctap2Error::KeepaliveCancel) {
+        "The reset was cancelled. Nothing was erased.".to_string()
+    } else {
+        format!("Reset failed: {}", err)
+    }
+}
+
+/// Sends authenticatorReset to the key that has just been plugged in at `path`. Blocks until the
+/// user touches the key or the firmware gives up. Setting `cancel` sends CTAPHID_CANCEL; the key
+/// then answers with KEEPALIVE_CANCEL unless it was already touched.
+pub(crate) fn reset_authenticator(path: &str, cancel: &AtomicBool) -> Result<String, String> {
+    let started = Instant::now();
+    let transport = loop {
+        match HidTransport::open_path(path) {
+            Ok(transport) => break transport,
+            Err(e) if started.elapsed() > OPEN_RETRY_WINDOW => {
+                return Err(format!("Could not open HID transport: {}", e));
+            }
+            Err(_) => thread::sleep(POLL_INTERVAL),
+        }
+    };
+    // Later sessions follow the key to its new path.
+    hid::select_device(Some(path.to_string()));
+
+    log::info!("Sending authenticatorReset, waiting for user presence...");
+    transport
+        .begin_request(CTAPHID_CBOR, &[CtapCommand::Reset as u8])
+        .map_err(|e| reset_error(e.to_string()))?;
+
+    let started = Instant::now();
+    let mut cancel_sent = false;
+    loop {
+        if !cancel_sent && cancel.load(Ordering::Relaxed) {
+            log::info!("Cancelling authenticatorReset...");
+            transport
+                .send_cancel()
+                .map_err(|e| format!("Could not cancel the reset: {}", e))?;
+            cancel_sent = true;
+        }
+        if started.elapsed() > RESPONSE_TIMEOUT {
+            let _ = transport.send_cancel();
+            return Err("The key did not answer the reset request".into());
+        }
+        match transport.poll_short_response(CTAPHID_CBOR, RESPONSE_POLL_MS) {
+            Ok(None) => continue,
+            Ok(Some(_)) => break,
+            Err(e) => return Err(reset_error(e.to_string())),
+        }
+    }
+
+    log::info!("authenticatorReset completed");
+    Ok("The key has been reset. All passkeys and the PIN were erased".into())
+}
@@ -0,0 +1,108 @@
+//! Touch-to-select device chooser.
+//!
+//! authenticatorSelection is sent to every connected key at once. Each key blinks and waits for
+//! a touch; the first one to answer with success becomes the selected device and the others get
+//! CTAPHID_CANCEL so they stop blinking.
+
+use super::constants::CtapCommand;
+use super::hid::{self, CTAPHID_CBOR, HidTransport};
+use crate::device::types::FidoHidDevice;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Per-device read timeout while polling round-robin.
+const POLL_TIMEOUT_MS: i32 = 20;
+
+pub(crate) fn list_fido_devices() -> Result<Vec<FidoHidDevice>, String> {
+    hid::list_devices().map_err(|e| e.to_string())
+}
+
+/// Waits for the user to touch one of the connected keys and binds PicoForge to it.
+///
+/// With a single key connected it is selected without asking for a touch. `cancel` aborts the
+/// wait from another thread.
+pub(crate) fn select_by_touch(
+    timeout: Duration,
+    cancel: Arc<AtomicBool>,
+) -> Result<FidoHidDevice, String> {
+    let devices = list_fido_devices()?;
+    match devices.len() {
+        0 => return Err("No FIDO device found".into()),
+        1 => {
+            hid::select_device(Some(devices[0].path.clone()));
+            return Ok(devices[0].clone());
+        }
+        n => log::info!("Sending authenticatorSelection to {} devices...", n),
+    }
+
+    let mut pending: Vec<(FidoHidDevice, HidTransport)> = Vec::new();
+    for device in devices {
+        let transport = match HidTransport::open_path(&device.path) {
+            Ok(transport) => transport,
+            Err(e) => {
+                log::warn!("Skipping {}: {}", device.path, e);
+                continue;
+            }
+        };
+        match transport.begin_request(CTAPHID_CBOR, &[CtapCommand::Selection as u8]) {
+            Ok(()) => pending.push((device, transport)),
+            Err(e) => log::warn!("Skipping {}: {}", device.path, e),
+        }
+    }
+    if pending.is_empty() {
+        return Err("None of the connected keys could be opened".into());
+    }
+
+    let started = Instant::now();
+    let result = loop {
+        if cancel.load(Ordering::Relaxed) {
+            break Err("Device selection cancelled".to_string());
+        }
+        if started.elapsed() > timeout {
+            break Err("No key was touched".to_string());
+        }
+
+        let mut chosen = None;
+        pending.retain(|(device, transport)| {
+            if chosen.is_some() {
+                return true;
+            }
+            match transport.poll_short_response(CTAPHID_CBOR, POLL_TIMEOUT_MS) {
+                Ok(None) => true,
+                Ok(Some(_)) => {
+                    chosen = Some(device.clone());
+                    false
+                }
+                Err(e) => {
+                    // Keys that do not implement authenticatorSelection or were unplugged
+                    // drop out; the rest keep waiting.
+                    log::warn!("{} left the selection: {}", device.path, e);
+                    false
+                }
+            }
+        });
+
+        if let Some(device) = chosen {
+            break Ok(device);
+        }
+        if pending.is_empty() {
+            break Err("None of the connected keys support touch selection".to_string());
+        }
+    };
+
+    for (device, transport) in &pending {
+        if let Err(e) = transport.send_cancel() {
+            log::warn!("Failed to cancel selection on {}: {}", device.path, e);
+        }
+    }
+
+    let device = result?;
+    log::info!(
+        "Selected FIDO device {} ({})",
+        device.product_name,
+        device.path
+    );
+    hid::select_device(Some(device.path.clone()));
+    Ok(device)
+}
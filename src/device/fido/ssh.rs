@@ -0,0 +1,135 @@
+//! OpenSSH security key credentials: creation, and recovery of resident `ssh:` keys.
+//!
+//! Credentials are created the way OpenSSH's sk-usbhid middleware does it: the user handle is
+//! the user name zero padded to 32 bytes ("openssh" and all zeros by default), and resident keys
+//! get credProtect so that they cannot be enumerated without the PIN.
+
+use super::get_device;
+use crate::device::ssh::{
+    SSH_APPLICATION_PREFIX, SSH_DEFAULT_USER, SshSkFlags, SshSkKey, SshSkKeyType,
+};
+use crate::device::types::StoredCredential;
+use ctap_hid_fido2::fidokey::{
+    credential_management::credential_management_params::CredentialProtectionPolicy,
+    make_credential::{CredentialSupportedKeyType, Extension, MakeCredentialArgsBuilder},
+};
+use ctap_hid_fido2::public_key_credential_user_entity::PublicKeyCredentialUserEntity;
+use rand::RngExt;
+
+const SSH_USER_ID_LEN: usize = 32;
+
+/// Options matching `ssh-keygen -t *-sk -O ...`.
+#[derive(Debug, Clone)]
+pub struct SshKeyOptions {
+    pub key_type: SshSkKeyType,
+    /// FIDO RP ID, must start with `ssh:`
+    pub application: String,
+    pub resident: bool,
+    /// Every signature needs the PIN (`-O verify-required`)
+    pub verify_required: bool,
+    /// Signatures do not need a touch (`-O no-touch-required`)
+    pub no_touch: bool,
+    /// Stored in the user handle of resident keys (`-O user=`)
+    pub user: Option<String>,
+    pub comment: String,
+}
+
+pub(crate) fn generate_key(
+    options: SshKeyOptions,
+    pin: Option<String>,
+) -> Result<SshSkKey, String> {
+    if !options.application.starts_with(SSH_APPLICATION_PREFIX) {
+        return Err(format!(
+            "Application must start with \"{}\"",
+            SSH_APPLICATION_PREFIX
+        ));
+    }
+    if options.verify_required && pin.is_none() {
+        return Err("verify-required keys need the FIDO PIN".into());
+    }
+    let user = options.user.as_deref().unwrap_or(SSH_DEFAULT_USER);
+    if user.len() > SSH_USER_ID_LEN {
+        return Err(format!(
+            "User name must be at most {} bytes",
+            SSH_USER_ID_LEN
+        ));
+    }
+
+    let device = get_device()?;
+    log::info!(
+        "Creating {} SSH key for {}",
+        options.key_type.name(),
+        options.application
+    );
+
+    let mut user_id = [0u8; SSH_USER_ID_LEN];
+    if options.user.is_some() {
+        user_id[..user.len()].copy_from_slice(user.as_bytes());
+    }
+    let user_entity = PublicKeyCredentialUserEntity::new(Some(&user_id), Some(user), Some(user));
+
+    let mut challenge = [0u8; 32];
+    rand::rng().fill(&mut challenge);
+
+    let key_type = match options.key_type {
+        SshSkKeyType::EcdsaSk => CredentialSupportedKeyType::Ecdsa256,
+        SshSkKeyType::Ed25519Sk => CredentialSupportedKeyType::Ed25519,
+    };
+    let mut builder = MakeCredentialArgsBuilder::new(&options.application, &challenge)
+        .key_type(key_type)
+        .user_entity(&user_entity);
+
+    // As in OpenSSH's sk-usbhid: verify-required keys always get credProtect UV-required, so
+    // the key enforces it and not only the ssh client. Other resident keys are hidden from
+    // enumeration without UV.
+    let cred_protect = if options.verify_required {
+        Some(CredentialProtectionPolicy::UserVerificationRequired)
+    } else if options.resident {
+        Some(CredentialProtectionPolicy::UserVerificationOptionalWithCredentialIdList)
+    } else {
+        None
+    };
+    let extensions = cred_protect.map(|policy| [Extension::CredProtect(Some(policy))]);
+    if let Some(extensions) = &extensions {
+        builder = builder.extensions(extensions);
+    }
+    if options.resident {
+        builder = builder.resident_key();
+    }
+    let args = match pin.as_deref() {
+        Some(pin) => builder.pin(pin).build(),
+        None => builder.without_pin_and_uv().build(),
+    };
+
+    let attestation = device
+        .make_credential_with_args(&args)
+        .map_err(|e| format!("makeCredential failed: {:?}", e))?;
+
+    let mut flags = SshSkFlags::empty();
+    if !options.no_touch {
+        flags |= SshSkFlags::USER_PRESENCE_REQD;
+    }
+    if options.verify_required {
+        flags |= SshSkFlags::USER_VERIFICATION_REQD;
+    }
+    if options.resident {
+        flags |= SshSkFlags::RESIDENT_KEY;
+    }
+
+    Ok(SshSkKey {
+        key_type: options.key_type,
+        public_key: attestation.credential_publickey.der.clone(),
+        application: options.application,
+        flags,
+        key_handle: attestation.credential_descriptor.id.clone(),
+        comment: options.comment,
+    })
+}
+
+/// Resident credentials whose RP ID is an SSH application.
+pub(crate) fn resident_ssh_credentials(credentials: &[StoredCredential]) -> Vec<&StoredCredential> {
+    credentials
+        .iter()
+        .filter(|c| c.rp_id.starts_with(SSH_APPLICATION_PREFIX))
+        .collect()
+}
@@ -0,0 +1,195 @@
+//! Local inventory of provisioned devices, used to check that a plugged-in key is genuine.
+//!
+//! For every enrolled serial number the inventory remembers the public key of the device key
+//! (rescue `KeyDevSign`). A key proves it is the same physical device by signing a fresh random
+//! challenge that verifies against the stored public key.
+
+use crate::device::error::PFError;
+use crate::device::rescue;
+use crate::device::types::{GenuineCheckResult, GenuineStatus};
+use directories::ProjectDirs;
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryEntry {
+    /// Uncompressed P-256 device public key, hex encoded.
+    pub public_key: String,
+    /// Unix timestamp (seconds) of the enrollment.
+    pub enrolled_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeviceInventory {
+    /// Entries keyed by device serial number.
+    pub devices: BTreeMap<String, InventoryEntry>,
+}
+
+/// Default inventory location inside the application data directory.
+pub fn default_path() -> PathBuf {
+    match ProjectDirs::from("in", "suyogtandel", "picoforge") {
+        Some(dirs) => dirs.data_local_dir().join("inventory.json"),
+        None => PathBuf::from("inventory.json"),
+    }
+}
+
+impl DeviceInventory {
+    /// Loads the inventory, returning an empty one if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self, PFError> {
+        match std::fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| {
+                PFError::Io(format!("Invalid inventory file {}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(PFError::Io(format!(
+                "Could not read inventory {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PFError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| PFError::Io(format!("Could not create {}: {}", dir.display(), e)))?;
+        }
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| PFError::Io(format!("Could not encode inventory: {}", e)))?;
+        std::fs::write(path, data)
+            .map_err(|e| PFError::Io(format!("Could not write {}: {}", path.display(), e)))
+    }
+
+    pub fn get(&self, serial: &str) -> Option<&InventoryEntry> {
+        self.devices.get(serial)
+    }
+
+    pub fn enroll(&mut self, serial: String, public_key: &[u8], label: Option<String>) {
+        let enrolled_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        self.devices.insert(
+            serial,
+            InventoryEntry {
+                public_key: hex::encode_upper(public_key),
+                enrolled_at,
+                label,
+            },
+        );
+    }
+}
+
+pub(crate) fn verify_device_signature(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ECDSA_P256_SHA256_ASN1, public_key)
+        .verify(message, signature)
+        .is_ok()
+}
+
+/// Sends a fresh random challenge to the device key and returns `(challenge, signature)`.
+fn challenge_device_key() -> Result<(Vec<u8>, Vec<u8>), PFError> {
+    let mut challenge = [0u8; 32];
+    rand::rng().fill(&mut challenge);
+
+    let digest = ring::digest::digest(&ring::digest::SHA256, &challenge);
+    let digest: [u8; 32] = digest
+        .as_ref()
+        .try_into()
+        .map_err(|_| PFError::Io("Unexpected digest length".into()))?;
+
+    let signature = rescue::sign_with_device_key(&digest)?;
+    Ok((challenge.to_vec(), signature))
+}
+
+/// Records the device key of the connected device in the inventory, after checking that the
+/// device can sign with it. A serial already enrolled with another device key is only replaced
+/// with `force`, since that is exactly what a cloned or swapped device looks like.
+pub fn enroll_device(
+    inventory_path: &Path,
+    label: Option<String>,
+    force: bool,
+) -> Result<String, PFError> {
+    let mut inventory = DeviceInventory::load(inventory_path)?;
+
+    let serial = rescue::read_serial()?;
+    let public_key = rescue::get_device_public_key()?;
+    let (challenge, signature) = challenge_device_key()?;
+
+    if !verify_device_signature(&public_key, &challenge, &signature) {
+        return Err(PFError::Device(
+            "Device signature does not match its own public key".into(),
+        ));
+    }
+
+    let replaced = inventory
+        .get(&serial)
+        .is_some_and(|entry| entry.public_key != hex::encode_upper(&public_key));
+    if replaced {
+        if !force {
+            return Err(PFError::Device(format!(
+                "Serial {} is already enrolled with a different device key. Re-enroll only if the device key was regenerated on purpose.",
+                serial
+            )));
+        }
+        log::warn!("Replacing the enrolled device key for serial {}", serial);
+    }
+
+    inventory.enroll(serial.clone(), &public_key, label);
+    inventory.save(inventory_path)?;
+
+    log::info!("Enrolled device {} in {}", serial, inventory_path.display());
+    Ok(format!("Device {} enrolled", serial))
+}
+
+/// Checks that the connected device holds the device key enrolled for its serial number.
+pub fn verify_genuine_device(inventory_path: &Path) -> Result<GenuineCheckResult, PFError> {
+    let inventory = DeviceInventory::load(inventory_path)?;
+    let serial = rescue::read_serial()?;
+
+    let Some(entry) = inventory.get(&serial) else {
+        return Ok(GenuineCheckResult {
+            serial,
+            status: GenuineStatus::NotEnrolled,
+            label: None,
+            detail: "This serial number is not in the inventory".into(),
+        });
+    };
+
+    let enrolled_key = hex::decode(&entry.public_key)
+        .map_err(|e| PFError::Io(format!("Invalid public key in inventory: {}", e)))?;
+
+    // Verify against the stored key only; whatever public key the device reports now is not
+    // trusted.
+    let (challenge, signature) = challenge_device_key()?;
+    let genuine = verify_device_signature(&enrolled_key, &challenge, &signature);
+
+    let (status, detail) = if genuine {
+        (
+            GenuineStatus::Genuine,
+            "Challenge signed by the enrolled device key".to_string(),
+        )
+    } else {
+        log::warn!(
+            "Device {} failed the genuine check: signature does not match the enrolled key",
+            serial
+        );
+        (
+            GenuineStatus::PossibleClone,
+            "Signature does not match the enrolled device key. This may be a swapped or counterfeit device."
+                .to_string(),
+        )
+    };
+
+    Ok(GenuineCheckResult {
+        serial,
+        status,
+        label: entry.label.clone(),
+        detail,
+    })
+}
@@ -1,7 +1,11 @@
 //! Tauri Commands to interact with the pico-fido firmware via rescue and fido protocols.
 #![allow(unused)]
 
-use crate::{device::error::PFError, device::fido, device::rescue, device::types::*};
+use crate::{
+    device::applets, device::error::PFError, device::export, device::fido, device::inventory,
+    device::management, device::oath, device::openpgp, device::otp, device::pem, device::rescue,
+    device::secrets, device::ssh, device::types::*,
+};
 
 pub fn read_device_details() -> Result<FullDeviceStatus, PFError> {
     match rescue::read_device_details() {
@@ -13,6 +17,11 @@ pub fn read_device_details() -> Result<FullDeviceStatus, PFError> {
     }
 }
 
+/// Detects the running firmware and its applets.
+pub fn probe_capabilities() -> Result<DeviceCapabilities, PFError> {
+    applets::probe::probe_capabilities()
+}
+
 pub async fn write_config(
     config: AppConfigInput,
     method: DeviceMethod,
@@ -43,18 +52,447 @@ pub(crate) fn change_fido_pin(
 pub(crate) fn set_min_pin_length(
     current_pin: String,
     min_pin_length: u8,
+    rp_ids: Vec<String>,
+    force_change_pin: bool,
 ) -> Result<String, String> {
-    fido::set_min_pin_length(current_pin, min_pin_length)
+    fido::set_min_pin_length(current_pin, min_pin_length, rp_ids, force_change_pin)
 }
 
 pub fn reboot(to_bootsel: bool) -> Result<String, PFError> {
     rescue::reboot_device(to_bootsel)
 }
 
-pub async fn get_credentials(pin: String) -> Result<Vec<StoredCredential>, String> {
+pub fn get_credentials(pin: String) -> Result<Vec<StoredCredential>, String> {
     fido::get_credentials(pin)
 }
 
+pub(crate) fn read_large_blobs(credentials: &[StoredCredential]) -> Result<LargeBlobArray, String> {
+    fido::large_blob::read_large_blobs(credentials)
+}
+
+pub(crate) fn write_large_blob(
+    pin: String,
+    large_blob_key: String,
+    data: Option<Vec<u8>>,
+) -> Result<String, String> {
+    fido::large_blob::write_large_blob(pin, large_blob_key, data)
+}
+
+pub(crate) fn delete_large_blob_entry(pin: String, index: usize) -> Result<String, String> {
+    fido::large_blob::delete_large_blob_entry(pin, index)
+}
+
+pub(crate) fn get_pin_retries() -> Result<PinRetries, String> {
+    fido::get_pin_retries()
+}
+
+pub(crate) fn get_creds_metadata(pin: String) -> Result<CredentialCapacity, String> {
+    fido::get_creds_metadata(pin)
+}
+
+pub(crate) fn estimate_capacity(info: &FidoDeviceInfo) -> Option<CredentialCapacity> {
+    fido::estimate_capacity(info)
+}
+
 pub fn delete_credential(pin: String, credential_id: String) -> Result<String, String> {
     fido::delete_credential(pin, credential_id)
 }
+
+/// Renders the credential list as JSON or CSV for an inventory audit.
+pub fn export_credentials(
+    credentials: &[StoredCredential],
+    format: export::ExportFormat,
+    redact_user: bool,
+) -> Result<String, PFError> {
+    export::export_credentials(credentials, format, redact_user)
+}
+
+pub fn save_credential_export(
+    path: &std::path::Path,
+    credentials: &[StoredCredential],
+    format: export::ExportFormat,
+    redact_user: bool,
+) -> Result<(), PFError> {
+    let contents = export::export_credentials(credentials, format, redact_user)?;
+    std::fs::write(path, contents)
+        .map_err(|e| PFError::Io(format!("Could not write {}: {}", path.display(), e)))
+}
+
+pub(crate) fn delete_credentials(
+    pin: String,
+    credential_ids: Vec<String>,
+) -> Result<Vec<CredentialDeleteResult>, String> {
+    fido::delete_credentials(pin, credential_ids)
+}
+
+pub(crate) fn update_user_information(
+    pin: String,
+    credential_id: String,
+    user_id: String,
+    user_name: String,
+    display_name: String,
+) -> Result<String, String> {
+    fido::update_user_information(pin, credential_id, user_id, user_name, display_name)
+}
+
+pub fn generate_ea_csr() -> Result<Vec<u8>, PFError> {
+    fido::generate_ea_csr()
+}
+
+/// Signs the enterprise attestation CSR with the CA key and certificate found at the given paths.
+/// `aaguid` is the hex encoded AAGUID reported by the device, if known.
+pub fn sign_ea_csr(
+    csr_der: &[u8],
+    ca_key_path: &std::path::Path,
+    ca_cert_path: &std::path::Path,
+    validity_days: u32,
+    aaguid: Option<&str>,
+) -> Result<Vec<u8>, PFError> {
+    let aaguid = aaguid
+        .map(hex::decode)
+        .transpose()
+        .map_err(|e| PFError::Io(format!("Invalid AAGUID: {}", e)))?;
+    fido::attestation::sign_csr(
+        csr_der,
+        ca_key_path,
+        ca_cert_path,
+        validity_days,
+        aaguid.as_deref(),
+    )
+}
+
+pub(crate) fn upload_ea_certificate(pin: String, cert_der: Vec<u8>) -> Result<String, String> {
+    fido::upload_ea_certificate(pin, cert_der)
+}
+
+pub(crate) fn toggle_always_uv(pin: String) -> Result<bool, String> {
+    fido::toggle_always_uv(pin)
+}
+
+pub(crate) fn set_pin_complexity_policy(pin: String, enabled: bool) -> Result<bool, String> {
+    fido::set_pin_complexity_policy(pin, enabled)
+}
+
+pub(crate) fn wait_for_device_removal(
+    timeout: std::time::Duration,
+    cancel: &std::sync::atomic::AtomicBool,
+) -> Result<Vec<String>, String> {
+    fido::reset::wait_for_removal(timeout, cancel)
+}
+
+pub(crate) fn wait_for_device_arrival(
+    others: &[String],
+    timeout: std::time::Duration,
+    cancel: &std::sync::atomic::AtomicBool,
+) -> Result<String, String> {
+    fido::reset::wait_for_arrival(others, timeout, cancel)
+}
+
+pub(crate) fn reset_fido_device(
+    path: &str,
+    cancel: &std::sync::atomic::AtomicBool,
+) -> Result<String, String> {
+    fido::reset::reset_authenticator(path, cancel)
+}
+
+pub(crate) fn list_fido_devices() -> Result<Vec<FidoHidDevice>, String> {
+    fido::selection::list_fido_devices()
+}
+
+pub(crate) fn select_device_by_touch(
+    timeout: std::time::Duration,
+    cancel: std::sync::Arc<std::sync::atomic::AtomicBool>,
+) -> Result<FidoHidDevice, String> {
+    fido::selection::select_by_touch(timeout, cancel)
+}
+
+pub(crate) fn enable_encryption_at_rest(pin: String, serial: String) -> Result<String, String> {
+    fido::encryption::enable_encryption(pin, serial)
+}
+
+pub(crate) fn disable_encryption_at_rest(pin: String) -> Result<String, String> {
+    fido::encryption::disable_encryption(pin)
+}
+
+pub(crate) fn unlock_device(serial: String) -> Result<String, String> {
+    fido::encryption::unlock_device(serial)
+}
+
+pub fn has_host_secret(serial: &str) -> bool {
+    secrets::has_host_secret(serial)
+}
+
+pub fn host_secret_path(serial: &str) -> Option<std::path::PathBuf> {
+    secrets::secret_path(serial)
+}
+
+pub(crate) fn enable_enterprise_attestation(pin: String) -> Result<String, String> {
+    fido::enable_enterprise_attestation(pin)
+}
+
+pub fn csr_common_name(csr_der: &[u8]) -> Result<Option<String>, PFError> {
+    fido::attestation::csr_common_name(csr_der)
+}
+
+/// Writes DER data to `path` as a PEM file with the given label.
+pub fn save_pem(path: &std::path::Path, label: &str, der: &[u8]) -> Result<(), PFError> {
+    std::fs::write(path, pem::encode(label, der))
+        .map_err(|e| PFError::Io(format!("Could not write {}: {}", path.display(), e)))
+}
+
+pub fn get_device_public_key() -> Result<Vec<u8>, PFError> {
+    rescue::get_device_public_key()
+}
+
+/// Encodes the raw device public key as a PEM `PUBLIC KEY` (SubjectPublicKeyInfo) block.
+pub fn device_public_key_pem(public_key: &[u8]) -> String {
+    let mut spki = rescue::constants::P256_SPKI_PREFIX.to_vec();
+    spki.extend_from_slice(public_key);
+    pem::encode("PUBLIC KEY", &spki)
+}
+
+/// Signs the SHA-256 digest of `challenge` with the device key and checks the signature against
+/// the device public key before returning it (DER encoded).
+pub fn sign_device_challenge(challenge: &[u8]) -> Result<Vec<u8>, PFError> {
+    let digest = ring::digest::digest(&ring::digest::SHA256, challenge);
+    let digest: [u8; 32] = digest
+        .as_ref()
+        .try_into()
+        .map_err(|_| PFError::Io("Unexpected digest length".into()))?;
+
+    let public_key = rescue::get_device_public_key()?;
+    let signature = rescue::sign_with_device_key(&digest)?;
+
+    if !inventory::verify_device_signature(&public_key, challenge, &signature) {
+        return Err(PFError::Device(
+            "Device returned an invalid signature".into(),
+        ));
+    }
+
+    Ok(signature)
+}
+
+/// Records the device key of the connected device in the inventory. A serial already enrolled
+/// with another device key is only replaced with `force`.
+pub fn enroll_device(
+    inventory_path: &std::path::Path,
+    label: Option<String>,
+    force: bool,
+) -> Result<String, PFError> {
+    inventory::enroll_device(inventory_path, label, force)
+}
+
+/// Checks that the connected device holds the device key enrolled for its serial number.
+pub fn verify_genuine_device(
+    inventory_path: &std::path::Path,
+) -> Result<GenuineCheckResult, PFError> {
+    inventory::verify_genuine_device(inventory_path)
+}
+
+/// Uploads a device certificate, given either as PEM or DER.
+pub fn upload_device_certificate(cert: &[u8]) -> Result<String, PFError> {
+    let der = pem::decode_or_der(cert)?;
+    rescue::upload_device_certificate(&der)
+}
+
+pub(crate) fn run_health_check(pin: Option<String>) -> Result<HealthCheckReport, String> {
+    fido::health::run_health_check(pin)
+}
+
+/// Creates a test credential for `rp_id` with the hmac-secret extension enabled.
+pub(crate) fn create_hmac_secret_credential(
+    rp_id: String,
+    pin: Option<String>,
+    resident: bool,
+) -> Result<HmacSecretCredential, String> {
+    fido::hmac_secret::create_credential(rp_id, pin, resident)
+}
+
+/// Derives hmac-secret outputs for one or two salts. Without a credential ID a resident
+/// credential for `rp_id` is used.
+pub(crate) fn derive_hmac_secret(
+    rp_id: String,
+    credential_id: Option<Vec<u8>>,
+    salt1: [u8; 32],
+    salt2: Option<[u8; 32]>,
+    pin: Option<String>,
+) -> Result<HmacSecretOutput, String> {
+    fido::hmac_secret::derive(rp_id, credential_id, salt1, salt2, pin)
+}
+
+/// Parses a salt given as 64 hex characters, or hashes any other input with SHA-256.
+pub fn parse_hmac_salt(input: &str) -> Result<[u8; 32], String> {
+    let input = input.trim();
+    if input.len() == 2 * fido::hmac_secret::HMAC_SALT_LEN
+        && input.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        fido::hmac_secret::salt_from_hex(input)
+    } else {
+        Ok(fido::hmac_secret::salt_from_text(input))
+    }
+}
+
+pub(crate) fn generate_ssh_key(
+    options: fido::ssh::SshKeyOptions,
+    pin: Option<String>,
+) -> Result<ssh::SshSkKey, String> {
+    fido::ssh::generate_key(options, pin)
+}
+
+/// OpenSSH key handles for the resident `ssh:` credentials among `credentials`. Credentials with
+/// keys OpenSSH cannot use are skipped.
+pub fn ssh_resident_keys(credentials: &[StoredCredential]) -> Vec<ssh::SshSkKey> {
+    fido::ssh::resident_ssh_credentials(credentials)
+        .into_iter()
+        .filter_map(|credential| {
+            ssh::SshSkKey::from_credential(credential)
+                .inspect_err(|e| log::warn!("Skipping {}: {}", credential.rp_id, e))
+                .ok()
+        })
+        .collect()
+}
+
+pub fn write_ssh_key(
+    key: &ssh::SshSkKey,
+    path: &std::path::Path,
+) -> Result<(std::path::PathBuf, std::path::PathBuf), PFError> {
+    ssh::write_key_files(key, path)
+}
+
+/// Exports a credential public key as PEM (SubjectPublicKeyInfo).
+pub fn credential_public_key_pem(key: &CredentialPublicKey) -> Result<String, PFError> {
+    fido::cose::to_pem(key)
+}
+
+/// Exports a credential public key as a JSON Web Key.
+pub fn credential_public_key_jwk(key: &CredentialPublicKey) -> Result<String, PFError> {
+    fido::cose::to_jwk(key)
+}
+
+pub fn cose_algorithm_name(algorithm: Option<i32>) -> String {
+    fido::cose::algorithm_name(algorithm)
+}
+
+pub fn cose_curve_name(curve: Option<u8>) -> String {
+    fido::cose::curve_name(curve)
+}
+
+pub fn oath_accounts(password: Option<String>) -> Result<Vec<OathAccount>, PFError> {
+    oath::list_accounts(password.as_deref())
+}
+
+pub fn oath_calculate(
+    account: &OathAccount,
+    password: Option<String>,
+) -> Result<OathCode, PFError> {
+    oath::calculate(account, password.as_deref())
+}
+
+pub fn oath_parse_uri(uri: &str) -> Result<oath::OathCredentialData, PFError> {
+    oath::uri::parse_otpauth_uri(uri)
+}
+
+pub fn oath_add_account(
+    credential: oath::OathCredentialData,
+    password: Option<String>,
+) -> Result<String, PFError> {
+    oath::add_account(&credential, password.as_deref())?;
+    Ok(format!("Added {}", credential.name()))
+}
+
+pub fn oath_delete_account(name: String, password: Option<String>) -> Result<String, PFError> {
+    oath::delete_account(&name, password.as_deref())?;
+    Ok(format!("Deleted {}", name))
+}
+
+pub fn oath_rename_account(
+    account: &OathAccount,
+    issuer: Option<String>,
+    new_account: String,
+    password: Option<String>,
+) -> Result<String, PFError> {
+    let name = oath::rename_account(
+        account,
+        issuer.as_deref(),
+        &new_account,
+        password.as_deref(),
+    )?;
+    Ok(format!("Renamed to {}", name))
+}
+
+pub fn oath_set_password(
+    current_password: Option<String>,
+    new_password: Option<String>,
+) -> Result<String, PFError> {
+    oath::set_password(current_password.as_deref(), new_password.as_deref())?;
+    Ok(if new_password.is_some() {
+        "OATH password set".into()
+    } else {
+        "OATH password removed".into()
+    })
+}
+
+pub fn otp_status() -> Result<OtpStatus, PFError> {
+    otp::read_status()
+}
+
+/// Parses an optional slot access code given as 12 hex characters.
+pub fn parse_otp_access_code(input: &str) -> Result<Option<[u8; 6]>, PFError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    hex::decode(input)
+        .ok()
+        .and_then(|code| code.try_into().ok())
+        .map(Some)
+        .ok_or_else(|| PFError::Io("Access code must be 12 hex characters".into()))
+}
+
+pub fn otp_program_slot(
+    slot: otp::OtpSlot,
+    config: otp::OtpSlotConfig,
+    access_code: Option<[u8; 6]>,
+) -> Result<String, PFError> {
+    otp::program_slot(slot, &config, access_code)?;
+    Ok(format!("{} programmed", slot.label()))
+}
+
+pub fn otp_delete_slot(
+    slot: otp::OtpSlot,
+    access_code: Option<[u8; 6]>,
+) -> Result<String, PFError> {
+    otp::delete_slot(slot, access_code)?;
+    Ok(format!("{} deleted", slot.label()))
+}
+
+pub fn otp_swap_slots() -> Result<String, PFError> {
+    otp::swap_slots()?;
+    Ok("OTP slots swapped".into())
+}
+
+pub fn otp_challenge_response(slot: otp::OtpSlot, challenge: Vec<u8>) -> Result<String, PFError> {
+    otp::challenge_response(slot, &challenge).map(hex::encode)
+}
+
+pub fn management_info() -> Result<ManagementInfo, PFError> {
+    management::read_info()
+}
+
+pub fn management_set_usb_enabled(enabled: u16) -> Result<String, PFError> {
+    management::set_usb_enabled(enabled)?;
+    Ok("USB applications updated. The key is restarting.".into())
+}
+
+pub fn openpgp_status() -> Result<OpenPgpCardStatus, PFError> {
+    openpgp::read_status()
+}
+
+pub fn openpgp_change_pin(
+    pin: OpenPgpPin,
+    current: String,
+    new: String,
+) -> Result<String, PFError> {
+    openpgp::change_pin(pin, &current, &new)?;
+    Ok(format!("{} changed", pin.name()))
+}
@@ -0,0 +1,40 @@
+//! Management applet instructions and device-info tags, following the YubiKey layout that
+//! pico-fido implements.
+#![allow(unused)]
+
+pub use crate::device::applets::constants::MANAGEMENT_AID;
+
+pub const INS_WRITE_CONFIG: u8 = 0x1C;
+pub const INS_READ_CONFIG: u8 = 0x1D;
+
+// Device-info tags
+pub const TAG_USB_SUPPORTED: u8 = 0x01;
+pub const TAG_SERIAL: u8 = 0x02;
+pub const TAG_USB_ENABLED: u8 = 0x03;
+pub const TAG_FORM_FACTOR: u8 = 0x04;
+pub const TAG_VERSION: u8 = 0x05;
+pub const TAG_AUTO_EJECT_TIMEOUT: u8 = 0x06;
+pub const TAG_CHALRESP_TIMEOUT: u8 = 0x07;
+pub const TAG_DEVICE_FLAGS: u8 = 0x08;
+pub const TAG_CONFIG_LOCK: u8 = 0x0A;
+pub const TAG_UNLOCK: u8 = 0x0B;
+pub const TAG_REBOOT: u8 = 0x0C;
+pub const TAG_NFC_SUPPORTED: u8 = 0x0D;
+pub const TAG_NFC_ENABLED: u8 = 0x0E;
+
+// Application bits in the supported/enabled masks
+pub const CAP_OTP: u16 = 0x0001;
+pub const CAP_U2F: u16 = 0x0002;
+pub const CAP_OPENPGP: u16 = 0x0008;
+pub const CAP_PIV: u16 = 0x0010;
+pub const CAP_OATH: u16 = 0x0020;
+pub const CAP_HSMAUTH: u16 = 0x0100;
+pub const CAP_FIDO2: u16 = 0x0200;
+
+/// Applications served over the FIDO HID interface
+pub const CAPS_FIDO_HID: u16 = CAP_U2F | CAP_FIDO2;
+/// Applications served over CCID, which PicoForge uses for every applet page
+pub const CAPS_CCID: u16 = CAP_OPENPGP | CAP_PIV | CAP_OATH | CAP_HSMAUTH;
+
+/// Status returned while the configuration is locked
+pub const SW_SECURITY_STATUS_NOT_SATISFIED: u16 = 0x6982;
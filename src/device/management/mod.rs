@@ -0,0 +1,130 @@
+//! The YubiKey-compatible management applet in pico-fido, which controls the applications
+//! enabled over USB.
+
+pub mod constants;
+
+use crate::device::applets::{AppletCard, constants::SW_SUCCESS, tlv};
+use crate::device::error::PFError;
+use crate::device::management::constants::*;
+use crate::device::rescue::constants::APDU_CLA_ISO;
+use crate::device::types::{ManagementApp, ManagementInfo};
+
+impl ManagementApp {
+    /// Bit of the application in the supported/enabled masks.
+    pub fn capability(&self) -> u16 {
+        match self {
+            Self::Otp => CAP_OTP,
+            Self::U2f => CAP_U2F,
+            Self::Fido2 => CAP_FIDO2,
+            Self::Oath => CAP_OATH,
+            Self::OpenPgp => CAP_OPENPGP,
+            Self::Piv => CAP_PIV,
+            Self::HsmAuth => CAP_HSMAUTH,
+        }
+    }
+}
+
+fn read_u16(value: &[u8]) -> u16 {
+    match *value {
+        [b] => b as u16,
+        [hi, lo, ..] => u16::from_be_bytes([hi, lo]),
+        [] => 0,
+    }
+}
+
+/// Parses the READ CONFIG response: a length byte, then the device-info TLVs.
+fn parse_info(data: &[u8], select_response: &[u8]) -> Result<ManagementInfo, PFError> {
+    let (len, rest) = data
+        .split_first()
+        .ok_or_else(|| PFError::Device("Empty management config".into()))?;
+    let items = tlv::parse(rest.get(..*len as usize).unwrap_or(rest))?;
+
+    let version = tlv::find(&items, TAG_VERSION)
+        .and_then(|v| match *v {
+            [major, minor, patch] => Some(format!("{}.{}.{}", major, minor, patch)),
+            _ => None,
+        })
+        .or_else(|| {
+            // Older firmware only reports its version in the select response.
+            let text = String::from_utf8_lossy(select_response);
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        });
+    let serial = tlv::find(&items, TAG_SERIAL)
+        .and_then(|v| v.try_into().ok())
+        .map(u32::from_be_bytes);
+    let usb_supported = tlv::find(&items, TAG_USB_SUPPORTED).map(read_u16);
+    let usb_enabled = tlv::find(&items, TAG_USB_ENABLED).map(read_u16);
+
+    Ok(ManagementInfo {
+        version,
+        serial,
+        usb_supported: usb_supported.unwrap_or_default(),
+        usb_enabled: usb_enabled.or(usb_supported).unwrap_or_default(),
+        config_locked: tlv::find(&items, TAG_CONFIG_LOCK).is_some_and(|v| v.first() == Some(&1)),
+    })
+}
+
+fn open() -> Result<(AppletCard, Vec<u8>), PFError> {
+    let card = AppletCard::connect()?;
+    let select_response = card.select(MANAGEMENT_AID)?;
+    Ok((card, select_response))
+}
+
+fn read_config(card: &AppletCard, select_response: &[u8]) -> Result<ManagementInfo, PFError> {
+    let (data, sw) = card.transmit(APDU_CLA_ISO, INS_READ_CONFIG, 0, 0, &[])?;
+    if sw != SW_SUCCESS {
+        return Err(PFError::Device(format!(
+            "Reading the management config failed with status {:04X}",
+            sw
+        )));
+    }
+    parse_info(&data, select_response)
+}
+
+pub fn read_info() -> Result<ManagementInfo, PFError> {
+    let (card, select_response) = open()?;
+    read_config(&card, &select_response)
+}
+
+/// Sets the applications enabled over USB and reboots the key so the host sees the new
+/// interfaces.
+pub fn set_usb_enabled(enabled: u16) -> Result<(), PFError> {
+    let (card, select_response) = open()?;
+    let info = read_config(&card, &select_response)?;
+    if info.config_locked {
+        return Err(PFError::Device(
+            "The configuration is locked with a lock code".into(),
+        ));
+    }
+    if enabled & !info.usb_supported != 0 {
+        return Err(PFError::Device(
+            "The key does not support every selected application".into(),
+        ));
+    }
+    if enabled == 0 {
+        return Err(PFError::Io(
+            "At least one application must stay enabled".into(),
+        ));
+    }
+
+    let mut tlvs = tlv::encode(TAG_USB_ENABLED, &enabled.to_be_bytes());
+    tlvs.extend(tlv::encode(TAG_REBOOT, &[]));
+    let mut data = vec![tlvs.len() as u8];
+    data.extend(tlvs);
+
+    let (_, sw) = card.transmit(APDU_CLA_ISO, INS_WRITE_CONFIG, 0, 0, &data)?;
+    match sw {
+        SW_SUCCESS => {
+            log::info!("Enabled USB applications set to {:04X}", enabled);
+            Ok(())
+        }
+        SW_SECURITY_STATUS_NOT_SATISFIED => Err(PFError::Device(
+            "The key refused the change: the configuration is locked".into(),
+        )),
+        sw => Err(PFError::Device(format!(
+            "Writing the management config failed with status {:04X}",
+            sw
+        ))),
+    }
+}
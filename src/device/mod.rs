@@ -1,5 +1,16 @@
+pub mod applets;
 pub mod error;
+pub mod export;
 pub mod fido;
+pub mod inventory;
 pub mod io;
+pub mod management;
+pub mod oath;
+pub mod openpgp;
+pub mod otp;
+pub mod pem;
+pub mod reader;
 pub mod rescue;
+pub mod secrets;
+pub mod ssh;
 pub mod types;
@@ -0,0 +1,60 @@
+//! YKOATH instructions, tags and defaults, as implemented by the OATH applet of pico-fido.
+#![allow(unused)]
+
+pub use crate::device::applets::constants::OATH_AID;
+
+// Instructions
+pub const INS_PUT: u8 = 0x01;
+pub const INS_DELETE: u8 = 0x02;
+pub const INS_SET_CODE: u8 = 0x03;
+pub const INS_RESET: u8 = 0x04;
+pub const INS_RENAME: u8 = 0x05;
+pub const INS_LIST: u8 = 0xA1;
+pub const INS_CALCULATE: u8 = 0xA2;
+pub const INS_VALIDATE: u8 = 0xA3;
+pub const INS_CALCULATE_ALL: u8 = 0xA4;
+pub const INS_SEND_REMAINING: u8 = 0xA5;
+
+/// P2 for CALCULATE / CALCULATE ALL: return truncated codes
+pub const P2_TRUNCATED: u8 = 0x01;
+
+// Tags
+pub const TAG_NAME: u8 = 0x71;
+pub const TAG_NAME_LIST: u8 = 0x72;
+pub const TAG_KEY: u8 = 0x73;
+pub const TAG_CHALLENGE: u8 = 0x74;
+pub const TAG_RESPONSE: u8 = 0x75;
+pub const TAG_TRUNCATED: u8 = 0x76;
+pub const TAG_HOTP: u8 = 0x77;
+pub const TAG_PROPERTY: u8 = 0x78;
+pub const TAG_VERSION: u8 = 0x79;
+pub const TAG_IMF: u8 = 0x7A;
+pub const TAG_ALGORITHM: u8 = 0x7B;
+pub const TAG_TOUCH: u8 = 0x7C;
+
+/// Property byte (sent without a length) that makes a credential require touch
+pub const PROP_REQUIRE_TOUCH: u8 = 0x02;
+
+// Type and algorithm share one byte: high nibble type, low nibble algorithm
+pub const TYPE_MASK: u8 = 0xF0;
+pub const ALGORITHM_MASK: u8 = 0x0F;
+pub const TYPE_HOTP: u8 = 0x10;
+pub const TYPE_TOTP: u8 = 0x20;
+pub const ALGORITHM_SHA1: u8 = 0x01;
+pub const ALGORITHM_SHA256: u8 = 0x02;
+pub const ALGORITHM_SHA512: u8 = 0x03;
+
+// Status words
+pub const SW_AUTH_REQUIRED: u16 = 0x6982;
+pub const SW_WRONG_PASSWORD: u16 = 0x6984;
+pub const SW_NO_SPACE: u16 = 0x6A84;
+
+pub const DEFAULT_PERIOD: u32 = 30;
+pub const DEFAULT_DIGITS: u8 = 6;
+pub const MAX_NAME_LEN: usize = 64;
+/// Shorter secrets are zero padded, like every YKOATH client does
+pub const HMAC_MINIMUM_KEY_SIZE: usize = 14;
+
+/// The access key is PBKDF2-HMAC-SHA1(password, device salt, 1000, 16)
+pub const PASSWORD_ITERATIONS: u32 = 1000;
+pub const PASSWORD_KEY_LEN: usize = 16;
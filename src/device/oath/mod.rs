@@ -0,0 +1,548 @@
+//! TOTP/HOTP accounts in the YKOATH applet of pico-fido, over PC/SC.
+//!
+//! Every operation opens a session: the applet is selected and, if an access password is set,
+//! the select response carries a challenge that has to be answered with an HMAC keyed by the
+//! password before anything else is accepted. The protocol is documented at
+//! <https://developers.yubico.com/OATH/YKOATH_Protocol.html>.
+
+pub mod constants;
+pub mod uri;
+
+use crate::device::applets::{AppletCard, constants::SW_SUCCESS, tlv};
+use crate::device::error::PFError;
+use crate::device::oath::constants::*;
+use crate::device::rescue::constants::APDU_CLA_ISO;
+use crate::device::types::{OathAccount, OathAlgorithm, OathCode, OathType};
+use rand::RngExt;
+use ring::{digest, hmac, pbkdf2};
+use std::num::NonZeroU32;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A new account, from an `otpauth://` URI or entered by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OathCredentialData {
+    pub issuer: Option<String>,
+    pub account: String,
+    pub secret: Vec<u8>,
+    pub oath_type: OathType,
+    pub algorithm: OathAlgorithm,
+    pub digits: u8,
+    pub period: u32,
+    /// Initial HOTP counter
+    pub counter: u32,
+    pub touch: bool,
+}
+
+impl OathCredentialData {
+    pub fn name(&self) -> String {
+        credential_name(
+            self.oath_type,
+            self.period,
+            self.issuer.as_deref(),
+            &self.account,
+        )
+    }
+}
+
+/// Builds the name stored on the key. TOTP accounts with a non-default period carry it as a
+/// `period/` prefix, the convention all YKOATH clients share.
+pub fn credential_name(
+    oath_type: OathType,
+    period: u32,
+    issuer: Option<&str>,
+    account: &str,
+) -> String {
+    let mut name = match issuer {
+        Some(issuer) if !issuer.is_empty() => format!("{}:{}", issuer, account),
+        _ => account.to_string(),
+    };
+    if oath_type == OathType::Totp && period != DEFAULT_PERIOD {
+        name = format!("{}/{}", period, name);
+    }
+    name
+}
+
+/// Splits a stored name into period, issuer and account.
+fn parse_name(oath_type: OathType, name: &str) -> (u32, Option<String>, String) {
+    let mut period = DEFAULT_PERIOD;
+    let mut rest = name;
+    if oath_type == OathType::Totp
+        && let Some((prefix, tail)) = name.split_once('/')
+        && let Ok(value) = prefix.parse::<u32>()
+    {
+        period = value;
+        rest = tail;
+    }
+    match rest.split_once(':') {
+        Some((issuer, account)) => (period, Some(issuer.to_string()), account.to_string()),
+        None => (period, None, rest.to_string()),
+    }
+}
+
+fn type_from_byte(byte: u8) -> Result<OathType, PFError> {
+    match byte & TYPE_MASK {
+        TYPE_HOTP => Ok(OathType::Hotp),
+        TYPE_TOTP => Ok(OathType::Totp),
+        other => Err(PFError::Device(format!("Unknown OATH type {:02X}", other))),
+    }
+}
+
+fn algorithm_from_byte(byte: u8) -> Result<OathAlgorithm, PFError> {
+    match byte & ALGORITHM_MASK {
+        ALGORITHM_SHA1 => Ok(OathAlgorithm::Sha1),
+        ALGORITHM_SHA256 => Ok(OathAlgorithm::Sha256),
+        ALGORITHM_SHA512 => Ok(OathAlgorithm::Sha512),
+        other => Err(PFError::Device(format!(
+            "Unknown OATH algorithm {:02X}",
+            other
+        ))),
+    }
+}
+
+fn type_byte(oath_type: OathType) -> u8 {
+    match oath_type {
+        OathType::Hotp => TYPE_HOTP,
+        OathType::Totp => TYPE_TOTP,
+    }
+}
+
+fn algorithm_byte(algorithm: OathAlgorithm) -> u8 {
+    match algorithm {
+        OathAlgorithm::Sha1 => ALGORITHM_SHA1,
+        OathAlgorithm::Sha256 => ALGORITHM_SHA256,
+        OathAlgorithm::Sha512 => ALGORITHM_SHA512,
+    }
+}
+
+fn hmac_algorithm(algorithm: OathAlgorithm) -> hmac::Algorithm {
+    match algorithm {
+        OathAlgorithm::Sha1 => hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+        OathAlgorithm::Sha256 => hmac::HMAC_SHA256,
+        OathAlgorithm::Sha512 => hmac::HMAC_SHA512,
+    }
+}
+
+/// Secrets longer than the hash block size are hashed and short ones padded, as HMAC would.
+fn shorten_secret(secret: &[u8], algorithm: OathAlgorithm) -> Vec<u8> {
+    let (hash, block_size) = match algorithm {
+        OathAlgorithm::Sha1 => (&digest::SHA1_FOR_LEGACY_USE_ONLY, 64),
+        OathAlgorithm::Sha256 => (&digest::SHA256, 64),
+        OathAlgorithm::Sha512 => (&digest::SHA512, 128),
+    };
+    let mut secret = if secret.len() > block_size {
+        digest::digest(hash, secret).as_ref().to_vec()
+    } else {
+        secret.to_vec()
+    };
+    if secret.len() < HMAC_MINIMUM_KEY_SIZE {
+        secret.resize(HMAC_MINIMUM_KEY_SIZE, 0);
+    }
+    secret
+}
+
+fn derive_access_key(password: &str, salt: &[u8]) -> [u8; PASSWORD_KEY_LEN] {
+    let mut key = [0u8; PASSWORD_KEY_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA1,
+        NonZeroU32::new(PASSWORD_ITERATIONS).unwrap(),
+        salt,
+        password.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn format_code(truncated: &[u8]) -> Result<String, PFError> {
+    let [digits, value @ ..] = truncated else {
+        return Err(PFError::Device("Empty OATH response".into()));
+    };
+    let value: [u8; 4] = value
+        .try_into()
+        .map_err(|_| PFError::Device("Malformed OATH response".into()))?;
+    let digits = *digits as usize;
+    let code = (u32::from_be_bytes(value) & 0x7FFF_FFFF) % 10u32.pow(digits as u32);
+    Ok(format!("{:0width$}", code, width = digits))
+}
+
+fn status_error(sw: u16, action: &str) -> PFError {
+    match sw {
+        SW_AUTH_REQUIRED => PFError::Device("The OATH applet is locked with a password".into()),
+        SW_WRONG_PASSWORD => PFError::Device("Wrong OATH password".into()),
+        SW_NO_SPACE => PFError::Device("No space left for OATH accounts".into()),
+        _ => PFError::Device(format!("{} failed with status {:04X}", action, sw)),
+    }
+}
+
+/// An authenticated connection to the OATH applet.
+struct OathSession {
+    card: AppletCard,
+    salt: Vec<u8>,
+}
+
+impl OathSession {
+    fn open(password: Option<&str>) -> Result<Self, PFError> {
+        let card = AppletCard::connect()?.with_send_remaining(INS_SEND_REMAINING);
+        let select = tlv::parse(&card.select(OATH_AID)?)?;
+        let salt = tlv::find(&select, TAG_NAME).unwrap_or_default().to_vec();
+        let challenge = tlv::find(&select, TAG_CHALLENGE).map(<[u8]>::to_vec);
+
+        let session = Self { card, salt };
+        if let Some(challenge) = challenge {
+            let algorithm = match tlv::find(&select, TAG_ALGORITHM) {
+                Some([byte]) => algorithm_from_byte(*byte)?,
+                _ => OathAlgorithm::Sha1,
+            };
+            let password = password.ok_or_else(|| {
+                PFError::Device("The OATH applet is locked with a password".into())
+            })?;
+            session.validate(password, &challenge, algorithm)?;
+        }
+        Ok(session)
+    }
+
+    fn send(&self, ins: u8, p1: u8, p2: u8, data: &[u8], action: &str) -> Result<Vec<u8>, PFError> {
+        let (response, sw) = self.card.transmit(APDU_CLA_ISO, ins, p1, p2, data)?;
+        if sw != SW_SUCCESS {
+            return Err(status_error(sw, action));
+        }
+        Ok(response)
+    }
+
+    /// Answers the select challenge, and checks the applet's answer to ours so that a card
+    /// that accepts any password is noticed.
+    fn validate(
+        &self,
+        password: &str,
+        challenge: &[u8],
+        algorithm: OathAlgorithm,
+    ) -> Result<(), PFError> {
+        let key = hmac::Key::new(
+            hmac_algorithm(algorithm),
+            &derive_access_key(password, &self.salt),
+        );
+        let mut our_challenge = [0u8; 8];
+        rand::rng().fill(&mut our_challenge);
+
+        let mut data = tlv::encode(TAG_RESPONSE, hmac::sign(&key, challenge).as_ref());
+        data.extend(tlv::encode(TAG_CHALLENGE, &our_challenge));
+        let response = tlv::parse(&self.send(INS_VALIDATE, 0, 0, &data, "Validate")?)?;
+
+        let expected = hmac::sign(&key, &our_challenge);
+        if tlv::find(&response, TAG_RESPONSE) != Some(expected.as_ref()) {
+            return Err(PFError::Device(
+                "The OATH applet gave a wrong response to the password challenge".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<OathAccount>, PFError> {
+        let response = tlv::parse(&self.send(INS_LIST, 0, 0, &[], "List")?)?;
+        response
+            .iter()
+            .filter(|(tag, _)| *tag == TAG_NAME_LIST)
+            .map(|(_, value)| {
+                let [kind, name @ ..] = value.as_slice() else {
+                    return Err(PFError::Device("Empty OATH list entry".into()));
+                };
+                let name = String::from_utf8_lossy(name).to_string();
+                let oath_type = type_from_byte(*kind)?;
+                let (period, issuer, account) = parse_name(oath_type, &name);
+                Ok(OathAccount {
+                    name,
+                    issuer,
+                    account,
+                    oath_type,
+                    algorithm: algorithm_from_byte(*kind)?,
+                    period,
+                    touch_required: false,
+                    code: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Calculates every TOTP account with the default period in one command. HOTP and touch
+    /// accounts are only reported, since calculating them changes state or needs the user.
+    fn calculate_all(&self, accounts: &mut [OathAccount]) -> Result<(), PFError> {
+        let now = unix_time();
+        let counter = now / DEFAULT_PERIOD as u64;
+        let data = tlv::encode(TAG_CHALLENGE, &counter.to_be_bytes());
+        let response =
+            tlv::parse(&self.send(INS_CALCULATE_ALL, 0, P2_TRUNCATED, &data, "Calculate")?)?;
+
+        let mut name = None;
+        for (tag, value) in response {
+            if tag == TAG_NAME {
+                name = Some(String::from_utf8_lossy(&value).to_string());
+                continue;
+            }
+            let Some(account) = name
+                .take()
+                .and_then(|name| accounts.iter_mut().find(|a| a.name == name))
+            else {
+                continue;
+            };
+            match tag {
+                TAG_TOUCH => account.touch_required = true,
+                TAG_TRUNCATED if account.period == DEFAULT_PERIOD => {
+                    let valid_from = counter * DEFAULT_PERIOD as u64;
+                    account.code = Some(OathCode {
+                        code: format_code(&value)?,
+                        valid_from,
+                        valid_to: Some(valid_from + DEFAULT_PERIOD as u64),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn calculate(&self, account: &OathAccount) -> Result<OathCode, PFError> {
+        let (challenge, valid_from, valid_to) = match account.oath_type {
+            OathType::Totp => {
+                let period = account.period.max(1) as u64;
+                let counter = unix_time() / period;
+                (
+                    counter.to_be_bytes().to_vec(),
+                    counter * period,
+                    Some((counter + 1) * period),
+                )
+            }
+            OathType::Hotp => (Vec::new(), unix_time(), None),
+        };
+        let mut data = tlv::encode(TAG_NAME, account.name.as_bytes());
+        data.extend(tlv::encode(TAG_CHALLENGE, &challenge));
+        let response =
+            tlv::parse(&self.send(INS_CALCULATE, 0, P2_TRUNCATED, &data, "Calculate")?)?;
+        let truncated = tlv::find(&response, TAG_TRUNCATED)
+            .ok_or_else(|| PFError::Device("The OATH applet returned no code".into()))?;
+
+        Ok(OathCode {
+            code: format_code(truncated)?,
+            valid_from,
+            valid_to,
+        })
+    }
+
+    fn put(&self, credential: &OathCredentialData) -> Result<(), PFError> {
+        let mut key = vec![
+            type_byte(credential.oath_type) | algorithm_byte(credential.algorithm),
+            credential.digits,
+        ];
+        key.extend(shorten_secret(&credential.secret, credential.algorithm));
+
+        let mut data = tlv::encode(TAG_NAME, credential.name().as_bytes());
+        data.extend(tlv::encode(TAG_KEY, &key));
+        if credential.touch {
+            data.extend_from_slice(&[TAG_PROPERTY, PROP_REQUIRE_TOUCH]);
+        }
+        if credential.oath_type == OathType::Hotp && credential.counter > 0 {
+            data.extend(tlv::encode(TAG_IMF, &credential.counter.to_be_bytes()));
+        }
+        self.send(INS_PUT, 0, 0, &data, "Adding the account")?;
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), PFError> {
+        let data = tlv::encode(TAG_NAME, name.as_bytes());
+        self.send(INS_DELETE, 0, 0, &data, "Deleting the account")?;
+        Ok(())
+    }
+
+    fn rename(&self, name: &str, new_name: &str) -> Result<(), PFError> {
+        let mut data = tlv::encode(TAG_NAME, name.as_bytes());
+        data.extend(tlv::encode(TAG_NAME, new_name.as_bytes()));
+        self.send(INS_RENAME, 0, 0, &data, "Renaming the account")?;
+        Ok(())
+    }
+
+    fn set_password(&self, password: Option<&str>) -> Result<(), PFError> {
+        let Some(password) = password else {
+            self.send(
+                INS_SET_CODE,
+                0,
+                0,
+                &tlv::encode(TAG_KEY, &[]),
+                "Clearing the password",
+            )?;
+            return Ok(());
+        };
+
+        let access_key = derive_access_key(password, &self.salt);
+        let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, &access_key);
+        let mut challenge = [0u8; 8];
+        rand::rng().fill(&mut challenge);
+
+        let mut key_data = vec![TYPE_TOTP | ALGORITHM_SHA1];
+        key_data.extend_from_slice(&access_key);
+        let mut data = tlv::encode(TAG_KEY, &key_data);
+        data.extend(tlv::encode(TAG_CHALLENGE, &challenge));
+        data.extend(tlv::encode(
+            TAG_RESPONSE,
+            hmac::sign(&key, &challenge).as_ref(),
+        ));
+        self.send(INS_SET_CODE, 0, 0, &data, "Setting the password")?;
+        Ok(())
+    }
+}
+
+/// Lists the accounts with the codes that can be calculated without a touch.
+pub fn list_accounts(password: Option<&str>) -> Result<Vec<OathAccount>, PFError> {
+    let session = OathSession::open(password)?;
+    let mut accounts = session.list()?;
+    session.calculate_all(&mut accounts)?;
+
+    for account in accounts.iter_mut().filter(|a| {
+        a.oath_type == OathType::Totp && a.period != DEFAULT_PERIOD && !a.touch_required
+    }) {
+        match session.calculate(account) {
+            Ok(code) => account.code = Some(code),
+            Err(e) => log::warn!("Could not calculate {}: {}", account.name, e),
+        }
+    }
+
+    log::info!("Read {} OATH accounts", accounts.len());
+    Ok(accounts)
+}
+
+/// Calculates one code. HOTP advances the counter; touch accounts block until touched.
+pub fn calculate(account: &OathAccount, password: Option<&str>) -> Result<OathCode, PFError> {
+    OathSession::open(password)?.calculate(account)
+}
+
+pub fn add_account(credential: &OathCredentialData, password: Option<&str>) -> Result<(), PFError> {
+    if credential.account.is_empty() {
+        return Err(PFError::Io("Account name is empty".into()));
+    }
+    if credential.secret.is_empty() {
+        return Err(PFError::Io("Secret is empty".into()));
+    }
+    if !(6..=8).contains(&credential.digits) {
+        return Err(PFError::Io("Digits must be 6, 7 or 8".into()));
+    }
+    let name = credential.name();
+    if name.len() > MAX_NAME_LEN {
+        return Err(PFError::Io(format!(
+            "Issuer and account name must be at most {} bytes together",
+            MAX_NAME_LEN
+        )));
+    }
+
+    let session = OathSession::open(password)?;
+    if session.list()?.iter().any(|a| a.name == name) {
+        return Err(PFError::Io(format!(
+            "An account named {} already exists",
+            name
+        )));
+    }
+    session.put(credential)?;
+    log::info!("Added OATH account {}", name);
+    Ok(())
+}
+
+pub fn delete_account(name: &str, password: Option<&str>) -> Result<(), PFError> {
+    OathSession::open(password)?.delete(name)?;
+    log::info!("Deleted OATH account {}", name);
+    Ok(())
+}
+
+/// Renames an account, keeping its period prefix.
+pub fn rename_account(
+    account: &OathAccount,
+    issuer: Option<&str>,
+    new_account: &str,
+    password: Option<&str>,
+) -> Result<String, PFError> {
+    if new_account.is_empty() {
+        return Err(PFError::Io("Account name is empty".into()));
+    }
+    let new_name = credential_name(account.oath_type, account.period, issuer, new_account);
+    if new_name.len() > MAX_NAME_LEN {
+        return Err(PFError::Io(format!(
+            "Issuer and account name must be at most {} bytes together",
+            MAX_NAME_LEN
+        )));
+    }
+
+    OathSession::open(password)?.rename(&account.name, &new_name)?;
+    log::info!("Renamed OATH account {} to {}", account.name, new_name);
+    Ok(new_name)
+}
+
+/// Sets a new access password, or removes it when `new_password` is `None`.
+pub fn set_password(
+    current_password: Option<&str>,
+    new_password: Option<&str>,
+) -> Result<(), PFError> {
+    let session = OathSession::open(current_password)?;
+    session.set_password(new_password)?;
+    log::info!(
+        "{} the OATH access password",
+        if new_password.is_some() {
+            "Set"
+        } else {
+            "Cleared"
+        }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credential_name_adds_issuer_and_period() {
+        assert_eq!(
+            credential_name(OathType::Totp, DEFAULT_PERIOD, Some("GitHub"), "alice"),
+            "GitHub:alice"
+        );
+        assert_eq!(
+            credential_name(OathType::Totp, 60, Some("GitHub"), "alice"),
+            "60/GitHub:alice"
+        );
+        assert_eq!(
+            credential_name(OathType::Totp, 60, Some(""), "bob"),
+            "60/bob"
+        );
+        // HOTP names never carry a period.
+        assert_eq!(credential_name(OathType::Hotp, 60, None, "bob"), "bob");
+    }
+
+    #[test]
+    fn parse_name_reverses_credential_name() {
+        for (oath_type, period, issuer, account) in [
+            (OathType::Totp, DEFAULT_PERIOD, Some("GitHub"), "alice"),
+            (OathType::Totp, 60, Some("GitHub"), "alice"),
+            (OathType::Totp, 15, None, "bob"),
+            (OathType::Hotp, DEFAULT_PERIOD, Some("Bank"), "carol"),
+        ] {
+            let name = credential_name(oath_type, period, issuer, account);
+            assert_eq!(
+                parse_name(oath_type, &name),
+                (period, issuer.map(str::to_string), account.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn parse_name_keeps_slashes_that_are_not_a_period() {
+        assert_eq!(
+            parse_name(OathType::Totp, "a/b:c"),
+            (DEFAULT_PERIOD, Some("a/b".into()), "c".into())
+        );
+        assert_eq!(
+            parse_name(OathType::Hotp, "60/bob"),
+            (DEFAULT_PERIOD, None, "60/bob".into())
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Parsing of `otpauth://` URIs (the Key Uri Format used by QR codes) and Base32 secrets.
+
+use super::OathCredentialData;
+use super::constants::{DEFAULT_DIGITS, DEFAULT_PERIOD};
+use crate::device::error::PFError;
+use crate::device::types::{OathAlgorithm, OathType};
+
+/// Decodes a Base32 secret, ignoring case, spaces and padding.
+pub fn decode_secret(secret: &str) -> Result<Vec<u8>, PFError> {
+    let normalized: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=' && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    data_encoding::BASE32_NOPAD
+        .decode(normalized.as_bytes())
+        .map_err(|e| PFError::Io(format!("Secret is not valid Base32: {}", e)))
+}
+
+pub fn parse_algorithm(name: &str) -> Result<OathAlgorithm, PFError> {
+    match name.to_ascii_uppercase().as_str() {
+        "SHA1" => Ok(OathAlgorithm::Sha1),
+        "SHA256" => Ok(OathAlgorithm::Sha256),
+        "SHA512" => Ok(OathAlgorithm::Sha512),
+        other => Err(PFError::Io(format!("Unsupported algorithm {}", other))),
+    }
+}
+
+/// Parses `otpauth://totp/Issuer:account?secret=...&issuer=...&algorithm=...&digits=...&period=...`.
+pub fn parse_otpauth_uri(uri: &str) -> Result<OathCredentialData, PFError> {
+    let invalid = |msg: &str| PFError::Io(format!("Invalid otpauth URI: {}", msg));
+    let url = url::Url::parse(uri.trim()).map_err(|e| invalid(&e.to_string()))?;
+    if url.scheme() != "otpauth" {
+        return Err(invalid("scheme must be otpauth"));
+    }
+    let oath_type = match url.host_str().map(str::to_ascii_lowercase).as_deref() {
+        Some("totp") => OathType::Totp,
+        Some("hotp") => OathType::Hotp,
+        _ => return Err(invalid("type must be totp or hotp")),
+    };
+
+    let label = percent_encoding::percent_decode_str(url.path().trim_start_matches('/'))
+        .decode_utf8_lossy()
+        .to_string();
+    let (mut issuer, account) = match label.split_once(':') {
+        Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim().to_string()),
+        None => (None, label.trim().to_string()),
+    };
+
+    let mut credential = OathCredentialData {
+        issuer: None,
+        account,
+        secret: Vec::new(),
+        oath_type,
+        algorithm: OathAlgorithm::Sha1,
+        digits: DEFAULT_DIGITS,
+        period: DEFAULT_PERIOD,
+        counter: 0,
+        touch: false,
+    };
+    let number = |key: &str, value: &str| {
+        value
+            .parse::<u32>()
+            .map_err(|_| invalid(&format!("{} must be a number", key)))
+    };
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "secret" => credential.secret = decode_secret(&value)?,
+            // The issuer parameter wins over the label prefix.
+            "issuer" => issuer = Some(value.trim().to_string()),
+            "algorithm" => credential.algorithm = parse_algorithm(&value)?,
+            "digits" => {
+                credential.digits = value
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|digits| (6..=8).contains(digits))
+                    .ok_or_else(|| invalid("digits must be 6, 7 or 8"))?
+            }
+            "period" => credential.period = number("period", &value)?,
+            "counter" => credential.counter = number("counter", &value)?,
+            _ => {}
+        }
+    }
+    if credential.secret.is_empty() {
+        return Err(invalid("secret is missing"));
+    }
+    if credential.period == 0 {
+        return Err(invalid("period must be positive"));
+    }
+    credential.issuer = issuer.filter(|i| !i.is_empty());
+    Ok(credential)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_secret_ignores_case_spaces_and_padding() {
+        assert_eq!(decode_secret("JBSWY3DP").unwrap(), b"Hello");
+        assert_eq!(decode_secret("jbsw y3dp").unwrap(), b"Hello");
+        assert_eq!(decode_secret("JBSW-Y3DP-EE======").unwrap(), b"Hello!");
+        assert!(decode_secret("JBSWY3D1").is_err());
+    }
+
+    #[test]
+    fn parses_full_uri() {
+        let credential = parse_otpauth_uri(
+            "otpauth://totp/Example:alice%40example.com?secret=JBSWY3DPEHPK3PXP\
+             &issuer=Example&algorithm=SHA256&digits=8&period=60",
+        )
+        .unwrap();
+        assert_eq!(credential.oath_type, OathType::Totp);
+        assert_eq!(credential.issuer.as_deref(), Some("Example"));
+        assert_eq!(credential.account, "alice@example.com");
+        assert_eq!(credential.secret, b"Hello!\xDE\xAD\xBE\xEF");
+        assert_eq!(credential.algorithm, OathAlgorithm::Sha256);
+        assert_eq!(credential.digits, 8);
+        assert_eq!(credential.period, 60);
+    }
+
+    #[test]
+    fn applies_defaults_and_issuer_parameter() {
+        let credential =
+            parse_otpauth_uri("otpauth://hotp/Label:bob?secret=JBSWY3DP&issuer=Other&counter=5")
+                .unwrap();
+        assert_eq!(credential.oath_type, OathType::Hotp);
+        assert_eq!(credential.issuer.as_deref(), Some("Other"));
+        assert_eq!(credential.account, "bob");
+        assert_eq!(credential.algorithm, OathAlgorithm::Sha1);
+        assert_eq!(credential.digits, DEFAULT_DIGITS);
+        assert_eq!(credential.period, DEFAULT_PERIOD);
+        assert_eq!(credential.counter, 5);
+
+        let credential = parse_otpauth_uri("otpauth://totp/carol?secret=JBSWY3DP").unwrap();
+        assert_eq!(credential.issuer, None);
+        assert_eq!(credential.account, "carol");
+    }
+
+    #[test]
+    fn rejects_invalid_uris() {
+        for uri in [
+            "https://totp/a?secret=JBSWY3DP",
+            "otpauth://motp/a?secret=JBSWY3DP",
+            "otpauth://totp/a",
+            "otpauth://totp/a?secret=JBSWY3DP&period=0",
+            "otpauth://totp/a?secret=JBSWY3DP&algorithm=MD5",
+            "otpauth://totp/a?secret=JBSWY3DP&digits=5",
+            "otpauth://totp/a?secret=JBSWY3DP&digits=9",
+            "otpauth://totp/a?secret=JBSWY3DP&digits=262",
+        ] {
+            assert!(parse_otpauth_uri(uri).is_err(), "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! OpenPGP card (v3.4) instructions, data objects and status words.
+#![allow(unused)]
+
+pub use crate::device::applets::constants::OPENPGP_AID;
+
+pub const INS_VERIFY: u8 = 0x20;
+pub const INS_CHANGE_REFERENCE_DATA: u8 = 0x24;
+pub const INS_GET_DATA: u8 = 0xCA;
+
+// PIN references (P2)
+pub const PW1_SIGN: u8 = 0x81;
+pub const PW1: u8 = 0x82;
+pub const PW3: u8 = 0x83;
+
+// Data objects
+pub const DO_AID: u16 = 0x4F;
+pub const DO_CARDHOLDER_DATA: u16 = 0x65;
+pub const DO_NAME: u16 = 0x5B;
+pub const DO_LANGUAGE: u16 = 0x5F2D;
+pub const DO_URL: u16 = 0x5F50;
+pub const DO_APPLICATION_DATA: u16 = 0x6E;
+pub const DO_DISCRETIONARY: u16 = 0x73;
+pub const DO_SECURITY_SUPPORT: u16 = 0x7A;
+pub const DO_SIGNATURE_COUNTER: u16 = 0x93;
+pub const DO_ALGORITHM_SIG: u16 = 0xC1;
+pub const DO_ALGORITHM_DEC: u16 = 0xC2;
+pub const DO_ALGORITHM_AUT: u16 = 0xC3;
+pub const DO_PW_STATUS: u16 = 0xC4;
+pub const DO_FINGERPRINTS: u16 = 0xC5;
+pub const DO_GENERATION_TIMES: u16 = 0xCD;
+
+pub const FINGERPRINT_SIZE: usize = 20;
+pub const GENERATION_TIME_SIZE: usize = 4;
+
+/// Minimum PIN lengths from the specification
+pub const USER_PIN_MIN_LEN: usize = 6;
+pub const ADMIN_PIN_MIN_LEN: usize = 8;
+
+// Algorithm IDs in the algorithm attributes
+pub const ALGO_RSA: u8 = 0x01;
+pub const ALGO_ECDH: u8 = 0x12;
+pub const ALGO_ECDSA: u8 = 0x13;
+pub const ALGO_EDDSA: u8 = 0x16;
+
+/// Curve OIDs (DER contents) and their names
+pub const CURVES: &[(&[u8], &str)] = &[
+    (
+        &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07],
+        "NIST P-256",
+    ),
+    (&[0x2B, 0x81, 0x04, 0x00, 0x22], "NIST P-384"),
+    (&[0x2B, 0x81, 0x04, 0x00, 0x23], "NIST P-521"),
+    (&[0x2B, 0x81, 0x04, 0x00, 0x0A], "secp256k1"),
+    (
+        &[0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x07],
+        "brainpoolP256r1",
+    ),
+    (
+        &[0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x0B],
+        "brainpoolP384r1",
+    ),
+    (
+        &[0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x0D],
+        "brainpoolP512r1",
+    ),
+    (
+        &[0x2B, 0x06, 0x01, 0x04, 0x01, 0xDA, 0x47, 0x0F, 0x01],
+        "Ed25519",
+    ),
+    (
+        &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01],
+        "Curve25519",
+    ),
+    (&[0x2B, 0x65, 0x70], "Ed25519"),
+    (&[0x2B, 0x65, 0x71], "Ed448"),
+    (&[0x2B, 0x65, 0x6E], "X25519"),
+    (&[0x2B, 0x65, 0x6F], "X448"),
+];
+
+/// SW1 of a failed verification, SW2 low nibble holds the retries left
+pub const SW1_WRONG_PIN: u8 = 0x63;
+pub const SW_SECURITY_STATUS_NOT_SATISFIED: u16 = 0x6982;
+pub const SW_PIN_BLOCKED: u16 = 0x6983;
+pub const SW_WRONG_DATA: u16 = 0x6A80;
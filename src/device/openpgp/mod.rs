@@ -0,0 +1,297 @@
+//! Read-only status and PIN management for the OpenPGP card applet of pico-openpgp and
+//! pico-fido.
+
+pub mod constants;
+
+use crate::device::applets::{AppletCard, constants::SW_SUCCESS, tlv};
+use crate::device::error::PFError;
+use crate::device::openpgp::constants::*;
+use crate::device::rescue::constants::APDU_CLA_ISO;
+use crate::device::types::{
+    OpenPgpCardStatus, OpenPgpKeyInfo, OpenPgpKeySlot, OpenPgpPin, OpenPgpPinStatus,
+};
+
+impl OpenPgpKeySlot {
+    fn algorithm_tag(&self) -> u16 {
+        match self {
+            Self::Signature => DO_ALGORITHM_SIG,
+            Self::Decryption => DO_ALGORITHM_DEC,
+            Self::Authentication => DO_ALGORITHM_AUT,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Self::Signature => 0,
+            Self::Decryption => 1,
+            Self::Authentication => 2,
+        }
+    }
+}
+
+impl OpenPgpPin {
+    fn reference(&self) -> u8 {
+        match self {
+            Self::User => PW1_SIGN,
+            Self::Admin => PW3,
+        }
+    }
+
+    fn min_len(&self) -> usize {
+        match self {
+            Self::User => USER_PIN_MIN_LEN,
+            Self::Admin => ADMIN_PIN_MIN_LEN,
+        }
+    }
+}
+
+fn algorithm_name(attributes: &[u8]) -> String {
+    match attributes {
+        [ALGO_RSA, hi, lo, ..] => format!("RSA {}", u16::from_be_bytes([*hi, *lo])),
+        [id @ (ALGO_ECDH | ALGO_ECDSA | ALGO_EDDSA), oid @ ..] => {
+            // A trailing byte may flag the public key format
+            let curve = CURVES
+                .iter()
+                .find(|(curve, _)| oid.starts_with(curve) && oid.len() <= curve.len() + 1)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| format!("curve {}", hex::encode_upper(oid)));
+            match *id {
+                ALGO_ECDH => format!("ECDH {}", curve),
+                ALGO_ECDSA => format!("ECDSA {}", curve),
+                _ => curve,
+            }
+        }
+        [] => "Unknown".to_string(),
+        [id, ..] => format!("Algorithm {:02X}", id),
+    }
+}
+
+/// Card names are stored as "Surname<<Given<Names".
+fn cardholder_name(raw: &[u8]) -> Option<String> {
+    let raw = String::from_utf8_lossy(raw);
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    let mut parts: Vec<String> = raw.split("<<").map(|p| p.replace('<', " ")).collect();
+    parts.reverse();
+    Some(parts.join(" ").trim().to_string())
+}
+
+fn text(raw: Option<&[u8]>) -> Option<String> {
+    raw.map(|v| String::from_utf8_lossy(v).trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn parse_status(
+    application_data: &[u8],
+    cardholder_data: &[u8],
+    url: Option<&[u8]>,
+    security_support: Option<&[u8]>,
+) -> Result<OpenPgpCardStatus, PFError> {
+    let mut items = tlv::parse_ber(application_data)?;
+    // Most cards wrap the data in its own 6E template
+    if let Some(inner) = tlv::find_ber(&items, DO_APPLICATION_DATA) {
+        items = tlv::parse_ber(inner)?;
+    }
+    let discretionary = tlv::find_ber(&items, DO_DISCRETIONARY)
+        .map(tlv::parse_ber)
+        .transpose()?
+        .unwrap_or_default();
+    // Some cards put the discretionary objects at the top level
+    let find = |tag| tlv::find_ber(&discretionary, tag).or_else(|| tlv::find_ber(&items, tag));
+
+    let aid = tlv::find_ber(&items, DO_AID)
+        .ok_or_else(|| PFError::Device("OpenPGP card did not report its AID".into()))?;
+    let [
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        major,
+        minor,
+        m_hi,
+        m_lo,
+        s0,
+        s1,
+        s2,
+        s3,
+        ..,
+    ] = *aid
+    else {
+        return Err(PFError::Device("Malformed OpenPGP AID".into()));
+    };
+
+    let pw_status = find(DO_PW_STATUS)
+        .filter(|v| v.len() >= 7)
+        .ok_or_else(|| PFError::Device("OpenPGP card did not report its PIN status".into()))?;
+    let pins = OpenPgpPinStatus {
+        user_pin_multiple_signatures: pw_status[0] != 0,
+        user_max_len: pw_status[1],
+        admin_max_len: pw_status[3],
+        user_retries: pw_status[4],
+        reset_code_retries: pw_status[5],
+        admin_retries: pw_status[6],
+    };
+
+    let fingerprints = find(DO_FINGERPRINTS).unwrap_or_default();
+    let times = find(DO_GENERATION_TIMES).unwrap_or_default();
+    let keys = OpenPgpKeySlot::all()
+        .iter()
+        .map(|slot| {
+            let i = slot.index();
+            let fingerprint = fingerprints
+                .get(i * FINGERPRINT_SIZE..(i + 1) * FINGERPRINT_SIZE)
+                .filter(|fp| fp.iter().any(|b| *b != 0))
+                .map(hex::encode_upper);
+            let created = times
+                .get(i * GENERATION_TIME_SIZE..(i + 1) * GENERATION_TIME_SIZE)
+                .and_then(|t| t.try_into().ok())
+                .map(u32::from_be_bytes)
+                .filter(|t| *t != 0);
+            OpenPgpKeyInfo {
+                slot: *slot,
+                algorithm: algorithm_name(find(slot.algorithm_tag()).unwrap_or_default()),
+                fingerprint,
+                created,
+            }
+        })
+        .collect();
+
+    let cardholder = tlv::parse_ber(cardholder_data)?;
+    let cardholder = match tlv::find_ber(&cardholder, DO_CARDHOLDER_DATA) {
+        Some(inner) => tlv::parse_ber(inner)?,
+        None => cardholder,
+    };
+
+    let signature_counter = security_support
+        .map(tlv::parse_ber)
+        .transpose()?
+        .and_then(|items| {
+            let items = match tlv::find_ber(&items, DO_SECURITY_SUPPORT) {
+                Some(inner) => tlv::parse_ber(inner).ok()?,
+                None => items,
+            };
+            tlv::find_ber(&items, DO_SIGNATURE_COUNTER)
+                .map(|c| c.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32))
+        })
+        .unwrap_or_default();
+
+    Ok(OpenPgpCardStatus {
+        aid: hex::encode_upper(aid),
+        version: format!("{}.{}", major, minor),
+        manufacturer: u16::from_be_bytes([m_hi, m_lo]),
+        serial: hex::encode_upper([s0, s1, s2, s3]),
+        keys,
+        pins,
+        signature_counter,
+        cardholder_name: tlv::find_ber(&cardholder, DO_NAME).and_then(cardholder_name),
+        language: text(tlv::find_ber(&cardholder, DO_LANGUAGE)),
+        url: text(url),
+    })
+}
+
+struct OpenPgpSession {
+    card: AppletCard,
+}
+
+impl OpenPgpSession {
+    fn open() -> Result<Self, PFError> {
+        let card = AppletCard::connect()?;
+        card.select(OPENPGP_AID)?;
+        Ok(Self { card })
+    }
+
+    /// Reads a data object. `None` if the card does not have it.
+    fn get_data(&self, tag: u16) -> Result<Option<Vec<u8>>, PFError> {
+        let [p1, p2] = tag.to_be_bytes();
+        let (data, sw) = self
+            .card
+            .transmit(APDU_CLA_ISO, INS_GET_DATA, p1, p2, &[])?;
+        match sw {
+            SW_SUCCESS => Ok(Some(data)),
+            _ if tag == DO_APPLICATION_DATA => Err(PFError::Device(format!(
+                "Reading the OpenPGP application data failed with status {:04X}",
+                sw
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    fn status(&self) -> Result<OpenPgpCardStatus, PFError> {
+        let application_data = self.get_data(DO_APPLICATION_DATA)?.unwrap_or_default();
+        let cardholder_data = self.get_data(DO_CARDHOLDER_DATA)?.unwrap_or_default();
+        let url = self.get_data(DO_URL)?;
+        let security_support = self.get_data(DO_SECURITY_SUPPORT)?;
+        parse_status(
+            &application_data,
+            &cardholder_data,
+            url.as_deref(),
+            security_support.as_deref(),
+        )
+    }
+}
+
+pub fn read_status() -> Result<OpenPgpCardStatus, PFError> {
+    OpenPgpSession::open()?.status()
+}
+
+/// Changes the user or admin PIN with CHANGE REFERENCE DATA, which checks the current PIN.
+pub fn change_pin(pin: OpenPgpPin, current: &str, new: &str) -> Result<(), PFError> {
+    if new.len() < pin.min_len() {
+        return Err(PFError::Io(format!(
+            "The new {} must be at least {} characters",
+            pin.name(),
+            pin.min_len()
+        )));
+    }
+
+    let session = OpenPgpSession::open()?;
+    let pins = session.status()?.pins;
+    let max_len = match pin {
+        OpenPgpPin::User => pins.user_max_len,
+        OpenPgpPin::Admin => pins.admin_max_len,
+    } as usize;
+    if max_len > 0 && new.len() > max_len {
+        return Err(PFError::Io(format!(
+            "The new {} must be at most {} characters",
+            pin.name(),
+            max_len
+        )));
+    }
+
+    let mut data = current.as_bytes().to_vec();
+    data.extend_from_slice(new.as_bytes());
+    let (_, sw) = session.card.transmit(
+        APDU_CLA_ISO,
+        INS_CHANGE_REFERENCE_DATA,
+        0,
+        pin.reference(),
+        &data,
+    )?;
+
+    match sw {
+        SW_SUCCESS => {
+            log::info!("Changed OpenPGP {}", pin.name());
+            Ok(())
+        }
+        sw if (sw >> 8) as u8 == SW1_WRONG_PIN => Err(PFError::Device(format!(
+            "Wrong {}: {} attempts left",
+            pin.name(),
+            sw & 0x0F
+        ))),
+        SW_SECURITY_STATUS_NOT_SATISFIED => Err(PFError::Device(format!("Wrong {}", pin.name()))),
+        SW_PIN_BLOCKED => Err(PFError::Device(format!("The {} is blocked", pin.name()))),
+        SW_WRONG_DATA => Err(PFError::Device(format!(
+            "The card rejected the new {}",
+            pin.name()
+        ))),
+        sw => Err(PFError::Device(format!(
+            "Changing the {} failed with status {:04X}",
+            pin.name(),
+            sw
+        ))),
+    }
+}
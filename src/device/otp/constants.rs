@@ -0,0 +1,91 @@
+//! YubiKey OTP slot commands, configuration layout and flags, as emulated by pico-fido.
+#![allow(unused)]
+
+pub use crate::device::applets::constants::OTP_AID;
+
+/// Instruction that carries a slot command in P1
+pub const INS_CONFIG: u8 = 0x01;
+
+// Slot commands
+pub const CMD_CONFIG_1: u8 = 0x01;
+pub const CMD_CONFIG_2: u8 = 0x03;
+pub const CMD_UPDATE_1: u8 = 0x04;
+pub const CMD_UPDATE_2: u8 = 0x05;
+pub const CMD_SWAP: u8 = 0x06;
+pub const CMD_DEVICE_SERIAL: u8 = 0x10;
+pub const CMD_CHAL_HMAC_1: u8 = 0x30;
+pub const CMD_CHAL_HMAC_2: u8 = 0x38;
+
+// Configuration structure sizes
+pub const FIXED_SIZE: usize = 16;
+pub const UID_SIZE: usize = 6;
+pub const KEY_SIZE: usize = 16;
+pub const ACC_CODE_SIZE: usize = 6;
+/// fixed + uid + key + acc_code + fixed_size + ext/tkt/cfg flags + rfu + crc
+pub const CONFIG_SIZE: usize = 52;
+pub const HMAC_KEY_SIZE: usize = 20;
+/// Longer HMAC-SHA1 keys are hashed first
+pub const SHA1_BLOCK_SIZE: usize = 64;
+pub const HMAC_CHALLENGE_SIZE: usize = 64;
+pub const HMAC_RESPONSE_SIZE: usize = 20;
+/// Scan codes fit in key + uid + fixed
+pub const SCAN_CODES_SIZE: usize = FIXED_SIZE + UID_SIZE + KEY_SIZE;
+
+/// Residual of the CRC over a valid configuration
+pub const CRC_OK_RESIDUAL: u16 = 0xF0B8;
+
+/// Status byte flags (touch level, low byte)
+pub const STATUS_CONFIG1_VALID: u8 = 0x01;
+pub const STATUS_CONFIG2_VALID: u8 = 0x02;
+pub const STATUS_CONFIG1_TOUCH: u8 = 0x04;
+pub const STATUS_CONFIG2_TOUCH: u8 = 0x08;
+
+/// USB HID usage codes get this bit for shifted characters
+pub const SHIFT: u8 = 0x80;
+
+bitflags::bitflags! {
+    /// Ticket flags
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TicketFlags: u8 {
+        const TAB_FIRST = 0x01;
+        const APPEND_TAB1 = 0x02;
+        const APPEND_TAB2 = 0x04;
+        const APPEND_DELAY1 = 0x08;
+        const APPEND_DELAY2 = 0x10;
+        const APPEND_CR = 0x20;
+        const CHAL_RESP = 0x40;
+        const PROTECT_CFG2 = 0x80;
+    }
+}
+
+bitflags::bitflags! {
+    /// Configuration flags; several bits mean different things depending on the slot mode
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ConfigFlags: u8 {
+        const SEND_REF = 0x01;
+        const SHORT_TICKET = 0x02;
+        const HMAC_LT64 = 0x04;
+        const CHAL_BTN_TRIG = 0x08;
+        const STATIC_TICKET = 0x20;
+        const CHAL_HMAC = 0x22;
+        const MAN_UPDATE = 0x80;
+    }
+}
+
+bitflags::bitflags! {
+    /// Extended flags
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ExtendedFlags: u8 {
+        const SERIAL_BTN_VISIBLE = 0x01;
+        const SERIAL_USB_VISIBLE = 0x02;
+        const SERIAL_API_VISIBLE = 0x04;
+        const USE_NUMERIC_KEYPAD = 0x08;
+        const FAST_TRIG = 0x10;
+        const ALLOW_UPDATE = 0x20;
+        const DORMANT = 0x40;
+        const LED_INV = 0x80;
+    }
+}
+
+/// Modhex alphabet used by Yubico OTP public IDs
+pub const MODHEX: &[u8; 16] = b"cbdefghijklnrtuv";
@@ -0,0 +1,497 @@
+//! YubiKey compatible OTP slots of pico-fido, programmed through the OTP applet over PC/SC.
+//!
+//! Each slot holds one 52 byte configuration. Writing sends the configuration followed by the
+//! slot's current access code, and the applet answers with its status bytes. The configuration
+//! layouts follow yubikey-manager so that slots written here behave the same as slots written
+//! by `ykman otp`.
+//!
+//! Only the CCID path is implemented. The HID keyboard (OTP) interface is not used, so the key
+//! has to expose its smart card interface.
+
+pub mod constants;
+
+use crate::device::applets::{AppletCard, constants::SW_SUCCESS};
+use crate::device::error::PFError;
+use crate::device::otp::constants::*;
+use crate::device::rescue::constants::APDU_CLA_ISO;
+use crate::device::types::{OtpSlotStatus, OtpStatus};
+use rand::RngExt;
+use ring::digest;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtpSlot {
+    /// Short press
+    One,
+    /// Long press; the slot KeePassXC and ykchalresp use by default
+    Two,
+}
+
+impl OtpSlot {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::One => "Slot 1 (short press)",
+            Self::Two => "Slot 2 (long press)",
+        }
+    }
+
+    fn config_command(&self) -> u8 {
+        match self {
+            Self::One => CMD_CONFIG_1,
+            Self::Two => CMD_CONFIG_2,
+        }
+    }
+
+    fn hmac_command(&self) -> u8 {
+        match self {
+            Self::One => CMD_CHAL_HMAC_1,
+            Self::Two => CMD_CHAL_HMAC_2,
+        }
+    }
+}
+
+/// What to program into a slot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OtpSlotConfig {
+    YubicoOtp {
+        /// Up to 16 bytes, typed as modhex before every OTP
+        public_id: Vec<u8>,
+        private_id: [u8; UID_SIZE],
+        key: [u8; KEY_SIZE],
+    },
+    StaticPassword {
+        password: String,
+    },
+    ChallengeResponse {
+        key: Vec<u8>,
+        /// The key blinks and waits for a touch before answering
+        require_touch: bool,
+    },
+}
+
+/// The CRC-16 the configuration is protected with (ISO 13239, reflected 0x8408).
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= *byte as u16;
+        for _ in 0..8 {
+            let carry = crc & 1;
+            crc >>= 1;
+            if carry == 1 {
+                crc ^= 0x8408;
+            }
+        }
+    }
+    crc
+}
+
+pub fn modhex_encode(data: &[u8]) -> String {
+    data.iter()
+        .flat_map(|byte| [MODHEX[(byte >> 4) as usize], MODHEX[(byte & 0x0F) as usize]])
+        .map(char::from)
+        .collect()
+}
+
+pub fn modhex_decode(input: &str) -> Result<Vec<u8>, PFError> {
+    let nibbles = input
+        .trim()
+        .to_ascii_lowercase()
+        .bytes()
+        .map(|c| {
+            MODHEX
+                .iter()
+                .position(|m| *m == c)
+                .map(|n| n as u8)
+                .ok_or_else(|| PFError::Io(format!("'{}' is not a modhex character", c as char)))
+        })
+        .collect::<Result<Vec<u8>, PFError>>()?;
+    if nibbles.len() % 2 != 0 {
+        return Err(PFError::Io("Modhex must have an even length".into()));
+    }
+    Ok(nibbles.chunks(2).map(|n| (n[0] << 4) | n[1]).collect())
+}
+
+/// Random public ID, private ID and AES key for a new Yubico OTP credential.
+pub fn generate_yubico_otp() -> (Vec<u8>, [u8; UID_SIZE], [u8; KEY_SIZE]) {
+    let mut rng = rand::rng();
+    let mut public_id = vec![0u8; 6];
+    rng.fill(public_id.as_mut_slice());
+    let mut private_id = [0u8; UID_SIZE];
+    rng.fill(&mut private_id);
+    let mut key = [0u8; KEY_SIZE];
+    rng.fill(&mut key);
+    (public_id, private_id, key)
+}
+
+/// USB HID usage code for a character on a US keyboard layout.
+fn scan_code(c: char) -> Option<u8> {
+    let code = match c {
+        'a'..='z' => 0x04 + (c as u8 - b'a'),
+        'A'..='Z' => (0x04 + (c as u8 - b'A')) | SHIFT,
+        '1'..='9' => 0x1E + (c as u8 - b'1'),
+        '0' => 0x27,
+        '!' => 0x1E | SHIFT,
+        '@' => 0x1F | SHIFT,
+        '#' => 0x20 | SHIFT,
+        '$' => 0x21 | SHIFT,
+        '%' => 0x22 | SHIFT,
+        '^' => 0x23 | SHIFT,
+        '&' => 0x24 | SHIFT,
+        '*' => 0x25 | SHIFT,
+        '(' => 0x26 | SHIFT,
+        ')' => 0x27 | SHIFT,
+        '\t' => 0x2B,
+        ' ' => 0x2C,
+        '-' => 0x2D,
+        '_' => 0x2D | SHIFT,
+        '=' => 0x2E,
+        '+' => 0x2E | SHIFT,
+        '[' => 0x2F,
+        '{' => 0x2F | SHIFT,
+        ']' => 0x30,
+        '}' => 0x30 | SHIFT,
+        '\\' => 0x31,
+        '|' => 0x31 | SHIFT,
+        ';' => 0x33,
+        ':' => 0x33 | SHIFT,
+        '\'' => 0x34,
+        '"' => 0x34 | SHIFT,
+        '`' => 0x35,
+        '~' => 0x35 | SHIFT,
+        ',' => 0x36,
+        '<' => 0x36 | SHIFT,
+        '.' => 0x37,
+        '>' => 0x37 | SHIFT,
+        '/' => 0x38,
+        '?' => 0x38 | SHIFT,
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Lays out the 52 byte slot configuration, CRC included.
+fn build_config(config: &OtpSlotConfig) -> Result<Vec<u8>, PFError> {
+    let mut fixed = Vec::new();
+    let mut uid = [0u8; UID_SIZE];
+    let mut key = [0u8; KEY_SIZE];
+    let mut ticket = TicketFlags::empty();
+    let mut cfg = ConfigFlags::empty();
+    let ext = ExtendedFlags::SERIAL_API_VISIBLE | ExtendedFlags::ALLOW_UPDATE;
+
+    match config {
+        OtpSlotConfig::YubicoOtp {
+            public_id,
+            private_id,
+            key: aes_key,
+        } => {
+            if public_id.len() > FIXED_SIZE {
+                return Err(PFError::Io(format!(
+                    "Public ID must be at most {} bytes",
+                    FIXED_SIZE
+                )));
+            }
+            fixed = public_id.clone();
+            uid = *private_id;
+            key = *aes_key;
+            ticket |= TicketFlags::APPEND_CR;
+        }
+        OtpSlotConfig::StaticPassword { password } => {
+            let codes = password
+                .chars()
+                .map(|c| {
+                    scan_code(c).ok_or_else(|| {
+                        PFError::Io(format!("'{}' cannot be typed on a US keyboard layout", c))
+                    })
+                })
+                .collect::<Result<Vec<u8>, PFError>>()?;
+            if codes.is_empty() || codes.len() > SCAN_CODES_SIZE {
+                return Err(PFError::Io(format!(
+                    "Static passwords must be 1 to {} characters",
+                    SCAN_CODES_SIZE
+                )));
+            }
+            let mut codes = codes;
+            codes.resize(SCAN_CODES_SIZE, 0);
+            key.copy_from_slice(&codes[..KEY_SIZE]);
+            uid.copy_from_slice(&codes[KEY_SIZE..KEY_SIZE + UID_SIZE]);
+            fixed = codes[KEY_SIZE + UID_SIZE..].to_vec();
+            ticket |= TicketFlags::APPEND_CR;
+            cfg |= ConfigFlags::SHORT_TICKET;
+        }
+        OtpSlotConfig::ChallengeResponse {
+            key: hmac_key,
+            require_touch,
+        } => {
+            // Like HMAC itself, keys longer than the SHA-1 block are hashed first.
+            let hmac_key = if hmac_key.len() > SHA1_BLOCK_SIZE {
+                digest::digest(&digest::SHA1_FOR_LEGACY_USE_ONLY, hmac_key)
+                    .as_ref()
+                    .to_vec()
+            } else {
+                hmac_key.clone()
+            };
+            if hmac_key.is_empty() || hmac_key.len() > HMAC_KEY_SIZE {
+                return Err(PFError::Io(format!(
+                    "HMAC-SHA1 keys must be 1 to {} bytes",
+                    HMAC_KEY_SIZE
+                )));
+            }
+            let mut padded = hmac_key;
+            padded.resize(HMAC_KEY_SIZE, 0);
+            key.copy_from_slice(&padded[..KEY_SIZE]);
+            uid[..HMAC_KEY_SIZE - KEY_SIZE].copy_from_slice(&padded[KEY_SIZE..]);
+            ticket |= TicketFlags::CHAL_RESP;
+            cfg |= ConfigFlags::CHAL_HMAC | ConfigFlags::HMAC_LT64;
+            if *require_touch {
+                cfg |= ConfigFlags::CHAL_BTN_TRIG;
+            }
+        }
+    }
+
+    let mut data = Vec::with_capacity(CONFIG_SIZE);
+    let fixed_size = fixed.len() as u8;
+    fixed.resize(FIXED_SIZE, 0);
+    data.extend_from_slice(&fixed);
+    data.extend_from_slice(&uid);
+    data.extend_from_slice(&key);
+    data.extend_from_slice(&[0u8; ACC_CODE_SIZE]); // new access code: none
+    data.push(fixed_size);
+    data.push(ext.bits());
+    data.push(ticket.bits());
+    data.push(cfg.bits());
+    data.extend_from_slice(&[0, 0]); // rfu
+    let crc = !crc16(&data);
+    data.extend_from_slice(&crc.to_le_bytes());
+    debug_assert_eq!(crc16(&data), CRC_OK_RESIDUAL);
+    Ok(data)
+}
+
+fn parse_status(data: &[u8]) -> Result<OtpStatus, PFError> {
+    let [major, minor, build, sequence, touch_level, ..] = *data else {
+        return Err(PFError::Device("Short OTP status".into()));
+    };
+    Ok(OtpStatus {
+        firmware_version: format!("{}.{}.{}", major, minor, build),
+        sequence,
+        slot1: OtpSlotStatus {
+            configured: touch_level & STATUS_CONFIG1_VALID != 0,
+            touch_triggered: touch_level & STATUS_CONFIG1_TOUCH != 0,
+        },
+        slot2: OtpSlotStatus {
+            configured: touch_level & STATUS_CONFIG2_VALID != 0,
+            touch_triggered: touch_level & STATUS_CONFIG2_TOUCH != 0,
+        },
+    })
+}
+
+struct OtpSession {
+    card: AppletCard,
+    status: OtpStatus,
+}
+
+impl OtpSession {
+    fn open() -> Result<Self, PFError> {
+        let card = AppletCard::connect()?;
+        let status = parse_status(&card.select(OTP_AID)?)?;
+        Ok(Self { card, status })
+    }
+
+    fn send(&self, command: u8, data: &[u8]) -> Result<Vec<u8>, PFError> {
+        let (response, sw) = self
+            .card
+            .transmit(APDU_CLA_ISO, INS_CONFIG, command, 0, data)?;
+        if sw != SW_SUCCESS {
+            return Err(PFError::Device(format!(
+                "OTP command {:02X} failed with status {:04X}",
+                command, sw
+            )));
+        }
+        Ok(response)
+    }
+
+    /// Writes a configuration and checks that the programming sequence moved on, which is how
+    /// the applet reports a rejected write (e.g. a wrong access code).
+    fn write(
+        &self,
+        command: u8,
+        config: &[u8],
+        access_code: Option<[u8; ACC_CODE_SIZE]>,
+    ) -> Result<OtpStatus, PFError> {
+        let mut data = config.to_vec();
+        data.extend_from_slice(&access_code.unwrap_or_default());
+        let status = parse_status(&self.send(command, &data)?)?;
+
+        // The sequence drops to 0 once no slot is configured.
+        let has_config = status.slot1.configured || status.slot2.configured;
+        let accepted = status.sequence == self.status.sequence.wrapping_add(1)
+            || (status.sequence == 0 && !has_config);
+        if !accepted {
+            return Err(PFError::Device(
+                "The key did not accept the configuration. Is the slot protected by an access code?"
+                    .into(),
+            ));
+        }
+        Ok(status)
+    }
+}
+
+pub fn read_status() -> Result<OtpStatus, PFError> {
+    Ok(OtpSession::open()?.status)
+}
+
+pub fn program_slot(
+    slot: OtpSlot,
+    config: &OtpSlotConfig,
+    access_code: Option<[u8; ACC_CODE_SIZE]>,
+) -> Result<OtpStatus, PFError> {
+    let data = build_config(config)?;
+    let status = OtpSession::open()?.write(slot.config_command(), &data, access_code)?;
+    log::info!("Programmed OTP {}", slot.label());
+    Ok(status)
+}
+
+pub fn delete_slot(
+    slot: OtpSlot,
+    access_code: Option<[u8; ACC_CODE_SIZE]>,
+) -> Result<OtpStatus, PFError> {
+    let status =
+        OtpSession::open()?.write(slot.config_command(), &[0u8; CONFIG_SIZE], access_code)?;
+    log::info!("Deleted OTP {}", slot.label());
+    Ok(status)
+}
+
+pub fn swap_slots() -> Result<OtpStatus, PFError> {
+    let status = OtpSession::open()?.write(CMD_SWAP, &[], None)?;
+    log::info!("Swapped OTP slots");
+    Ok(status)
+}
+
+/// Pads a challenge to the full 64 bytes. Slots are programmed with HMAC_LT64, so the key strips
+/// every trailing byte equal to the last one; padding with a different byte keeps the challenge
+/// itself intact, as ykman does.
+fn pad_challenge(challenge: &[u8]) -> Vec<u8> {
+    let pad = if challenge.last() == Some(&0) { 1 } else { 0 };
+    let mut padded = challenge.to_vec();
+    padded.resize(HMAC_CHALLENGE_SIZE, pad);
+    padded
+}
+
+/// Sends an HMAC-SHA1 challenge (up to 64 bytes) to a challenge-response slot.
+pub fn challenge_response(slot: OtpSlot, challenge: &[u8]) -> Result<Vec<u8>, PFError> {
+    if challenge.len() > HMAC_CHALLENGE_SIZE {
+        return Err(PFError::Io(format!(
+            "Challenges must be at most {} bytes",
+            HMAC_CHALLENGE_SIZE
+        )));
+    }
+    let response = OtpSession::open()?.send(slot.hmac_command(), &pad_challenge(challenge))?;
+    if response.len() < HMAC_RESPONSE_SIZE {
+        return Err(PFError::Device(
+            "The slot returned no response. Is it configured for challenge-response?".into(),
+        ));
+    }
+    Ok(response[..HMAC_RESPONSE_SIZE].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UID_OFFSET: usize = FIXED_SIZE;
+    const KEY_OFFSET: usize = UID_OFFSET + UID_SIZE;
+    const FLAGS_OFFSET: usize = KEY_OFFSET + KEY_SIZE + ACC_CODE_SIZE;
+
+    #[test]
+    fn crc16_matches_reference() {
+        // CRC-16/MCRF4XX check value
+        assert_eq!(crc16(b"123456789"), 0x6F91);
+    }
+
+    #[test]
+    fn modhex_round_trips() {
+        assert_eq!(modhex_encode(&[0x00, 0x12, 0xFF]), "ccbdvv");
+        assert_eq!(modhex_decode(" CCBDvv ").unwrap(), [0x00, 0x12, 0xFF]);
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(modhex_decode(&modhex_encode(&data)).unwrap(), data);
+        assert!(modhex_decode("cba").is_err());
+        assert!(modhex_decode("ccbz").is_err());
+    }
+
+    #[test]
+    fn challenges_are_padded_with_a_different_byte() {
+        let padded = pad_challenge(b"abc");
+        assert_eq!(padded.len(), HMAC_CHALLENGE_SIZE);
+        assert_eq!(padded[..3], *b"abc");
+        assert!(padded[3..].iter().all(|b| *b == 0));
+
+        let padded = pad_challenge(&[0x12, 0x00]);
+        assert_eq!(padded[..2], [0x12, 0x00]);
+        assert!(padded[2..].iter().all(|b| *b == 1));
+
+        assert_eq!(
+            pad_challenge(&[7; HMAC_CHALLENGE_SIZE]),
+            [7; HMAC_CHALLENGE_SIZE]
+        );
+    }
+
+    #[test]
+    fn yubico_otp_config_layout() {
+        let (public_id, private_id, key) = generate_yubico_otp();
+        let data = build_config(&OtpSlotConfig::YubicoOtp {
+            public_id: public_id.clone(),
+            private_id,
+            key,
+        })
+        .unwrap();
+        assert_eq!(data.len(), CONFIG_SIZE);
+        assert_eq!(crc16(&data), CRC_OK_RESIDUAL);
+        assert_eq!(data[..public_id.len()], public_id[..]);
+        assert_eq!(data[UID_OFFSET..KEY_OFFSET], private_id);
+        assert_eq!(data[KEY_OFFSET..KEY_OFFSET + KEY_SIZE], key);
+        assert_eq!(data[FLAGS_OFFSET] as usize, public_id.len());
+        assert_eq!(data[FLAGS_OFFSET + 2], TicketFlags::APPEND_CR.bits());
+    }
+
+    #[test]
+    fn challenge_response_config_splits_key() {
+        let hmac_key: Vec<u8> = (1..=HMAC_KEY_SIZE as u8).collect();
+        let data = build_config(&OtpSlotConfig::ChallengeResponse {
+            key: hmac_key.clone(),
+            require_touch: true,
+        })
+        .unwrap();
+        assert_eq!(crc16(&data), CRC_OK_RESIDUAL);
+        assert_eq!(
+            data[KEY_OFFSET..KEY_OFFSET + KEY_SIZE],
+            hmac_key[..KEY_SIZE]
+        );
+        assert_eq!(
+            data[UID_OFFSET..UID_OFFSET + HMAC_KEY_SIZE - KEY_SIZE],
+            hmac_key[KEY_SIZE..]
+        );
+        let cfg = ConfigFlags::from_bits_retain(data[FLAGS_OFFSET + 3]);
+        assert!(cfg.contains(ConfigFlags::CHAL_HMAC | ConfigFlags::HMAC_LT64));
+        assert!(cfg.contains(ConfigFlags::CHAL_BTN_TRIG));
+    }
+
+    #[test]
+    fn static_password_config() {
+        let data = build_config(&OtpSlotConfig::StaticPassword {
+            password: "aB1".into(),
+        })
+        .unwrap();
+        assert_eq!(crc16(&data), CRC_OK_RESIDUAL);
+        assert_eq!(data[KEY_OFFSET..KEY_OFFSET + 3], [0x04, 0x05 | SHIFT, 0x1E]);
+        assert!(
+            build_config(&OtpSlotConfig::StaticPassword {
+                password: "caf\u{e9}".into()
+            })
+            .is_err()
+        );
+        assert!(
+            build_config(&OtpSlotConfig::StaticPassword {
+                password: String::new()
+            })
+            .is_err()
+        );
+    }
+}
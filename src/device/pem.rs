@@ -0,0 +1,37 @@
+//! Minimal PEM encoding and decoding for certificates, CSRs and public keys.
+
+use crate::device::error::PFError;
+use base64::Engine;
+
+/// Encodes DER data as a PEM block with the given label.
+pub fn encode(label: &str, der: &[u8]) -> String {
+    let b64 = base64::engine::general_purpose::STANDARD.encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in b64.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap_or_default());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+/// Returns the DER contents of the first PEM block in `data`, or `data` itself if it is not PEM.
+pub fn decode_or_der(data: &[u8]) -> Result<Vec<u8>, PFError> {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return Ok(data.to_vec());
+    };
+    let Some(start) = text.find("-----BEGIN ") else {
+        return Ok(data.to_vec());
+    };
+
+    let body: String = text[start..]
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.starts_with("-----END "))
+        .map(str::trim)
+        .collect();
+
+    base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .map_err(|e| PFError::Io(format!("Invalid PEM data: {}", e)))
+}
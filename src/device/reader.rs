@@ -0,0 +1,59 @@
+//! Picks the PC/SC reader of the key PicoForge manages.
+//!
+//! FIDO HID sessions are bound to the key chosen by touch (see `fido::selection`). The CCID side
+//! follows that choice by matching the USB serial number of the HID device against the reader
+//! names, which pcsc-lite builds as `<product> (<serial>) 00 00`. With a single key connected any
+//! reader is assumed to be its own, as before. With several keys and no matching reader the rescue
+//! and applet calls fail instead of talking to the wrong key.
+
+use crate::device::error::PFError;
+use crate::device::fido::hid;
+use pcsc::{Card, Context, Protocols, Scope, ShareMode};
+use std::ffi::CString;
+
+/// Returns the reader that belongs to the current FIDO device.
+fn find_reader(ctx: &Context) -> Result<CString, PFError> {
+    let mut readers_buf = [0; 2048];
+    let mut readers: Vec<CString> = ctx
+        .list_readers(&mut readers_buf)?
+        .map(|r| r.to_owned())
+        .collect();
+    if readers.is_empty() {
+        log::info!("No Smart Card Reader found");
+        return Err(PFError::NoDevice);
+    }
+
+    let devices = hid::list_devices().unwrap_or_default();
+    let current = hid::selected_device()
+        .and_then(|path| devices.iter().find(|d| d.path == path))
+        .or(devices.first());
+
+    let serial = current.and_then(|d| d.serial_number.as_deref());
+    if let Some(serial) = serial.filter(|s| !s.is_empty()) {
+        let serial = serial.to_uppercase();
+        if let Some(reader) = readers
+            .iter()
+            .find(|r| r.to_string_lossy().to_uppercase().contains(&serial))
+        {
+            log::debug!("Using reader {:?} for serial {}", reader, serial);
+            return Ok(reader.clone());
+        }
+    }
+
+    if devices.len() > 1 {
+        return Err(PFError::Device(
+            "Several keys are connected and the smart card reader of the chosen key cannot be identified. Unplug the other keys to use this page.".into(),
+        ));
+    }
+    Ok(readers.swap_remove(0))
+}
+
+/// Connects to the reader of the current key.
+pub fn connect() -> Result<Card, PFError> {
+    let ctx = Context::establish(Scope::User).map_err(|e| {
+        log::error!("Failed to establish PCSC context: {}", e);
+        PFError::Pcsc(e)
+    })?;
+    let reader = find_reader(&ctx)?;
+    Ok(ctx.connect(&reader, ShareMode::Shared, Protocols::ANY)?)
+}
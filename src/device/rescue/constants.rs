@@ -61,6 +61,15 @@ pub enum SignParam {
     UploadCert = 0x03,
 }
 
+/// Length of the uncompressed P-256 device key returned by SignParam::GetPublicKey
+pub const DEVICE_KEY_PUBLIC_LEN: usize = 65;
+
+/// DER SubjectPublicKeyInfo prefix for an uncompressed P-256 (prime256v1) public key
+pub const P256_SPKI_PREFIX: &[u8] = &[
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01, 0x06, 0x08, 0x2A,
+    0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
 /// P1 Parameters for RescueInstruction::Reboot (0x1F)
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -4,28 +4,13 @@
 
 pub mod constants;
 
-use crate::device::{error::PFError, rescue::constants::*, types::*};
+use crate::device::{error::PFError, reader, rescue::constants::*, types::*};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use pcsc::{Context, Protocols, Scope, ShareMode};
 use std::io::Cursor;
 
-/// Connects to the first available reader and selects the Rescue Applet
+/// Connects to the reader of the current key and selects the Rescue Applet
 fn connect_and_select() -> Result<(pcsc::Card, Vec<u8>), PFError> {
-    let ctx = Context::establish(Scope::User).map_err(|e| {
-        log::error!("Failed to establish PCSC context: {}", e);
-        PFError::Pcsc(e)
-    })?;
-
-    let mut readers_buf = [0; 2048];
-    let mut readers = ctx.list_readers(&mut readers_buf)?;
-
-    // Use the first reader found
-    let reader = readers.next().ok_or_else(|| {
-        log::info!("No Smart Card Reader found");
-        PFError::NoDevice
-    })?;
-
-    let card = ctx.connect(reader, ShareMode::Shared, Protocols::ANY)?;
+    let card = reader::connect()?;
 
     // Select Applet APDU: 00 A4 04 04 [Len] [AID]
     let mut apdu = vec![
@@ -54,6 +39,26 @@ fn connect_and_select() -> Result<(pcsc::Card, Vec<u8>), PFError> {
     Ok((card, rx.to_vec()))
 }
 
+/// Extracts the serial number from the Rescue Applet select response.
+fn parse_serial(select_resp: &[u8]) -> Option<String> {
+    // FIX: Handle missing Serial Number safely
+    // If the firmware sends 14 bytes, we have a serial. If it sends 6, we don't.
+    serial_from_select_data(&select_resp[..select_resp.len().saturating_sub(2)])
+}
+
+/// Extracts the serial number from the select response data, without the status word. It is
+/// also the key's USB serial number.
+pub(crate) fn serial_from_select_data(data: &[u8]) -> Option<String> {
+    (data.len() >= 12).then(|| hex::encode_upper(&data[4..12]))
+}
+
+/// Reads the device serial number without touching the rest of the device state.
+pub fn read_serial() -> Result<String, PFError> {
+    let (_, select_resp) = connect_and_select()?;
+    parse_serial(&select_resp)
+        .ok_or_else(|| PFError::Device("Device did not report a serial number".into()))
+}
+
 pub fn read_device_details() -> Result<FullDeviceStatus, PFError> {
     log::info!("Reading full device details");
     let (card, select_resp) = connect_and_select()?;
@@ -70,16 +75,12 @@ pub fn read_device_details() -> Result<FullDeviceStatus, PFError> {
     let version_major = select_resp[2];
     let version_minor = select_resp[3];
 
-    // FIX: Handle missing Serial Number safely
-    // If the firmware sends 14 bytes, we have a serial. If it sends 6, we don't.
-    let serial_str = if select_resp.len() >= 14 {
-        hex::encode_upper(&select_resp[4..12])
-    } else {
+    let serial_str = parse_serial(&select_resp).unwrap_or_else(|| {
         log::warn!(
             "Device did not return a Serial Number (Firmware mismatch?). Using placeholder."
         );
         "00000000".to_string()
-    };
+    });
 
     log::info!("Device Version: {}.{}", version_major, version_minor);
     log::info!("Device Serial: {}", serial_str);
@@ -418,3 +419,103 @@ pub fn enable_secure_boot(lock: bool) -> Result<String, PFError> {
         Err(PFError::Device(format!("Secure Boot failed: {:02X?}", rx)))
     }
 }
+
+/// Reads the uncompressed SEC1 public key (65 bytes, P-256) of the device key.
+pub fn get_device_public_key() -> Result<Vec<u8>, PFError> {
+    log::info!("Reading device key public key");
+    let (card, _) = connect_and_select()?;
+
+    let apdu = [
+        APDU_CLA_PROPRIETARY,
+        RescueInstruction::KeyDevSign as u8,
+        SignParam::GetPublicKey as u8,
+        P2_UNUSED,
+        0x00,
+    ];
+
+    let mut rx_buf = [0; 258];
+    let rx = card.transmit(&apdu, &mut rx_buf)?;
+
+    if !rx.ends_with(&SW_SUCCESS) {
+        log::error!("Reading device public key failed: {:02X?}", rx);
+        return Err(PFError::Device(format!(
+            "Reading device public key failed: {:02X?}",
+            rx
+        )));
+    }
+
+    let key = &rx[..rx.len() - 2];
+    if key.len() != DEVICE_KEY_PUBLIC_LEN || key[0] != 0x04 {
+        return Err(PFError::Device(format!(
+            "Unexpected device public key ({} bytes)",
+            key.len()
+        )));
+    }
+
+    Ok(key.to_vec())
+}
+
+/// Signs a 32 byte SHA-256 digest with the device key. Returns a DER encoded ECDSA signature.
+pub fn sign_with_device_key(digest: &[u8; 32]) -> Result<Vec<u8>, PFError> {
+    log::info!("Signing challenge with device key");
+    let (card, _) = connect_and_select()?;
+
+    // APDU: 80 10 01 00 20 [Digest] 00
+    let mut apdu = vec![
+        APDU_CLA_PROPRIETARY,
+        RescueInstruction::KeyDevSign as u8,
+        SignParam::SignData as u8,
+        P2_UNUSED,
+        digest.len() as u8,
+    ];
+    apdu.extend_from_slice(digest);
+    apdu.push(0x00); // Le
+
+    let mut rx_buf = [0; 258];
+    let rx = card.transmit(&apdu, &mut rx_buf)?;
+
+    if rx.ends_with(&SW_SUCCESS) && rx.len() > 2 {
+        Ok(rx[..rx.len() - 2].to_vec())
+    } else {
+        log::error!("Device key signature failed: {:02X?}", rx);
+        Err(PFError::Device(format!("Signing failed: {:02X?}", rx)))
+    }
+}
+
+/// Stores a DER encoded certificate for the device key on the device.
+pub fn upload_device_certificate(cert_der: &[u8]) -> Result<String, PFError> {
+    log::info!("Uploading device certificate ({} bytes)", cert_der.len());
+
+    if cert_der.is_empty() || cert_der.len() > u16::MAX as usize {
+        return Err(PFError::Io("Invalid certificate size".into()));
+    }
+
+    let (card, _) = connect_and_select()?;
+
+    // Certificates rarely fit a short APDU, so use extended length when needed:
+    // 80 10 03 00 00 [Lc hi] [Lc lo] [Data]
+    let mut apdu = vec![
+        APDU_CLA_PROPRIETARY,
+        RescueInstruction::KeyDevSign as u8,
+        SignParam::UploadCert as u8,
+        P2_UNUSED,
+    ];
+    if cert_der.len() <= 255 {
+        apdu.push(cert_der.len() as u8);
+    } else {
+        apdu.push(0x00);
+        apdu.write_u16::<BigEndian>(cert_der.len() as u16).unwrap();
+    }
+    apdu.extend_from_slice(cert_der);
+
+    let mut rx_buf = [0; 256];
+    let rx = card.transmit(&apdu, &mut rx_buf)?;
+
+    if rx.ends_with(&SW_SUCCESS) {
+        log::info!("Device certificate uploaded successfully");
+        Ok("Device certificate uploaded".into())
+    } else {
+        log::error!("Device certificate upload failed: {:02X?}", rx);
+        Err(PFError::Device(format!("Upload failed: {:02X?}", rx)))
+    }
+}
@@ -0,0 +1,116 @@
+//! Host-side secrets for pico-fido encryption at rest.
+//!
+//! When encryption at rest is enabled the device key is wrapped with a 32 byte secret that only
+//! the host knows, and the key stays locked after every power-up until the host sends it back.
+//! One secret is kept per device serial in the application data directory. The file is created
+//! readable by the current user only.
+
+use crate::device::error::PFError;
+use directories::ProjectDirs;
+use rand::RngExt;
+use std::path::PathBuf;
+
+pub const HOST_SECRET_LEN: usize = 32;
+
+fn secrets_dir() -> PathBuf {
+    match ProjectDirs::from("in", "suyogtandel", "picoforge") {
+        Some(dirs) => dirs.data_local_dir().join("secrets"),
+        None => PathBuf::from("secrets"),
+    }
+}
+
+/// Location of the secret for the device with the given serial number. `None` when the serial
+/// is not known, e.g. the `?` shown when it could not be read, since such a file would be shared
+/// by every key.
+pub fn secret_path(serial: &str) -> Option<PathBuf> {
+    let name: String = serial
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    (!name.is_empty()).then(|| secrets_dir().join(format!("{}.key", name)))
+}
+
+fn unknown_serial() -> PFError {
+    PFError::Device("The serial number of this key is unknown".into())
+}
+
+pub fn has_host_secret(serial: &str) -> bool {
+    secret_path(serial).is_some_and(|path| path.is_file())
+}
+
+/// Loads the secret for `serial`, or `None` if none has been created yet.
+pub fn load_host_secret(serial: &str) -> Result<Option<[u8; HOST_SECRET_LEN]>, PFError> {
+    let path = secret_path(serial).ok_or_else(unknown_serial)?;
+    let data = match std::fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(PFError::Io(format!(
+                "Could not read {}: {}",
+                path.display(),
+                e
+            )));
+        }
+    };
+
+    let bytes = hex::decode(data.trim())
+        .map_err(|e| PFError::Io(format!("Invalid secret file {}: {}", path.display(), e)))?;
+    let secret: [u8; HOST_SECRET_LEN] = bytes.try_into().map_err(|_| {
+        PFError::Io(format!(
+            "Secret file {} does not hold a {} byte secret",
+            path.display(),
+            HOST_SECRET_LEN
+        ))
+    })?;
+    Ok(Some(secret))
+}
+
+/// Returns the existing secret for `serial` or creates and stores a new random one.
+pub fn load_or_create_host_secret(serial: &str) -> Result<[u8; HOST_SECRET_LEN], PFError> {
+    if let Some(secret) = load_host_secret(serial)? {
+        return Ok(secret);
+    }
+
+    let mut secret = [0u8; HOST_SECRET_LEN];
+    rand::rng().fill(&mut secret);
+
+    let path = secret_path(serial).ok_or_else(unknown_serial)?;
+    write_private(&path, hex::encode(secret).as_bytes())?;
+    log::info!("Stored new host secret at {}", path.display());
+    Ok(secret)
+}
+
+#[cfg(unix)]
+pub(crate) fn write_private(path: &std::path::Path, data: &[u8]) -> Result<(), PFError> {
+    use std::io::Write;
+    use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+
+    if let Some(dir) = path.parent() {
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|e| PFError::Io(format!("Could not create {}: {}", dir.display(), e)))?;
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| PFError::Io(format!("Could not create {}: {}", path.display(), e)))?;
+    file.write_all(data)
+        .map_err(|e| PFError::Io(format!("Could not write {}: {}", path.display(), e)))
+}
+
+/// On Windows the per-user local app data directory is already limited to the user.
+#[cfg(not(unix))]
+pub(crate) fn write_private(path: &std::path::Path, data: &[u8]) -> Result<(), PFError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| PFError::Io(format!("Could not create {}: {}", dir.display(), e)))?;
+    }
+    std::fs::write(path, data)
+        .map_err(|e| PFError::Io(format!("Could not write {}: {}", path.display(), e)))
+}
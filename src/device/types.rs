@@ -65,8 +65,86 @@ pub enum DeviceMethod {
     Rescue,
 }
 
+/// Applets that pico-keys firmwares expose over PC/SC.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PicoApplet {
+    Fido,
+    OpenPgp,
+    SmartCardHsm,
+    Oath,
+    Otp,
+    Management,
+    Rescue,
+}
+
+impl PicoApplet {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Fido => "FIDO",
+            Self::OpenPgp => "OpenPGP",
+            Self::SmartCardHsm => "SmartCard-HSM",
+            Self::Oath => "OATH",
+            Self::Otp => "OTP",
+            Self::Management => "Management",
+            Self::Rescue => "Rescue",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PicoFirmware {
+    Fido,
+    Hsm,
+    OpenPgp,
+    Unknown,
+}
+
+impl PicoFirmware {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Fido => "Pico FIDO",
+            Self::Hsm => "Pico HSM",
+            Self::OpenPgp => "Pico OpenPGP",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
+/// What the connected key runs, from probing the applet AIDs and CTAPHID.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceCapabilities {
+    pub firmware: PicoFirmware,
+    pub applets: Vec<PicoApplet>,
+    /// A FIDO HID interface is enumerated
+    pub ctaphid: bool,
+}
+
+impl DeviceCapabilities {
+    pub fn has(&self, applet: PicoApplet) -> bool {
+        self.applets.contains(&applet)
+    }
+
+    /// Whether the CTAP features (passkeys, PIN, tools) can be used.
+    pub fn fido_available(&self) -> bool {
+        self.ctaphid || self.has(PicoApplet::Fido)
+    }
+}
+
 // Fido stuff:
 
+/// A connected FIDO HID device, identified by its OS path.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FidoHidDevice {
+    pub path: String,
+    pub product_name: String,
+    /// USB serial number, used to find the key's PC/SC reader
+    pub serial_number: Option<String>,
+    pub vid: u16,
+    pub pid: u16,
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct FidoDeviceInfo {
@@ -76,8 +154,18 @@ pub struct FidoDeviceInfo {
     pub options: std::collections::HashMap<String, bool>,
     pub max_msg_size: i32,
     pub pin_protocols: Vec<u32>,
-    // pub remaining_disc_creds: u32,
+    pub algorithms: Vec<i32>,
+    /// remainingDiscoverableCredentials from getInfo (0x14), `None` if the device does not report it
+    pub remaining_disc_creds: Option<u32>,
     pub min_pin_length: u32,
+    /// forcePINChange from getInfo (0x0C): PIN tokens are refused until the PIN is changed
+    pub force_pin_change: bool,
+    /// maxRPIDsForSetMinPINLength from getInfo (0x10)
+    pub max_rpids_for_min_pin_length: u32,
+    /// pinComplexityPolicy from getInfo (0x1B), `None` if the device does not report it
+    pub pin_complexity_policy: Option<bool>,
+    /// pinComplexityPolicyURL from getInfo (0x1C)
+    pub pin_complexity_policy_url: Option<String>,
     pub firmware_version: String,
 }
 
@@ -90,4 +178,355 @@ pub struct StoredCredential {
     pub user_display_name: String,
     pub user_id: String,
     pub credential_id: String,
+    pub public_key: Option<CredentialPublicKey>,
+    /// credProtect policy (1 = UV optional, 2 = UV optional with credential ID list, 3 = UV required)
+    pub cred_protect: Option<u8>,
+    /// largeBlobKey, hex encoded
+    pub large_blob_key: Option<String>,
+}
+
+/// Discoverable credential slots, from credentialManagement getCredsMetadata.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialCapacity {
+    /// `None` when only getInfo's remaining count is known (no PIN for getCredsMetadata)
+    pub existing: Option<u32>,
+    pub remaining: u32,
+}
+
+/// Remaining PIN / built-in UV attempts (clientPIN getPINRetries and getUVRetries).
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PinRetries {
+    pub pin_retries: u32,
+    /// powerCycleState: the key must be replugged before the PIN can be tried again
+    pub power_cycle_required: bool,
+    /// `None` if the device has no built-in user verification
+    pub uv_retries: Option<u32>,
+}
+
+/// One entry of the authenticator's large-blob array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LargeBlobEntry {
+    /// Position in the serialized array
+    pub index: usize,
+    /// Size of the encrypted entry as stored
+    pub stored_size: usize,
+    /// Credential whose largeBlobKey decrypts the entry, if it is one of the listed credentials
+    pub credential_id: Option<String>,
+    /// Decrypted and decompressed contents
+    pub data: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LargeBlobArray {
+    pub entries: Vec<LargeBlobEntry>,
+    /// Size of the serialized array including the hash trailer
+    pub serialized_size: usize,
+    /// maxSerializedLargeBlobArray from getInfo (0x0B)
+    pub max_size: usize,
+    /// The stored array failed its integrity check and was read as the initial empty array
+    pub integrity_failed: bool,
+}
+
+/// Outcome of deleting one credential as part of a batch.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialDeleteResult {
+    pub credential_id: String,
+    pub error: Option<String>,
+}
+
+/// Credential public key decoded from its COSE_Key encoding.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialPublicKey {
+    /// COSE key type (1 = OKP, 2 = EC2)
+    pub key_type: i32,
+    pub algorithm: Option<i32>,
+    pub curve: Option<u8>,
+    /// Hex encoded coordinates
+    pub x: String,
+    pub y: Option<String>,
+}
+
+// Genuine device verification:
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum GenuineStatus {
+    /// The device signed the challenge with the key enrolled for its serial.
+    Genuine,
+    /// No inventory entry exists for this serial.
+    NotEnrolled,
+    /// The signature does not verify against the enrolled key: swapped or counterfeit device.
+    PossibleClone,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GenuineCheckResult {
+    pub serial: String,
+    pub status: GenuineStatus,
+    pub label: Option<String>,
+    pub detail: String,
+}
+
+// Health check:
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheckStep {
+    pub name: String,
+    pub duration_ms: u128,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheckReport {
+    pub algorithm: String,
+    pub steps: Vec<HealthCheckStep>,
+    pub user_present: bool,
+    pub user_verified: bool,
+    pub passed: bool,
+}
+
+// hmac-secret:
+
+/// A credential created with the hmac-secret extension.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HmacSecretCredential {
+    pub rp_id: String,
+    /// Hex encoded
+    pub credential_id: String,
+    pub resident: bool,
+}
+
+/// Decrypted hmac-secret outputs, hex encoded.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HmacSecretOutput {
+    pub credential_id: String,
+    /// The key derives separate outputs with and without user verification
+    pub user_verified: bool,
+    pub output1: String,
+    pub output2: Option<String>,
+    /// Resident credentials the key found for the RP; the first one was used
+    pub matching_credentials: usize,
+}
+
+// OATH:
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OathType {
+    Hotp,
+    Totp,
+}
+
+impl OathType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hotp => "HOTP",
+            Self::Totp => "TOTP",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OathAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl OathAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA1",
+            Self::Sha256 => "SHA256",
+            Self::Sha512 => "SHA512",
+        }
+    }
+}
+
+/// A code calculated by the OATH applet.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OathCode {
+    pub code: String,
+    /// Unix time the TOTP period started at
+    pub valid_from: u64,
+    /// Unix time the TOTP code expires at; `None` for HOTP
+    pub valid_to: Option<u64>,
+}
+
+/// An account stored in the OATH applet.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OathAccount {
+    /// Name on the key: `[period/][issuer:]account`
+    pub name: String,
+    pub issuer: Option<String>,
+    pub account: String,
+    pub oath_type: OathType,
+    pub algorithm: OathAlgorithm,
+    pub period: u32,
+    pub touch_required: bool,
+    /// Filled in for TOTP accounts that calculate without a touch
+    pub code: Option<OathCode>,
+}
+
+// OTP slots:
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtpSlotStatus {
+    pub configured: bool,
+    /// Keyboard slots (Yubico OTP, static password) fire on touch; challenge-response slots don't
+    pub touch_triggered: bool,
+}
+
+/// Status bytes the OTP applet returns after select and every write.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtpStatus {
+    pub firmware_version: String,
+    /// Programming sequence, increased on every accepted write
+    pub sequence: u8,
+    pub slot1: OtpSlotStatus,
+    pub slot2: OtpSlotStatus,
+}
+
+// Management applet:
+
+/// Applications the management applet can switch on and off.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManagementApp {
+    Otp,
+    U2f,
+    Fido2,
+    Oath,
+    OpenPgp,
+    Piv,
+    HsmAuth,
+}
+
+impl ManagementApp {
+    pub fn all() -> &'static [ManagementApp] {
+        &[
+            Self::Fido2,
+            Self::U2f,
+            Self::Otp,
+            Self::Oath,
+            Self::OpenPgp,
+            Self::Piv,
+            Self::HsmAuth,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Otp => "OTP",
+            Self::U2f => "FIDO U2F",
+            Self::Fido2 => "FIDO2",
+            Self::Oath => "OATH",
+            Self::OpenPgp => "OpenPGP",
+            Self::Piv => "PIV",
+            Self::HsmAuth => "YubiHSM Auth",
+        }
+    }
+}
+
+/// Device info read from the management applet. Masks hold the `CAP_*` bits.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagementInfo {
+    pub version: Option<String>,
+    pub serial: Option<u32>,
+    pub usb_supported: u16,
+    pub usb_enabled: u16,
+    /// Changes need the lock code, which PicoForge does not handle
+    pub config_locked: bool,
+}
+
+// OpenPGP card:
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenPgpKeySlot {
+    Signature,
+    Decryption,
+    Authentication,
+}
+
+impl OpenPgpKeySlot {
+    pub fn all() -> &'static [OpenPgpKeySlot] {
+        &[Self::Signature, Self::Decryption, Self::Authentication]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Signature => "Signature",
+            Self::Decryption => "Decryption",
+            Self::Authentication => "Authentication",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenPgpKeyInfo {
+    pub slot: OpenPgpKeySlot,
+    /// e.g. "RSA 2048" or "Ed25519"
+    pub algorithm: String,
+    /// Hex, `None` when the slot holds no key
+    pub fingerprint: Option<String>,
+    /// Unix time the key was generated or imported
+    pub created: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenPgpPinStatus {
+    pub user_retries: u8,
+    pub reset_code_retries: u8,
+    pub admin_retries: u8,
+    pub user_max_len: u8,
+    pub admin_max_len: u8,
+    /// The user PIN stays verified for several signatures
+    pub user_pin_multiple_signatures: bool,
+}
+
+/// Decoded Application Related Data and cardholder objects of an OpenPGP card.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenPgpCardStatus {
+    /// Full application identifier, hex
+    pub aid: String,
+    /// OpenPGP card specification version, e.g. "3.4"
+    pub version: String,
+    pub manufacturer: u16,
+    pub serial: String,
+    pub keys: Vec<OpenPgpKeyInfo>,
+    pub pins: OpenPgpPinStatus,
+    pub signature_counter: u32,
+    pub cardholder_name: Option<String>,
+    pub language: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenPgpPin {
+    User,
+    Admin,
+}
+
+impl OpenPgpPin {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::User => "User PIN",
+            Self::Admin => "Admin PIN",
+        }
+    }
 }
@@ -65,8 +65,11 @@ impl Append for BufferAppender {
     fn flush(&self) {}
 }
 
-/// Initializes log4rs with custom configuration for stdout and file logging.
-pub fn logger_init() {
+/// Initializes log4rs with custom configuration for console and file logging.
+///
+/// With `console_to_stderr` the console output goes to stderr, so the CLI can keep stdout for
+/// command output.
+pub fn logger_init(console_to_stderr: bool) {
     let qual = "in";
     let org = "suyogtandel";
     let app = "picoforge";
@@ -103,7 +106,11 @@ pub fn logger_init() {
 
     // Console Appender
     let stdout = ConsoleAppender::builder()
-        .target(Target::Stdout)
+        .target(if console_to_stderr {
+            Target::Stderr
+        } else {
+            Target::Stdout
+        })
         .encoder(Box::new(PatternEncoder::new(
             "[{d(%Y-%m-%d %H:%M:%S %Z)} {h({l})} {t}] {m}{n}",
         )))
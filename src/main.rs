@@ -6,6 +6,7 @@ use gpui_component::Root;
 use gpui_component::{Theme, ThemeMode};
 use ui::rootview::ApplicationRoot;
 
+mod cli;
 mod device;
 pub mod logging;
 mod ui;
@@ -15,8 +16,20 @@ mod ui;
 // due to lack in complete context of code and the way it is structured, it has done a shit job. I did make a lot of changes to
 // the code by myself, so it is not complete AI slop. Right now I am trading development time with code quality, I will later
 // improve the code quality and correctly categorize the code into individial components.
-fn main() {
-    logging::logger_init();
+fn main() -> std::process::ExitCode {
+    // Release builds have no console of their own, so the CLI (including clap's help and errors)
+    // writes to the one it was started from.
+    #[cfg(all(windows, not(debug_assertions)))]
+    if std::env::args_os().len() > 1 {
+        attach_parent_console();
+    }
+
+    if let Some(command) = cli::parse() {
+        logging::logger_init(true);
+        return cli::run(command);
+    }
+
+    logging::logger_init(false);
     let app = Application::new().with_assets(ui::assets::Assets);
 
     app.run(move |cx| {
@@ -65,7 +78,7 @@ fn main() {
             };
 
             cx.open_window(window_options, |window, cx| {
-                let view = cx.new(|cx| ApplicationRoot::new(cx));
+                let view = cx.new(ApplicationRoot::new);
                 cx.new(|cx| Root::new(view, window, cx))
             })?;
 
@@ -73,4 +86,17 @@ fn main() {
         })
         .detach();
     });
+
+    std::process::ExitCode::SUCCESS
+}
+
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+    // SAFETY: no preconditions; this fails harmlessly when there is no parent console, e.g. when
+    // started from Explorer.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
 }
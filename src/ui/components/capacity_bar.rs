@@ -0,0 +1,96 @@
+use crate::device::types::CredentialCapacity;
+use gpui::*;
+use gpui_component::{ActiveTheme, Icon, IconName, Theme, h_flex, progress::Progress, v_flex};
+
+/// Warn once fewer than this share of the discoverable credential slots is left.
+const NEARLY_FULL_RATIO: f32 = 0.1;
+
+/// Usage bar for discoverable credential storage, with a warning when the key is close to full.
+#[derive(IntoElement)]
+pub struct CapacityBar {
+    capacity: CredentialCapacity,
+    estimated: bool,
+}
+
+impl CapacityBar {
+    pub fn new(capacity: CredentialCapacity) -> Self {
+        Self {
+            capacity,
+            estimated: false,
+        }
+    }
+
+    /// Marks the count as read from getInfo rather than with getCredsMetadata.
+    pub fn estimated(mut self, estimated: bool) -> Self {
+        self.estimated = estimated;
+        self
+    }
+}
+
+impl RenderOnce for CapacityBar {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme: &Theme = cx.theme();
+
+        let remaining = self.capacity.remaining;
+        // Without getCredsMetadata the total is unknown, so only a full key can be flagged.
+        let (percent, nearly_full) = match self.capacity.existing {
+            Some(existing) => {
+                let total = existing + remaining;
+                let percent = if total > 0 {
+                    existing as f32 / total as f32 * 100.0
+                } else {
+                    100.0
+                };
+                let nearly_full =
+                    total == 0 || (remaining as f32) <= total as f32 * NEARLY_FULL_RATIO;
+                (Some(percent), nearly_full)
+            }
+            None => (None, remaining == 0),
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .text_sm()
+                    .child(
+                        div()
+                            .text_color(theme.muted_foreground)
+                            .child(if self.estimated {
+                                "Passkey Storage (reported by key)"
+                            } else {
+                                "Passkey Storage"
+                            }),
+                    )
+                    .child(
+                        div()
+                            .text_color(theme.foreground)
+                            .child(match self.capacity.existing {
+                                Some(existing) => {
+                                    format!("{} used, {} remaining", existing, remaining)
+                                }
+                                None => format!("{} remaining", remaining),
+                            }),
+                    ),
+            )
+            .children(percent.map(|percent| Progress::new().value(percent)))
+            .children(nearly_full.then(|| {
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .text_sm()
+                    .text_color(gpui::yellow())
+                    .child(Icon::new(IconName::TriangleAlert).size_4())
+                    .child(if remaining == 0 {
+                        "Passkey storage is full. New registrations will fail until passkeys are deleted."
+                            .to_string()
+                    } else {
+                        format!(
+                            "Only {} passkey slots left. Delete unused passkeys before registering new ones.",
+                            remaining
+                        )
+                    })
+            }))
+    }
+}
@@ -1,4 +1,6 @@
 pub mod button;
+pub mod capacity_bar;
 pub mod card;
 pub mod page_view;
+pub mod pin_warning;
 pub mod sidebar;
@@ -0,0 +1,43 @@
+use crate::device::types::PinRetries;
+use gpui::*;
+use gpui_component::ActiveTheme;
+
+/// Warn once this many PIN attempts or fewer are left.
+pub const PIN_RETRY_WARNING_THRESHOLD: u32 = 2;
+
+/// Lockout warning for dialogs and forms that send the FIDO PIN. Only built when there is
+/// something to warn about.
+#[derive(IntoElement)]
+pub struct PinRetryWarning {
+    message: String,
+}
+
+impl PinRetryWarning {
+    pub fn new(retries: Option<PinRetries>) -> Option<Self> {
+        let retries = retries?;
+        let message = if retries.pin_retries == 0 {
+            "The PIN is blocked. The key can only be used again after a factory reset of the FIDO application, which erases all passkeys.".to_string()
+        } else if retries.power_cycle_required {
+            "Too many wrong PINs in a row. Unplug and replug the key before trying again."
+                .to_string()
+        } else if retries.pin_retries <= PIN_RETRY_WARNING_THRESHOLD {
+            format!(
+                "Only {} PIN attempt{} left. Once none are left the PIN is blocked and the key can only be recovered with a factory reset, which erases all passkeys.",
+                retries.pin_retries,
+                if retries.pin_retries == 1 { "" } else { "s" }
+            )
+        } else {
+            return None;
+        };
+        Some(Self { message })
+    }
+}
+
+impl RenderOnce for PinRetryWarning {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        div()
+            .text_sm()
+            .text_color(cx.theme().danger)
+            .child(self.message)
+    }
+}
@@ -12,13 +12,17 @@ use gpui_component::{
 };
 use std::rc::Rc;
 
+type SelectHandler<V> = Rc<dyn Fn(&mut V, ActiveView, &mut Window, &mut Context<V>)>;
+type RefreshHandler<V> = Rc<dyn Fn(&mut V, &mut Window, &mut Context<V>)>;
+
 pub struct AppSidebar<V: 'static> {
     active_view: ActiveView,
     width: Pixels,
     collapsed: bool,
     state: GlobalDeviceState,
-    on_select: Option<Rc<dyn Fn(&mut V, ActiveView, &mut Window, &mut Context<V>)>>,
-    on_refresh: Option<Rc<dyn Fn(&mut V, &mut Window, &mut Context<V>)>>,
+    on_select: Option<SelectHandler<V>>,
+    on_refresh: Option<RefreshHandler<V>>,
+    on_choose_key: Option<RefreshHandler<V>>,
 }
 
 impl<V: 'static> AppSidebar<V> {
@@ -35,6 +39,7 @@ impl<V: 'static> AppSidebar<V> {
             state,
             on_select: None,
             on_refresh: None,
+            on_choose_key: None,
         }
     }
 
@@ -54,6 +59,15 @@ impl<V: 'static> AppSidebar<V> {
         self
     }
 
+    /// Shown only when more than one FIDO key is connected.
+    pub fn on_choose_key(
+        mut self,
+        handler: impl Fn(&mut V, &mut Window, &mut Context<V>) + 'static,
+    ) -> Self {
+        self.on_choose_key = Some(Rc::new(handler));
+        self
+    }
+
     pub fn render(self, cx: &mut Context<V>) -> impl IntoElement {
         let width = self.width;
         let collapsed = self.collapsed;
@@ -64,6 +78,8 @@ impl<V: 'static> AppSidebar<V> {
 
         let on_refresh = self.on_refresh.clone();
         let on_refresh_collapsed = self.on_refresh.clone();
+        let on_choose_key = self.on_choose_key.clone();
+        let key_count = state.fido_devices.len();
 
         v_flex()
             .h_full()
@@ -126,47 +142,7 @@ impl<V: 'static> AppSidebar<V> {
                     .w_full()
                     .flex_grow()
                     .bg(rgb(colors::zinc::ZINC900))
-                    .child(
-                        SidebarGroup::new("Menu").child(
-                            SidebarMenu::new()
-                                .child(self.menu_item(
-                                    cx,
-                                    "Home",
-                                    "icons/house.svg",
-                                    ActiveView::Home,
-                                ))
-                                .child(self.menu_item(
-                                    cx,
-                                    "Passkeys",
-                                    "icons/key-round.svg",
-                                    ActiveView::Passkeys,
-                                ))
-                                .child(self.menu_item(
-                                    cx,
-                                    "Configuration",
-                                    "icons/settings.svg",
-                                    ActiveView::Configuration,
-                                ))
-                                .child(self.menu_item(
-                                    cx,
-                                    "Security",
-                                    "icons/shield-check.svg",
-                                    ActiveView::Security,
-                                ))
-                                .child(self.menu_item(
-                                    cx,
-                                    "Logs",
-                                    "icons/scroll-text.svg",
-                                    ActiveView::Logs,
-                                ))
-                                .child(self.menu_item_icon_name(
-                                    cx,
-                                    "About",
-                                    IconName::Info,
-                                    ActiveView::About,
-                                )),
-                        ),
-                    ),
+                    .child(SidebarGroup::new("Menu").child(self.menu(cx))),
             )
             .child(
                 v_flex()
@@ -265,10 +241,50 @@ impl<V: 'static> AppSidebar<V> {
                                     },
                                 )),
                             )
+                            .children((key_count > 1).then(|| {
+                                PFIconButton::new(
+                                    Icon::default().path("icons/key.svg"),
+                                    format!("Choose Key ({} connected)", key_count),
+                                )
+                                .on_click(cx.listener(
+                                    move |this, _, window, cx| {
+                                        if let Some(f) = &on_choose_key {
+                                            f(this, window, cx);
+                                        }
+                                    },
+                                ))
+                            }))
                     }),
             )
     }
 
+    /// Pages for applets the key does not have are left out, e.g. Passkeys on a Pico HSM.
+    fn menu(&self, cx: &mut Context<V>) -> SidebarMenu {
+        let items: [(&'static str, &'static str, ActiveView); 9] = [
+            ("Home", "icons/house.svg", ActiveView::Home),
+            ("Passkeys", "icons/key-round.svg", ActiveView::Passkeys),
+            ("Authenticator", "icons/clock.svg", ActiveView::Oath),
+            ("OTP", "icons/key.svg", ActiveView::Otp),
+            ("OpenPGP", "icons/lock.svg", ActiveView::OpenPgp),
+            (
+                "Configuration",
+                "icons/settings.svg",
+                ActiveView::Configuration,
+            ),
+            ("Security", "icons/shield-check.svg", ActiveView::Security),
+            ("Tools", "icons/square-terminal.svg", ActiveView::Tools),
+            ("Logs", "icons/scroll-text.svg", ActiveView::Logs),
+        ];
+
+        let mut menu = SidebarMenu::new();
+        for (label, icon_path, view) in items {
+            if self.state.is_view_available(view) {
+                menu = menu.child(self.menu_item(cx, label, icon_path, view));
+            }
+        }
+        menu.child(self.menu_item_icon_name(cx, "About", IconName::Info, ActiveView::About))
+    }
+
     fn menu_item(
         &self,
         cx: &mut Context<V>,
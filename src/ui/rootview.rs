@@ -4,8 +4,11 @@ use crate::ui::ui_types::{ActiveView, GlobalDeviceState};
 use crate::ui::{
     colors,
     views::{
-        about::AboutView, config::ConfigView, home::HomeView, logs::LogsView,
-        passkeys::PasskeysEvent, passkeys::PasskeysView, security::SecurityView,
+        about::AboutView, config::ConfigEvent, config::ConfigView, home::HomeEvent, home::HomeView,
+        logs::LogsView, oath::OathEvent, oath::OathView, openpgp::OpenPgpEvent,
+        openpgp::OpenPgpView, otp::OtpEvent, otp::OtpView, passkeys::PasskeysEvent,
+        passkeys::PasskeysView, security::SecurityEvent, security::SecurityView, tools::ToolsEvent,
+        tools::ToolsView,
     },
 };
 
@@ -19,6 +22,12 @@ use gpui_component::{
     scroll::ScrollableElement,
     v_flex,
 };
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// How long the keys blink waiting for a touch in the device chooser.
+const SELECT_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub struct ApplicationRoot {
     active_view: ActiveView,
@@ -26,9 +35,21 @@ pub struct ApplicationRoot {
     state: GlobalDeviceState,
     device_loading: bool,
     sidebar_width: Pixels,
+    home_view: Option<Entity<HomeView>>,
     config_view: Option<Entity<ConfigView>>,
     passkeys_view: Option<Entity<PasskeysView>>,
+    security_view: Option<Entity<SecurityView>>,
+    tools_view: Option<Entity<ToolsView>>,
+    oath_view: Option<Entity<OathView>>,
+    otp_view: Option<Entity<OtpView>>,
+    openpgp_view: Option<Entity<OpenPgpView>>,
     logs_view: Option<Entity<LogsView>>,
+    /// Host secret unlock of a key with encryption at rest
+    unlock_task: Option<Task<()>>,
+    /// Applet probe that fills `state.capabilities`
+    probe_task: Option<Task<()>>,
+    /// Pending touch-to-select and the flag that aborts it
+    select_task: Option<(Task<()>, Arc<AtomicBool>)>,
 }
 
 impl ApplicationRoot {
@@ -39,9 +60,18 @@ impl ApplicationRoot {
             state: GlobalDeviceState::new(),
             device_loading: false,
             sidebar_width: px(255.),
+            home_view: None,
             config_view: None,
             passkeys_view: None,
+            security_view: None,
+            tools_view: None,
+            oath_view: None,
+            otp_view: None,
+            openpgp_view: None,
             logs_view: None,
+            unlock_task: None,
+            probe_task: None,
+            select_task: None,
         };
         this.refresh_device_status(None, cx);
         this
@@ -54,6 +84,8 @@ impl ApplicationRoot {
 
         self.device_loading = true;
         self.state.error = None;
+        self.state.fido_devices = io::list_fido_devices().unwrap_or_default();
+        self.probe_capabilities(cx);
         cx.notify();
 
         match io::read_device_details() {
@@ -71,12 +103,12 @@ impl ApplicationRoot {
                     }
                 }
 
-                if let Some(config_view) = &self.config_view {
-                    if let Some(window) = window {
-                        config_view.update(cx, |view, cx| {
-                            view.update_device_status(Some(status.clone()), window, cx);
-                        });
-                    }
+                if let Some(config_view) = &self.config_view
+                    && let Some(window) = window
+                {
+                    config_view.update(cx, |view, cx| {
+                        view.update_device_status(Some(status.clone()), window, cx);
+                    });
                 }
 
                 if let Some(passkeys_view) = &self.passkeys_view {
@@ -85,16 +117,195 @@ impl ApplicationRoot {
                         view.update_device_status(Some(status.clone()), fido, cx);
                     });
                 }
+
+                if let Some(security_view) = &self.security_view {
+                    let fido = self.state.fido_info.clone();
+                    security_view.update(cx, |view, cx| {
+                        view.update_device_status(Some(status.clone()), fido, cx);
+                    });
+                }
+
+                if let Some(tools_view) = &self.tools_view {
+                    let fido = self.state.fido_info.clone();
+                    tools_view.update(cx, |view, cx| {
+                        view.update_device_status(Some(status.clone()), fido, cx);
+                    });
+                }
+
+                self.unlock_device(status.info.serial.clone(), cx);
             }
             Err(e) => {
                 self.state.device_status = None;
                 self.state.error = Some(format!("{}", e));
                 self.state.fido_info = None;
+
+                if let Some(security_view) = &self.security_view {
+                    security_view.update(cx, |view, cx| {
+                        view.update_device_status(None, None, cx);
+                    });
+                }
+
+                if let Some(tools_view) = &self.tools_view {
+                    tools_view.update(cx, |view, cx| {
+                        view.update_device_status(None, None, cx);
+                    });
+                }
             }
         }
+        if let Some(home_view) = &self.home_view {
+            let state = self.state.clone();
+            home_view.update(cx, |view, cx| {
+                view.update_state(state, cx);
+            });
+        }
+
         self.device_loading = false;
         cx.notify();
     }
+
+    /// Selects every known applet in the background, then hides the pages the key cannot use.
+    fn probe_capabilities(&mut self, cx: &mut Context<Self>) {
+        self.probe_task = Some(cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::probe_capabilities() })
+                .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.state.capabilities = match result {
+                    Ok(caps) => Some(caps),
+                    Err(e) => {
+                        log::warn!("Capability probe failed: {}", e);
+                        None
+                    }
+                };
+                if !this.state.is_view_available(this.active_view) {
+                    this.active_view = ActiveView::Home;
+                }
+                this.update_capabilities(cx);
+                cx.notify();
+            });
+        }));
+    }
+
+    fn update_capabilities(&mut self, cx: &mut Context<Self>) {
+        if let Some(oath_view) = &self.oath_view {
+            let capabilities = self.state.capabilities.clone();
+            oath_view.update(cx, |view, cx| {
+                view.update_capabilities(capabilities, cx);
+            });
+        }
+        if let Some(otp_view) = &self.otp_view {
+            let capabilities = self.state.capabilities.clone();
+            otp_view.update(cx, |view, cx| {
+                view.update_capabilities(capabilities, cx);
+            });
+        }
+        if let Some(openpgp_view) = &self.openpgp_view {
+            let capabilities = self.state.capabilities.clone();
+            openpgp_view.update(cx, |view, cx| {
+                view.update_capabilities(capabilities, cx);
+            });
+        }
+        if let Some(security_view) = &self.security_view {
+            let capabilities = self.state.capabilities.clone();
+            security_view.update(cx, |view, cx| {
+                view.update_capabilities(capabilities, cx);
+            });
+        }
+        if let Some(home_view) = &self.home_view {
+            let state = self.state.clone();
+            home_view.update(cx, |view, cx| {
+                view.update_state(state, cx);
+            });
+        }
+    }
+
+    /// An encrypted key relocks on every power-up and cannot be asked whether it is locked, so the
+    /// host secret is sent on each refresh. Unlocking a key that is already unlocked is harmless.
+    fn unlock_device(&mut self, serial: String, cx: &mut Context<Self>) {
+        if !io::has_host_secret(&serial) {
+            return;
+        }
+
+        let task = cx.background_executor().spawn(async move {
+            match io::unlock_device(serial.clone()) {
+                Ok(_) => log::info!("Unlocked device {}", serial),
+                Err(e) => log::warn!("Could not unlock device {}: {}", serial, e),
+            }
+        });
+        self.unlock_task = Some(task);
+    }
+
+    fn open_choose_key_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.select_task.is_some() {
+            return;
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        let key_count = self.state.fido_devices.len();
+        let root = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let root = root.clone();
+
+            dialog
+                .title("Choose Key")
+                .close_button(false)
+                .overlay_closable(false)
+                .keyboard(false)
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .child(format!(
+                            "{} keys are connected and all of them are blinking now.",
+                            key_count
+                        ))
+                        .child("Touch the key you want PicoForge to manage. The other keys stop blinking as soon as one is touched."),
+                )
+                .footer(move |_, _, _, _| {
+                    let root = root.clone();
+
+                    vec![Button::new("cancel-select").label("Cancel").on_click(
+                        move |_, window, cx| {
+                            let _ = root.update(cx, |this, _| {
+                                if let Some((_, cancel)) = &this.select_task {
+                                    cancel.store(true, Ordering::Relaxed);
+                                }
+                            });
+                            window.close_dialog(cx);
+                        },
+                    )]
+                })
+        });
+
+        let flag = cancel.clone();
+        let task = cx.spawn_in(window, async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::select_device_by_touch(SELECT_TIMEOUT, flag) })
+                .await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let cancelled = this
+                    .select_task
+                    .take()
+                    .is_some_and(|(_, cancel)| cancel.load(Ordering::Relaxed));
+                if cancelled {
+                    return;
+                }
+                window.close_dialog(cx);
+                match result {
+                    Ok(device) => {
+                        window.push_notification(format!("Using {}", device.product_name), cx);
+                        this.state = GlobalDeviceState::new();
+                        this.refresh_device_status(Some(window), cx);
+                    }
+                    Err(e) => window.push_notification(format!("Error: {}", e), cx),
+                }
+            });
+        });
+        self.select_task = Some((task, cancel));
+    }
 }
 
 impl Render for ApplicationRoot {
@@ -125,6 +336,9 @@ impl Render for ApplicationRoot {
                     .on_refresh(|this, window, cx| {
                         this.refresh_device_status(Some(window), cx);
                     })
+                    .on_choose_key(|this, window, cx| {
+                        this.open_choose_key_dialog(window, cx);
+                    })
                     .render(cx),
                 )
                 .child(
@@ -157,12 +371,29 @@ impl Render for ApplicationRoot {
                                 .flex_grow()
                                 .bg(cx.theme().background)
                                 .child(match self.active_view {
-                                    ActiveView::Home => HomeView::build(
-                                        &self.state,
-                                        cx.theme(),
-                                        window.bounds().size.width,
-                                    )
-                                    .into_any_element(),
+                                    ActiveView::Home => {
+                                        let view = self.home_view.get_or_insert_with(|| {
+                                            let view = cx.new(|cx| {
+                                                HomeView::new(window, cx, self.state.clone())
+                                            });
+                                            cx.subscribe_in(
+                                                &view,
+                                                window,
+                                                |_, _, event: &HomeEvent, window, cx| match event {
+                                                    HomeEvent::Notification(msg) => {
+                                                        window
+                                                            .push_notification(msg.to_string(), cx);
+                                                    }
+                                                    HomeEvent::CloseDialog => {
+                                                        window.close_dialog(cx);
+                                                    }
+                                                },
+                                            )
+                                            .detach();
+                                            view
+                                        });
+                                        view.clone().into_any_element()
+                                    }
                                     ActiveView::Passkeys => {
                                         let view = self.passkeys_view.get_or_insert_with(|| {
                                             let view = cx.new(|cx| {
@@ -176,7 +407,7 @@ impl Render for ApplicationRoot {
                                             cx.subscribe_in(
                                                 &view,
                                                 window,
-                                                |_, _, event: &PasskeysEvent, window, cx| {
+                                                |this, _, event: &PasskeysEvent, window, cx| {
                                                     match event {
                                                         PasskeysEvent::Notification(msg) => {
                                                             window.push_notification(
@@ -187,6 +418,13 @@ impl Render for ApplicationRoot {
                                                         PasskeysEvent::CloseDialog => {
                                                             window.close_dialog(cx);
                                                         }
+                                                        PasskeysEvent::DeviceReset => {
+                                                            this.state = GlobalDeviceState::new();
+                                                            this.refresh_device_status(
+                                                                Some(window),
+                                                                cx,
+                                                            );
+                                                        }
                                                     }
                                                 },
                                             )
@@ -197,18 +435,159 @@ impl Render for ApplicationRoot {
                                     }
                                     ActiveView::Configuration => {
                                         let view = self.config_view.get_or_insert_with(|| {
-                                            cx.new(|cx| {
+                                            let view = cx.new(|cx| {
                                                 ConfigView::new(
                                                     window,
                                                     cx,
                                                     self.state.device_status.clone(),
                                                 )
-                                            })
+                                            });
+                                            cx.subscribe_in(
+                                                &view,
+                                                window,
+                                                |_, _, event: &ConfigEvent, window, cx| match event
+                                                {
+                                                    ConfigEvent::Notification(msg) => {
+                                                        window
+                                                            .push_notification(msg.to_string(), cx);
+                                                    }
+                                                },
+                                            )
+                                            .detach();
+                                            view
                                         });
                                         view.clone().into_any_element()
                                     }
                                     ActiveView::Security => {
-                                        SecurityView::build(cx).into_any_element()
+                                        let view = self.security_view.get_or_insert_with(|| {
+                                            let view = cx.new(|cx| {
+                                                SecurityView::new(
+                                                    window,
+                                                    cx,
+                                                    self.state.device_status.clone(),
+                                                    self.state.fido_info.clone(),
+                                                    self.state.capabilities.clone(),
+                                                )
+                                            });
+                                            cx.subscribe_in(
+                                                &view,
+                                                window,
+                                                |_, _, event: &SecurityEvent, window, cx| {
+                                                    match event {
+                                                        SecurityEvent::Notification(msg) => {
+                                                            window.push_notification(
+                                                                msg.to_string(),
+                                                                cx,
+                                                            );
+                                                        }
+                                                        SecurityEvent::CloseDialog => {
+                                                            window.close_dialog(cx);
+                                                        }
+                                                    }
+                                                },
+                                            )
+                                            .detach();
+                                            view
+                                        });
+                                        view.clone().into_any_element()
+                                    }
+                                    ActiveView::Tools => {
+                                        let view = self.tools_view.get_or_insert_with(|| {
+                                            let view = cx.new(|cx| {
+                                                ToolsView::new(
+                                                    window,
+                                                    cx,
+                                                    self.state.device_status.clone(),
+                                                    self.state.fido_info.clone(),
+                                                )
+                                            });
+                                            cx.subscribe_in(
+                                                &view,
+                                                window,
+                                                |_, _, event: &ToolsEvent, window, cx| match event {
+                                                    ToolsEvent::Notification(msg) => {
+                                                        window
+                                                            .push_notification(msg.to_string(), cx);
+                                                    }
+                                                },
+                                            )
+                                            .detach();
+                                            view
+                                        });
+                                        view.clone().into_any_element()
+                                    }
+                                    ActiveView::Oath => {
+                                        let view = self.oath_view.get_or_insert_with(|| {
+                                            let view = cx.new(|cx| {
+                                                OathView::new(
+                                                    window,
+                                                    cx,
+                                                    self.state.capabilities.clone(),
+                                                )
+                                            });
+                                            cx.subscribe_in(
+                                                &view,
+                                                window,
+                                                |_, _, event: &OathEvent, window, cx| match event {
+                                                    OathEvent::Notification(msg) => {
+                                                        window
+                                                            .push_notification(msg.to_string(), cx);
+                                                    }
+                                                },
+                                            )
+                                            .detach();
+                                            view
+                                        });
+                                        view.clone().into_any_element()
+                                    }
+                                    ActiveView::Otp => {
+                                        let view = self.otp_view.get_or_insert_with(|| {
+                                            let view = cx.new(|cx| {
+                                                OtpView::new(
+                                                    window,
+                                                    cx,
+                                                    self.state.capabilities.clone(),
+                                                )
+                                            });
+                                            cx.subscribe_in(
+                                                &view,
+                                                window,
+                                                |_, _, event: &OtpEvent, window, cx| match event {
+                                                    OtpEvent::Notification(msg) => {
+                                                        window
+                                                            .push_notification(msg.to_string(), cx);
+                                                    }
+                                                },
+                                            )
+                                            .detach();
+                                            view
+                                        });
+                                        view.clone().into_any_element()
+                                    }
+                                    ActiveView::OpenPgp => {
+                                        let view = self.openpgp_view.get_or_insert_with(|| {
+                                            let view = cx.new(|cx| {
+                                                OpenPgpView::new(
+                                                    window,
+                                                    cx,
+                                                    self.state.capabilities.clone(),
+                                                )
+                                            });
+                                            cx.subscribe_in(
+                                                &view,
+                                                window,
+                                                |_, _, event: &OpenPgpEvent, window, cx| match event
+                                                {
+                                                    OpenPgpEvent::Notification(msg) => {
+                                                        window
+                                                            .push_notification(msg.to_string(), cx);
+                                                    }
+                                                },
+                                            )
+                                            .detach();
+                                            view
+                                        });
+                                        view.clone().into_any_element()
                                     }
                                     ActiveView::Logs => {
                                         let view = self.logs_view.get_or_insert_with(|| {
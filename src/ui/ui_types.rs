@@ -1,13 +1,19 @@
 use gpui::SharedString;
 
-use crate::device::types::{FidoDeviceInfo, FullDeviceStatus};
+use crate::device::types::{
+    DeviceCapabilities, FidoDeviceInfo, FidoHidDevice, FullDeviceStatus, PicoApplet,
+};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ActiveView {
     Home,
     Passkeys,
+    Oath,
+    Otp,
+    OpenPgp,
     Configuration,
     Security,
+    Tools,
     Logs,
     About,
 }
@@ -17,6 +23,10 @@ pub struct GlobalDeviceState {
     pub device_status: Option<FullDeviceStatus>,
     pub fido_info: Option<FidoDeviceInfo>,
     pub error: Option<String>,
+    /// Every connected FIDO key, so that the sidebar can offer touch-to-select
+    pub fido_devices: Vec<FidoHidDevice>,
+    /// Firmware and applets found by probing; `None` until a key answered
+    pub capabilities: Option<DeviceCapabilities>,
 }
 
 impl GlobalDeviceState {
@@ -25,6 +35,32 @@ impl GlobalDeviceState {
             device_status: None,
             fido_info: None,
             error: None,
+            fido_devices: Vec::new(),
+            capabilities: None,
+        }
+    }
+
+    /// Pages are hidden once probing shows the key lacks their applet. Until then every page is
+    /// shown, as before.
+    pub fn is_view_available(&self, view: ActiveView) -> bool {
+        match view {
+            ActiveView::Passkeys | ActiveView::Tools => self
+                .capabilities
+                .as_ref()
+                .is_none_or(|caps| caps.fido_available()),
+            ActiveView::Oath => self
+                .capabilities
+                .as_ref()
+                .is_none_or(|caps| caps.has(PicoApplet::Oath)),
+            ActiveView::Otp => self
+                .capabilities
+                .as_ref()
+                .is_none_or(|caps| caps.has(PicoApplet::Otp)),
+            ActiveView::OpenPgp => self
+                .capabilities
+                .as_ref()
+                .is_none_or(|caps| caps.has(PicoApplet::OpenPgp)),
+            _ => true,
         }
     }
 }
@@ -167,3 +203,76 @@ impl LedDriverType {
         ]
     }
 }
+
+// passkeys view:
+
+/// Progress of the guided FIDO reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetStage {
+    Unplug,
+    Replug,
+    Touch,
+}
+
+impl ResetStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Unplug => "Unplug the key now.",
+            Self::Replug => "Plug the key back in.",
+            Self::Touch => "Touch the key to confirm the reset.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasskeySortOrder {
+    RelyingParty,
+    User,
+}
+
+impl PasskeySortOrder {
+    pub fn label(&self) -> SharedString {
+        match self {
+            Self::RelyingParty => "Sort by Relying Party".into(),
+            Self::User => "Sort by User".into(),
+        }
+    }
+
+    pub fn all() -> &'static [Self] {
+        &[Self::RelyingParty, Self::User]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredProtectFilter {
+    Any,
+    Level1,
+    Level2,
+    Level3,
+}
+
+impl CredProtectFilter {
+    pub fn label(&self) -> SharedString {
+        match self {
+            Self::Any => "Any credProtect".into(),
+            Self::Level1 => "credProtect 1 (UV optional)".into(),
+            Self::Level2 => "credProtect 2 (UV optional with ID list)".into(),
+            Self::Level3 => "credProtect 3 (UV required)".into(),
+        }
+    }
+
+    /// Credentials that don't report credProtect are treated as level 1 (the CTAP default).
+    pub fn matches(&self, cred_protect: Option<u8>) -> bool {
+        let level = cred_protect.unwrap_or(1);
+        match self {
+            Self::Any => true,
+            Self::Level1 => level == 1,
+            Self::Level2 => level == 2,
+            Self::Level3 => level == 3,
+        }
+    }
+
+    pub fn all() -> &'static [Self] {
+        &[Self::Any, Self::Level1, Self::Level2, Self::Level3]
+    }
+}
@@ -1,11 +1,15 @@
 use crate::device::io;
-use crate::device::types::{AppConfigInput, FullDeviceStatus};
-use crate::ui::components::{card::Card, page_view::PageView};
+use crate::device::management::constants::{CAPS_CCID, CAPS_FIDO_HID};
+use crate::device::types::{
+    AppConfigInput, DeviceMethod, FullDeviceStatus, ManagementApp, ManagementInfo,
+};
+use crate::ui::components::{button::PFButton, card::Card, page_view::PageView};
 use crate::ui::ui_types::{LedDriverType, UsbIdentityPreset};
+use gpui::prelude::*;
 use gpui::*;
-use gpui_component::button::{ButtonCustomVariant, ButtonVariants};
+use gpui_component::button::{ButtonCustomVariant, ButtonVariant, ButtonVariants};
 use gpui_component::{
-    ActiveTheme, Disableable, Icon, Theme,
+    ActiveTheme, Disableable, Icon, Theme, WindowExt,
     button::Button,
     input::{Input, InputState},
     select::{Select, SelectItem, SelectState},
@@ -66,9 +70,21 @@ pub struct ConfigView {
     loading: bool,
     device_status: Option<FullDeviceStatus>,
     is_custom_vendor: bool,
+    /// Management applet info; `None` until read or when the key has no such applet
+    interfaces: Option<ManagementInfo>,
+    /// USB applications as toggled in the Interfaces card, not yet written
+    interfaces_enabled: u16,
+    interfaces_loading: bool,
     _task: Option<Task<()>>,
+    _interfaces_task: Option<Task<()>>,
+}
+
+pub enum ConfigEvent {
+    Notification(String),
 }
 
+impl EventEmitter<ConfigEvent> for ConfigView {}
+
 impl ConfigView {
     pub fn new(
         window: &mut Window,
@@ -186,7 +202,7 @@ impl ConfigView {
         let touch_timeout_input =
             cx.new(|cx| InputState::new(window, cx).default_value(current_touch_timeout.clone()));
 
-        Self {
+        let mut this = Self {
             vendor_select,
             vid_input,
             pid_input,
@@ -202,8 +218,159 @@ impl ConfigView {
             loading: false,
             device_status: device_status.clone(),
             is_custom_vendor,
+            interfaces: None,
+            interfaces_enabled: 0,
+            interfaces_loading: false,
             _task: None,
+            _interfaces_task: None,
+        };
+        if this.device_status.is_some() {
+            this.load_interfaces(cx);
+        }
+        this
+    }
+
+    fn load_interfaces(&mut self, cx: &mut Context<Self>) {
+        self.interfaces_loading = true;
+        cx.notify();
+
+        self._interfaces_task = Some(cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::management_info() })
+                .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.interfaces_loading = false;
+                match result {
+                    Ok(info) => {
+                        this.interfaces_enabled = info.usb_enabled;
+                        this.interfaces = Some(info);
+                    }
+                    Err(e) => {
+                        log::warn!("Management applet not available: {}", e);
+                        this.interfaces = None;
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn toggle_interface(&mut self, app: ManagementApp, enabled: bool, cx: &mut Context<Self>) {
+        if enabled {
+            self.interfaces_enabled |= app.capability();
+        } else {
+            self.interfaces_enabled &= !app.capability();
         }
+        cx.notify();
+    }
+
+    /// Warnings for interfaces PicoForge relies on that the pending change would switch off.
+    fn interface_warnings(&self, current: u16) -> Vec<&'static str> {
+        let new = self.interfaces_enabled;
+        let lost = |mask: u16| current & mask != 0 && new & mask == 0;
+        let mut warnings = Vec::new();
+
+        let uses_fido = self
+            .device_status
+            .as_ref()
+            .is_some_and(|s| s.method == DeviceMethod::Fido);
+        if uses_fido && lost(CAPS_FIDO_HID) {
+            warnings.push(
+                "PicoForge reads and writes this key's configuration over the FIDO interface. Without FIDO2 or U2F only the rescue applet can reach it, and passkeys stop working.",
+            );
+        }
+        if lost(CAPS_CCID) {
+            warnings.push(
+                "This card talks to the management applet over the smart card (CCID) interface. With no smart card application left, PicoForge cannot switch them back on.",
+            );
+        }
+        warnings
+    }
+
+    fn apply_interfaces(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(info) = &self.interfaces else {
+            return;
+        };
+        if self.interfaces_enabled == 0 {
+            cx.emit(ConfigEvent::Notification(
+                "At least one application must stay enabled".to_string(),
+            ));
+            return;
+        }
+        let enabled = self.interfaces_enabled;
+        let warnings = self.interface_warnings(info.usb_enabled);
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, cx| {
+            let view_handle = view_handle.clone();
+            let theme = cx.theme();
+
+            let mut body = v_flex().gap_3().child(
+                "The key restarts to apply the new USB applications. Unsaved changes elsewhere on this page are lost.",
+            );
+            for warning in &warnings {
+                body = body.child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.danger)
+                        .child(*warning),
+                );
+            }
+
+            dialog
+                .confirm()
+                .title("Change USB Applications")
+                .child(body)
+                .on_ok(move |_, _, cx| {
+                    let _ = view_handle.update(cx, |this, cx| {
+                        this.write_interfaces(enabled, cx);
+                    });
+                    true
+                })
+                .on_cancel(|_, _, _| true)
+                .button_props(
+                    gpui_component::dialog::DialogButtonProps::default()
+                        .ok_text("Apply & Restart")
+                        .ok_variant(if warnings.is_empty() {
+                            ButtonVariant::Primary
+                        } else {
+                            ButtonVariant::Danger
+                        }),
+                )
+        });
+    }
+
+    fn write_interfaces(&mut self, enabled: u16, cx: &mut Context<Self>) {
+        self.interfaces_loading = true;
+        cx.notify();
+
+        self._interfaces_task = Some(cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::management_set_usb_enabled(enabled) })
+                .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.interfaces_loading = false;
+                match result {
+                    Ok(msg) => {
+                        if let Some(info) = &mut this.interfaces {
+                            info.usb_enabled = enabled;
+                        }
+                        cx.emit(ConfigEvent::Notification(msg));
+                    }
+                    Err(e) => {
+                        if let Some(info) = &this.interfaces {
+                            this.interfaces_enabled = info.usb_enabled;
+                        }
+                        cx.emit(ConfigEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+            });
+        }));
     }
 
     fn apply_changes(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
@@ -244,19 +411,19 @@ impl ConfigView {
         }
 
         let led_gpio_str = self.led_gpio_input.read(cx).text().to_string();
-        if let Ok(val) = led_gpio_str.parse::<u8>() {
-            if val != current_config.led_gpio {
-                changes.led_gpio = Some(val);
-            }
+        if let Ok(val) = led_gpio_str.parse::<u8>()
+            && val != current_config.led_gpio
+        {
+            changes.led_gpio = Some(val);
         }
 
         let driver_idx = self.led_driver_select.read(cx).selected_index(cx);
-        if let Some(idx) = driver_idx {
-            if let Some(driver) = LedDriverType::all().get(idx.row) {
-                let val = driver.value();
-                if Some(val) != current_config.led_driver {
-                    changes.led_driver = Some(val);
-                }
+        if let Some(idx) = driver_idx
+            && let Some(driver) = LedDriverType::all().get(idx.row)
+        {
+            let val = driver.value();
+            if Some(val) != current_config.led_driver {
+                changes.led_driver = Some(val);
             }
         }
 
@@ -266,10 +433,10 @@ impl ConfigView {
         }
 
         let touch_timeout_str = self.touch_timeout_input.read(cx).text().to_string();
-        if let Ok(val) = touch_timeout_str.parse::<u8>() {
-            if val != current_config.touch_timeout {
-                changes.touch_timeout = Some(val);
-            }
+        if let Ok(val) = touch_timeout_str.parse::<u8>()
+            && val != current_config.touch_timeout
+        {
+            changes.touch_timeout = Some(val);
         }
 
         if (self.led_dimmable != current_config.led_dimmable)
@@ -396,6 +563,12 @@ impl ConfigView {
         self.led_brightness_slider
             .update(cx, |slider, cx| slider.set_value(brightness, window, cx));
 
+        if self.device_status.is_some() {
+            self.load_interfaces(cx);
+        } else {
+            self.interfaces = None;
+        }
+
         cx.notify();
     }
 
@@ -607,6 +780,74 @@ impl ConfigView {
     }
 }
 
+impl ConfigView {
+    fn render_interfaces_card(&mut self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let info = self.interfaces.clone()?;
+        let busy = self.interfaces_loading || info.config_locked;
+
+        let mut list = v_flex().gap_4();
+        for app in ManagementApp::all().iter().copied() {
+            if info.usb_supported & app.capability() == 0 {
+                continue;
+            }
+            let listener = cx.listener(move |this, checked: &bool, _, cx| {
+                this.toggle_interface(app, *checked, cx);
+            });
+            list = list.child(
+                gpui_component::h_flex()
+                    .items_center()
+                    .justify_between()
+                    .child(app.name())
+                    .child(
+                        Switch::new(SharedString::from(format!("interface-{:?}", app)))
+                            .checked(self.interfaces_enabled & app.capability() != 0)
+                            .disabled(busy)
+                            .on_click(listener),
+                    ),
+            );
+        }
+
+        let apply_listener = cx.listener(|this, _, window, cx| {
+            this.apply_interfaces(window, cx);
+        });
+        let theme = cx.theme();
+
+        let mut description = "Applications enabled over USB".to_string();
+        if let Some(version) = &info.version {
+            description.push_str(&format!(" (management {})", version));
+        }
+
+        Some(
+            Card::new()
+                .title("Interfaces")
+                .description(description)
+                .icon(Icon::default().path("icons/network.svg"))
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .child(list)
+                        .when(info.config_locked, |this| {
+                            this.child(
+                                div()
+                                    .text_sm()
+                                    .text_color(theme.muted_foreground)
+                                    .child("The configuration is locked with a lock code."),
+                            )
+                        })
+                        .child(
+                            gpui_component::h_flex().justify_end().child(
+                                PFButton::new("Apply & Restart")
+                                    .id("apply-interfaces")
+                                    .small()
+                                    .disabled(busy || self.interfaces_enabled == info.usb_enabled)
+                                    .on_click(apply_listener),
+                            ),
+                        ),
+                ),
+        )
+    }
+}
+
 impl Render for ConfigView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = cx.theme();
@@ -630,6 +871,9 @@ impl Render for ConfigView {
 
         let led_card = self.render_led_card(cx).into_any_element();
         let options_card = self.render_options_card(cx).into_any_element();
+        let interfaces_card = self
+            .render_interfaces_card(cx)
+            .map(|card| card.into_any_element());
 
         let theme = cx.theme();
 
@@ -652,7 +896,8 @@ impl Render for ConfigView {
                         .child(identity_card)
                         .child(led_card)
                         .child(touch_card)
-                        .child(options_card),
+                        .child(options_card)
+                        .children(interfaces_card),
                 )
                 .child(
                     gpui_component::h_flex().justify_end().child(
@@ -672,7 +917,7 @@ impl Render for ConfigView {
                             })),
                     ),
                 ),
-            &theme,
+            theme,
         )
         .into_any_element()
     }
@@ -1,55 +1,232 @@
-use crate::device::types::DeviceMethod;
-use crate::ui::components::{card::Card, page_view::PageView};
+use crate::device::io;
+use crate::device::types::{DeviceMethod, HealthCheckReport};
+use crate::ui::components::{
+    button::PFButton, capacity_bar::CapacityBar, card::Card, page_view::PageView,
+};
 use crate::ui::ui_types::GlobalDeviceState;
+use gpui::prelude::*;
 use gpui::*;
-use gpui_component::StyledExt;
-use gpui_component::{Icon, IconName, Theme, badge::Badge, h_flex, progress::Progress, v_flex};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::{ActiveTheme, StyledExt, WindowExt};
+use gpui_component::{
+    Icon, IconName, Theme,
+    badge::Badge,
+    h_flex,
+    input::{Input, InputState},
+    progress::Progress,
+    v_flex,
+};
 
-pub struct HomeView;
+pub struct HomeView {
+    state: GlobalDeviceState,
+    health_report: Option<HealthCheckReport>,
+    loading: bool,
+
+    _task: Option<Task<()>>,
+}
+
+pub enum HomeEvent {
+    Notification(String),
+    CloseDialog,
+}
+
+impl EventEmitter<HomeEvent> for HomeView {}
 
 impl HomeView {
-    pub fn build(
-        state: &GlobalDeviceState,
-        theme: &Theme,
-        window_width: Pixels,
-    ) -> impl IntoElement {
-        let connected = state.device_status.is_some();
-        let is_wide = window_width > px(1100.0);
-        let columns = if is_wide { 2 } else { 1 };
+    pub fn new(_window: &mut Window, _cx: &mut Context<Self>, state: GlobalDeviceState) -> Self {
+        Self {
+            state,
+            health_report: None,
+            loading: false,
+            _task: None,
+        }
+    }
 
-        PageView::build(
-            "Device Overview",
-            "Quick view of your device status and specifications.",
-            if !connected {
-                // No Device Status Placeholder
-                div()
-                    .flex()
-                    .items_center()
-                    .justify_center()
-                    .h_64()
-                    .border_1()
-                    .border_color(theme.border)
-                    .rounded_xl()
-                    .child(
-                        div()
-                            .text_color(theme.muted_foreground)
-                            .child("No Device Connected"),
-                    )
-                    .into_any_element()
-            } else {
-                // Card Grid
-                div()
-                    .grid()
-                    .grid_cols(columns)
-                    .gap_6()
-                    .child(Self::render_device_info(state, theme))
-                    .child(Self::render_fido_info(state, theme))
-                    .child(Self::render_led_config(state, theme))
-                    .child(Self::render_security_status(state, theme))
-                    .into_any_element()
-            },
-            theme,
-        )
+    pub fn update_state(&mut self, state: GlobalDeviceState, cx: &mut Context<Self>) {
+        if self.state == state {
+            return;
+        }
+        if self.state.device_status.as_ref().map(|s| &s.info.serial)
+            != state.device_status.as_ref().map(|s| &s.info.serial)
+        {
+            self.health_report = None;
+        }
+        self.state = state;
+        cx.notify();
+    }
+
+    fn run_health_check(&mut self, pin: Option<String>, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        self.health_report = None;
+        cx.emit(HomeEvent::CloseDialog);
+        cx.emit(HomeEvent::Notification(
+            "Touch your key each time it blinks".to_string(),
+        ));
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::run_health_check(pin) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(report) => {
+                        cx.emit(HomeEvent::Notification(if report.passed {
+                            "Health check passed".to_string()
+                        } else {
+                            "Health check found problems".to_string()
+                        }));
+                        this.health_report = Some(report);
+                    }
+                    Err(e) => {
+                        cx.emit(HomeEvent::Notification(format!(
+                            "Health check failed: {}",
+                            e
+                        )));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn open_health_check_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let pin_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("FIDO PIN (optional)")
+                .masked(true)
+        });
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view_handle.clone();
+            let input = pin_input.clone();
+
+            dialog
+                .title("Run Health Check")
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .child("A throwaway credential is created for a dummy site and used once. Nothing is stored on the key. Enter your PIN to also test user verification.")
+                        .child(Input::new(&pin_input)),
+                )
+                .footer(move |_, _, _, _| {
+                    let view = view.clone();
+                    let input = input.clone();
+
+                    vec![
+                        Button::new("cancel")
+                            .label("Cancel")
+                            .on_click(|_, window, cx| window.close_dialog(cx)),
+                        Button::new("run").primary().label("Run").on_click(
+                            move |_, _, cx| {
+                                let pin = input.read(cx).text().to_string();
+                                let pin = (!pin.is_empty()).then_some(pin);
+                                let _ = view.update(cx, |this, cx| {
+                                    this.run_health_check(pin, cx);
+                                });
+                            },
+                        ),
+                    ]
+                })
+        });
+    }
+
+    fn render_health_check(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let has_fido = self.state.fido_info.is_some();
+        let loading = self.loading;
+        let run_listener = cx.listener(|this, _, window, cx| {
+            this.open_health_check_dialog(window, cx);
+        });
+
+        let theme = cx.theme();
+
+        let body = match &self.health_report {
+            Some(report) => v_flex()
+                .gap_3()
+                .text_sm()
+                .child(
+                    h_flex()
+                        .justify_between()
+                        .items_center()
+                        .child(
+                            div()
+                                .text_color(theme.muted_foreground)
+                                .child(format!("Algorithm {}", report.algorithm)),
+                        )
+                        .child(
+                            Badge::new()
+                                .child(if report.passed { "Passed" } else { "Failed" })
+                                .color(if report.passed {
+                                    gpui::green()
+                                } else {
+                                    gpui::red()
+                                }),
+                        ),
+                )
+                .children(report.steps.iter().map(|step| {
+                    h_flex()
+                        .justify_between()
+                        .items_center()
+                        .gap_4()
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .items_center()
+                                .child(
+                                    Icon::default()
+                                        .path(if step.passed {
+                                            "icons/circle-check.svg"
+                                        } else {
+                                            "icons/circle-x.svg"
+                                        })
+                                        .size_4()
+                                        .text_color(if step.passed {
+                                            gpui::green()
+                                        } else {
+                                            gpui::red()
+                                        }),
+                                )
+                                .child(step.name.clone()),
+                        )
+                        .child(div().text_color(theme.muted_foreground).child(
+                            if step.duration_ms > 0 {
+                                format!("{} ({} ms)", step.detail, step.duration_ms)
+                            } else {
+                                step.detail.clone()
+                            },
+                        ))
+                }))
+                .into_any_element(),
+            None => div()
+                .text_sm()
+                .text_color(theme.muted_foreground)
+                .child(if loading {
+                    "Running... touch your key when it blinks."
+                } else {
+                    "Create and use a throwaway credential to check that the key can sign."
+                })
+                .into_any_element(),
+        };
+
+        Card::new()
+            .title("Health Check")
+            .icon(Icon::default().path("icons/heart.svg"))
+            .header_right(
+                PFButton::new("Run Self-Test")
+                    .id("health-check-btn")
+                    .small()
+                    .disabled(!has_fido || loading)
+                    .on_click(run_listener),
+            )
+            .child(body)
     }
 
     // Helper for Key-Value pairs
@@ -88,6 +265,40 @@ impl HomeView {
 
         let flash_percent = (info.flash_used as f32 / info.flash_total as f32) * 100.0;
 
+        let applications =
+            state.capabilities.as_ref().map(|caps| {
+                v_flex()
+                    .gap_2()
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .text_sm()
+                            .child(
+                                div()
+                                    .text_color(theme.muted_foreground)
+                                    .child("Applications"),
+                            )
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(theme.foreground)
+                                    .child(caps.firmware.name()),
+                            ),
+                    )
+                    .child(
+                        h_flex()
+                            .flex_wrap()
+                            .gap_2()
+                            .children(caps.applets.iter().map(|applet| {
+                                Badge::new().child(applet.name()).color(theme.primary)
+                            }))
+                            .children(
+                                caps.ctaphid
+                                    .then(|| Badge::new().child("CTAPHID").color(theme.secondary)),
+                            ),
+                    )
+            });
+
         Card::new()
             .title("Device Information")
             .icon(Icon::default().path("icons/cpu.svg"))
@@ -124,6 +335,7 @@ impl HomeView {
                                 false,
                             )),
                     )
+                    .children(applications)
                     .child(div().h_px().bg(theme.border))
                     .child(
                         v_flex()
@@ -194,6 +406,14 @@ impl HomeView {
                     )
                     .child(div().h_px().bg(theme.border))
                     .child(Self::render_kv("AAGUID", fido.aaguid.clone(), theme, true))
+                    .when_some(
+                        io::estimate_capacity(fido)
+                            .filter(|_| fido.options.get("rk").copied().unwrap_or(false)),
+                        |el, capacity| {
+                            el.child(div().h_px().bg(theme.border))
+                                .child(CapacityBar::new(capacity).estimated(true))
+                        },
+                    )
                     .into_any_element()
             } else {
                 div()
@@ -389,3 +609,50 @@ impl HomeView {
             )
     }
 }
+
+impl Render for HomeView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let connected = self.state.device_status.is_some();
+        let is_wide = window.bounds().size.width > px(1100.0);
+        let columns = if is_wide { 2 } else { 1 };
+
+        let health_check = connected.then(|| self.render_health_check(cx).into_any_element());
+        let state = &self.state;
+        let theme = cx.theme();
+
+        PageView::build(
+            "Device Overview",
+            "Quick view of your device status and specifications.",
+            if !connected {
+                // No Device Status Placeholder
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .h_64()
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded_xl()
+                    .child(
+                        div()
+                            .text_color(theme.muted_foreground)
+                            .child("No Device Connected"),
+                    )
+                    .into_any_element()
+            } else {
+                // Card Grid
+                div()
+                    .grid()
+                    .grid_cols(columns)
+                    .gap_6()
+                    .child(Self::render_device_info(state, theme))
+                    .child(Self::render_fido_info(state, theme))
+                    .child(Self::render_led_config(state, theme))
+                    .child(Self::render_security_status(state, theme))
+                    .children(health_check)
+                    .into_any_element()
+            },
+            theme,
+        )
+    }
+}
@@ -38,18 +38,18 @@ impl LogsView {
     }
 
     fn sync_logs(&mut self) {
-        if let Some(buffer) = LOG_BUFFER.get() {
-            if let Ok(logs) = buffer.lock() {
-                self.logs = logs.clone();
-            }
+        if let Some(buffer) = LOG_BUFFER.get()
+            && let Ok(logs) = buffer.lock()
+        {
+            self.logs = logs.clone();
         }
     }
 
     fn clear(&mut self, cx: &mut Context<Self>) {
-        if let Some(buffer) = LOG_BUFFER.get() {
-            if let Ok(mut logs) = buffer.lock() {
-                logs.clear();
-            }
+        if let Some(buffer) = LOG_BUFFER.get()
+            && let Ok(mut logs) = buffer.lock()
+        {
+            logs.clear();
         }
         self.logs.clear();
         cx.notify();
@@ -2,5 +2,9 @@ pub mod about;
 pub mod config;
 pub mod home;
 pub mod logs;
+pub mod oath;
+pub mod openpgp;
+pub mod otp;
 pub mod passkeys;
 pub mod security;
+pub mod tools;
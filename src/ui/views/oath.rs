@@ -0,0 +1,916 @@
+use crate::device::io;
+use crate::device::oath::{
+    OathCredentialData,
+    constants::{DEFAULT_DIGITS, DEFAULT_PERIOD},
+    uri,
+};
+use crate::device::types::{DeviceCapabilities, OathAccount, OathAlgorithm, OathType, PicoApplet};
+use crate::ui::components::{button::PFButton, card::Card, page_view::PageView};
+use gpui::prelude::*;
+use gpui::*;
+use gpui_component::button::ButtonVariant;
+use gpui_component::{
+    ActiveTheme, Icon, StyledExt, WindowExt,
+    badge::Badge,
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputState},
+    radio::{Radio, RadioGroup},
+    v_flex,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const OATH_TYPES: [OathType; 2] = [OathType::Totp, OathType::Hotp];
+const OATH_ALGORITHMS: [OathAlgorithm; 3] = [
+    OathAlgorithm::Sha1,
+    OathAlgorithm::Sha256,
+    OathAlgorithm::Sha512,
+];
+const OATH_DIGITS: [u8; 2] = [6, 8];
+
+pub struct OathView {
+    capabilities: Option<DeviceCapabilities>,
+    password_input: Entity<InputState>,
+    accounts: Option<Vec<OathAccount>>,
+    /// Index of the account whose name is being edited
+    renaming: Option<usize>,
+    rename_issuer_input: Entity<InputState>,
+    rename_account_input: Entity<InputState>,
+    uri_input: Entity<InputState>,
+    issuer_input: Entity<InputState>,
+    account_input: Entity<InputState>,
+    secret_input: Entity<InputState>,
+    period_input: Entity<InputState>,
+    add_type: OathType,
+    add_algorithm: OathAlgorithm,
+    add_digits: u8,
+    add_touch: bool,
+    new_password_input: Entity<InputState>,
+    confirm_password_input: Entity<InputState>,
+    loading: bool,
+
+    _task: Option<Task<()>>,
+}
+
+pub enum OathEvent {
+    Notification(String),
+}
+
+impl EventEmitter<OathEvent> for OathView {}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl OathView {
+    pub fn new(
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        capabilities: Option<DeviceCapabilities>,
+    ) -> Self {
+        let password_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("OATH password (only if one is set)")
+                .masked(true)
+        });
+        let rename_issuer_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Issuer (optional)"));
+        let rename_account_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Account name"));
+        let uri_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("otpauth://totp/Issuer:account?secret=...")
+        });
+        let issuer_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Issuer (optional)"));
+        let account_input = cx.new(|cx| InputState::new(window, cx).placeholder("Account name"));
+        let secret_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Secret key (Base32)")
+                .masked(true)
+        });
+        let period_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Period in seconds")
+                .default_value(DEFAULT_PERIOD.to_string())
+        });
+        let new_password_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("New password")
+                .masked(true)
+        });
+        let confirm_password_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Confirm new password")
+                .masked(true)
+        });
+
+        // Counts down the TOTP codes and reads fresh ones when a period ends.
+        let view_weak = cx.entity().downgrade();
+        let mut cx_async = cx.to_async();
+        cx.spawn(async move |_, _| {
+            loop {
+                cx_async
+                    .background_executor()
+                    .timer(Duration::from_secs(1))
+                    .await;
+
+                let Some(view) = view_weak.upgrade() else {
+                    break;
+                };
+                view.update(&mut cx_async, |view, cx| {
+                    if view.has_expired_code() && !view.loading {
+                        view.load_accounts(cx);
+                    }
+                    cx.notify();
+                })
+                .ok();
+            }
+        })
+        .detach();
+
+        Self {
+            capabilities,
+            password_input,
+            accounts: None,
+            renaming: None,
+            rename_issuer_input,
+            rename_account_input,
+            uri_input,
+            issuer_input,
+            account_input,
+            secret_input,
+            period_input,
+            add_type: OathType::Totp,
+            add_algorithm: OathAlgorithm::Sha1,
+            add_digits: DEFAULT_DIGITS,
+            add_touch: false,
+            new_password_input,
+            confirm_password_input,
+            loading: false,
+            _task: None,
+        }
+    }
+
+    pub fn update_capabilities(
+        &mut self,
+        capabilities: Option<DeviceCapabilities>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.capabilities == capabilities {
+            return;
+        }
+        self.capabilities = capabilities;
+        self.accounts = None;
+        self.renaming = None;
+        cx.notify();
+    }
+
+    fn has_oath(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_some_and(|caps| caps.has(PicoApplet::Oath))
+    }
+
+    fn has_expired_code(&self) -> bool {
+        let now = unix_time();
+        self.accounts.iter().flatten().any(|account| {
+            account
+                .code
+                .as_ref()
+                .and_then(|code| code.valid_to)
+                .is_some_and(|valid_to| valid_to <= now)
+        })
+    }
+
+    fn read_input(input: &Entity<InputState>, cx: &App) -> Option<String> {
+        let text = input.read(cx).text().to_string();
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn password(&self, cx: &App) -> Option<String> {
+        let text = self.password_input.read(cx).text().to_string();
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn load_accounts(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let password = self.password(cx);
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::oath_accounts(password) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(accounts) => this.accounts = Some(accounts),
+                    Err(e) => {
+                        this.accounts = None;
+                        cx.emit(OathEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Runs a change on the applet in the background, then reads the accounts again.
+    fn run_change(
+        &mut self,
+        change: impl FnOnce(Option<String>) -> Result<String, String> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        if self.loading {
+            return;
+        }
+        let password = self.password(cx);
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { change(password) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                let succeeded = result.is_ok();
+                cx.emit(OathEvent::Notification(match result {
+                    Ok(msg) => msg,
+                    Err(e) => format!("Error: {}", e),
+                }));
+                if succeeded {
+                    this.renaming = None;
+                    this.load_accounts(cx);
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn calculate(&mut self, index: usize, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let Some(account) = self.accounts.as_ref().and_then(|a| a.get(index)).cloned() else {
+            return;
+        };
+        let password = self.password(cx);
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let name = account.name.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::oath_calculate(&account, password) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(code) => {
+                        if let Some(account) =
+                            this.accounts.iter_mut().flatten().find(|a| a.name == name)
+                        {
+                            account.code = Some(code);
+                        }
+                    }
+                    Err(e) => cx.emit(OathEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn start_rename(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(account) = self.accounts.as_ref().and_then(|a| a.get(index)) else {
+            return;
+        };
+        let issuer = account.issuer.clone().unwrap_or_default();
+        let name = account.account.clone();
+        self.rename_issuer_input
+            .update(cx, |input, cx| input.set_value(issuer, window, cx));
+        self.rename_account_input
+            .update(cx, |input, cx| input.set_value(name, window, cx));
+        self.renaming = Some(index);
+        cx.notify();
+    }
+
+    fn save_rename(&mut self, cx: &mut Context<Self>) {
+        let Some(account) = self
+            .renaming
+            .and_then(|index| self.accounts.as_ref()?.get(index))
+            .cloned()
+        else {
+            return;
+        };
+        let issuer = Self::read_input(&self.rename_issuer_input, cx);
+        let Some(new_account) = Self::read_input(&self.rename_account_input, cx) else {
+            cx.emit(OathEvent::Notification("Enter an account name".to_string()));
+            return;
+        };
+        self.run_change(
+            move |password| {
+                io::oath_rename_account(&account, issuer, new_account, password)
+                    .map_err(|e| e.to_string())
+            },
+            cx,
+        );
+    }
+
+    fn open_delete_dialog(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(account) = self.accounts.as_ref().and_then(|a| a.get(index)) else {
+            return;
+        };
+        let name = account.name.clone();
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view_handle = view_handle.clone();
+            let name = name.clone();
+
+            dialog
+                .confirm()
+                .title("Delete Account")
+                .child(format!(
+                    "Delete {}? Codes for it can no longer be generated unless you add the secret again.",
+                    name
+                ))
+                .on_ok(move |_, _, cx| {
+                    let name = name.clone();
+                    let _ = view_handle.update(cx, |this, cx| {
+                        this.run_change(
+                            move |password| {
+                                io::oath_delete_account(name, password).map_err(|e| e.to_string())
+                            },
+                            cx,
+                        );
+                    });
+                    true
+                })
+                .on_cancel(|_, _, _| true)
+                .button_props(
+                    gpui_component::dialog::DialogButtonProps::default()
+                        .ok_text("Delete")
+                        .ok_variant(ButtonVariant::Danger),
+                )
+        });
+    }
+
+    fn add_from_uri(&mut self, cx: &mut Context<Self>) {
+        let Some(uri) = Self::read_input(&self.uri_input, cx) else {
+            cx.emit(OathEvent::Notification(
+                "Paste an otpauth:// URI".to_string(),
+            ));
+            return;
+        };
+        let credential = match io::oath_parse_uri(&uri) {
+            Ok(credential) => credential,
+            Err(e) => {
+                cx.emit(OathEvent::Notification(e.to_string()));
+                return;
+            }
+        };
+        self.add_account(credential, cx);
+    }
+
+    fn add_manual(&mut self, cx: &mut Context<Self>) {
+        let Some(account) = Self::read_input(&self.account_input, cx) else {
+            cx.emit(OathEvent::Notification("Enter an account name".to_string()));
+            return;
+        };
+        let secret = match Self::read_input(&self.secret_input, cx)
+            .ok_or_else(|| "Enter the secret key".to_string())
+            .and_then(|secret| uri::decode_secret(&secret).map_err(|e| e.to_string()))
+        {
+            Ok(secret) => secret,
+            Err(e) => {
+                cx.emit(OathEvent::Notification(e));
+                return;
+            }
+        };
+        let period = match Self::read_input(&self.period_input, cx)
+            .map(|p| p.parse::<u32>())
+            .transpose()
+        {
+            Ok(period) if period != Some(0) => period.unwrap_or(DEFAULT_PERIOD),
+            _ => {
+                cx.emit(OathEvent::Notification(
+                    "Period must be a positive number of seconds".to_string(),
+                ));
+                return;
+            }
+        };
+
+        self.add_account(
+            OathCredentialData {
+                issuer: Self::read_input(&self.issuer_input, cx),
+                account,
+                secret,
+                oath_type: self.add_type,
+                algorithm: self.add_algorithm,
+                digits: self.add_digits,
+                period,
+                counter: 0,
+                touch: self.add_touch,
+            },
+            cx,
+        );
+    }
+
+    fn add_account(&mut self, mut credential: OathCredentialData, cx: &mut Context<Self>) {
+        // Touch is not part of the URI format, so the checkbox applies to both ways of adding.
+        credential.touch |= self.add_touch;
+        self.run_change(
+            move |password| io::oath_add_account(credential, password).map_err(|e| e.to_string()),
+            cx,
+        );
+    }
+
+    fn change_password(&mut self, clear: bool, window: &mut Window, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let new_password = if clear {
+            None
+        } else {
+            let new = self.new_password_input.read(cx).text().to_string();
+            let confirm = self.confirm_password_input.read(cx).text().to_string();
+            if new.is_empty() {
+                cx.emit(OathEvent::Notification(
+                    "Enter the new password".to_string(),
+                ));
+                return;
+            }
+            if new != confirm {
+                cx.emit(OathEvent::Notification(
+                    "The passwords do not match".to_string(),
+                ));
+                return;
+            }
+            Some(new)
+        };
+
+        let password = self.password(cx);
+        self.loading = true;
+        cx.notify();
+
+        self._task = Some(cx.spawn_in(window, async move |this, cx| {
+            let next_password = new_password.clone().unwrap_or_default();
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    io::oath_set_password(password, new_password).map_err(|e| e.to_string())
+                })
+                .await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                this.loading = false;
+                match result {
+                    Ok(msg) => {
+                        // Later reads must authenticate with the password just set.
+                        for (input, value) in [
+                            (&this.password_input, next_password),
+                            (&this.new_password_input, String::new()),
+                            (&this.confirm_password_input, String::new()),
+                        ] {
+                            input.update(cx, |input, cx| input.set_value(value, window, cx));
+                        }
+                        cx.emit(OathEvent::Notification(msg));
+                        this.load_accounts(cx);
+                    }
+                    Err(e) => cx.emit(OathEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn render_account(
+        &self,
+        index: usize,
+        account: &OathAccount,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let loading = self.loading;
+        let now = unix_time();
+        let code = account
+            .code
+            .as_ref()
+            .filter(|code| code.valid_to.is_none_or(|valid_to| valid_to > now));
+
+        let calculate_listener = cx.listener(move |this, _, _, cx| {
+            this.calculate(index, cx);
+        });
+        let rename_listener = cx.listener(move |this, _, window, cx| {
+            this.start_rename(index, window, cx);
+        });
+        let delete_listener = cx.listener(move |this, _, window, cx| {
+            this.open_delete_dialog(index, window, cx);
+        });
+        let save_listener = cx.listener(|this, _, _, cx| {
+            this.save_rename(cx);
+        });
+        let cancel_listener = cx.listener(|this, _, _, cx| {
+            this.renaming = None;
+            cx.notify();
+        });
+
+        let theme = cx.theme();
+        let title = match &account.issuer {
+            Some(issuer) => issuer.clone(),
+            None => account.account.clone(),
+        };
+        let mut details = Vec::new();
+        if account.issuer.is_some() {
+            details.push(account.account.clone());
+        }
+        details.push(account.oath_type.name().to_string());
+        if account.algorithm != OathAlgorithm::Sha1 {
+            details.push(account.algorithm.name().to_string());
+        }
+        if account.oath_type == OathType::Totp && account.period != DEFAULT_PERIOD {
+            details.push(format!("{}s", account.period));
+        }
+
+        let code_text = code.map(|code| code.code.clone());
+        let remaining = code
+            .and_then(|code| code.valid_to)
+            .map(|valid_to| valid_to.saturating_sub(now));
+
+        v_flex()
+            .gap_3()
+            .p_4()
+            .border_1()
+            .border_color(theme.border)
+            .rounded_lg()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .gap_4()
+                    .child(
+                        v_flex()
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .items_center()
+                                    .child(div().font_medium().child(title))
+                                    .when(account.touch_required, |this| {
+                                        this.child(
+                                            Badge::new().child("Touch").color(theme.secondary),
+                                        )
+                                    }),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(theme.muted_foreground)
+                                    .child(details.join(" · ")),
+                            ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_3()
+                            .items_center()
+                            .children(code_text.clone().map(|code| {
+                                div()
+                                    .font_family("monospace")
+                                    .text_xl()
+                                    .font_semibold()
+                                    .child(code)
+                            }))
+                            .children(remaining.map(|seconds| {
+                                div()
+                                    .text_xs()
+                                    .text_color(theme.muted_foreground)
+                                    .child(format!("{}s", seconds))
+                            })),
+                    ),
+            )
+            .child(if self.renaming == Some(index) {
+                h_flex()
+                    .gap_2()
+                    .child(div().flex_1().child(Input::new(&self.rename_issuer_input)))
+                    .child(div().flex_1().child(Input::new(&self.rename_account_input)))
+                    .child(
+                        PFButton::new("Save")
+                            .id(SharedString::from(format!("oath-rename-save-{}", index)))
+                            .small()
+                            .disabled(loading)
+                            .on_click(save_listener),
+                    )
+                    .child(
+                        PFButton::new("Cancel")
+                            .id(SharedString::from(format!("oath-rename-cancel-{}", index)))
+                            .small()
+                            .on_click(cancel_listener),
+                    )
+            } else {
+                h_flex()
+                    .gap_2()
+                    .child(
+                        PFButton::new(if account.oath_type == OathType::Hotp {
+                            "Next Code"
+                        } else {
+                            "Calculate"
+                        })
+                        .id(SharedString::from(format!("oath-calc-{}", index)))
+                        .small()
+                        .disabled(loading)
+                        .on_click(calculate_listener),
+                    )
+                    .children(code_text.map(|code| {
+                        PFButton::new("Copy")
+                            .id(SharedString::from(format!("oath-copy-{}", index)))
+                            .small()
+                            .on_click(move |_, _, cx| {
+                                cx.write_to_clipboard(ClipboardItem::new_string(code.clone()));
+                            })
+                    }))
+                    .child(
+                        PFButton::new("Rename")
+                            .id(SharedString::from(format!("oath-rename-{}", index)))
+                            .small()
+                            .disabled(loading)
+                            .on_click(rename_listener),
+                    )
+                    .child(
+                        PFButton::new("Delete")
+                            .id(SharedString::from(format!("oath-delete-{}", index)))
+                            .small()
+                            .disabled(loading)
+                            .on_click(delete_listener),
+                    )
+            })
+    }
+
+    fn render_accounts(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let load_listener = cx.listener(|this, _, _, cx| {
+            this.load_accounts(cx);
+        });
+
+        let rows = self.accounts.as_ref().map(|accounts| {
+            if accounts.is_empty() {
+                return div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("No OATH accounts on this key.")
+                    .into_any_element();
+            }
+            let mut list = v_flex().gap_3();
+            for (i, account) in accounts.iter().enumerate() {
+                list = list.child(self.render_account(i, account, cx));
+            }
+            list.into_any_element()
+        });
+
+        let theme = cx.theme();
+
+        Card::new()
+            .title("Accounts")
+            .icon(Icon::default().path("icons/clock.svg"))
+            .description(
+                "TOTP codes refresh on their own; HOTP and touch accounts calculate on demand",
+            )
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(div().flex_1().child(Input::new(&self.password_input)))
+                            .child(
+                                PFButton::new(if self.accounts.is_some() {
+                                    "Refresh"
+                                } else {
+                                    "Load Accounts"
+                                })
+                                .id("oath-load-btn")
+                                .disabled(loading)
+                                .on_click(load_listener),
+                            ),
+                    )
+                    .children(rows)
+                    .when(loading, |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child("Touch your key if it blinks."),
+                        )
+                    }),
+            )
+    }
+
+    fn render_add(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let type_index = OATH_TYPES.iter().position(|t| *t == self.add_type);
+        let algorithm_index = OATH_ALGORITHMS
+            .iter()
+            .position(|a| *a == self.add_algorithm);
+        let digits_index = OATH_DIGITS.iter().position(|d| *d == self.add_digits);
+
+        let uri_listener = cx.listener(|this, _, _, cx| {
+            this.add_from_uri(cx);
+        });
+        let manual_listener = cx.listener(|this, _, _, cx| {
+            this.add_manual(cx);
+        });
+        let type_listener = cx.listener(|this, index: &usize, _, cx| {
+            if let Some(oath_type) = OATH_TYPES.get(*index) {
+                this.add_type = *oath_type;
+            }
+            cx.notify();
+        });
+        let algorithm_listener = cx.listener(|this, index: &usize, _, cx| {
+            if let Some(algorithm) = OATH_ALGORITHMS.get(*index) {
+                this.add_algorithm = *algorithm;
+            }
+            cx.notify();
+        });
+        let digits_listener = cx.listener(|this, index: &usize, _, cx| {
+            if let Some(digits) = OATH_DIGITS.get(*index) {
+                this.add_digits = *digits;
+            }
+            cx.notify();
+        });
+        let touch_listener = cx.listener(|this, checked: &bool, _, cx| {
+            this.add_touch = *checked;
+            cx.notify();
+        });
+
+        let theme = cx.theme();
+
+        Card::new()
+            .title("Add Account")
+            .icon(Icon::default().path("icons/plus.svg"))
+            .description("From an otpauth:// URI (the text of a QR code) or a secret key")
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(div().font_medium().child("otpauth:// URI"))
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(div().flex_1().child(Input::new(&self.uri_input)))
+                                    .child(
+                                        PFButton::new("Add")
+                                            .id("oath-add-uri-btn")
+                                            .disabled(loading)
+                                            .on_click(uri_listener),
+                                    ),
+                            ),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(div().font_medium().child("Manual Entry"))
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(div().flex_1().child(Input::new(&self.issuer_input)))
+                                    .child(div().flex_1().child(Input::new(&self.account_input))),
+                            )
+                            .child(Input::new(&self.secret_input))
+                            .child(
+                                RadioGroup::horizontal("oath-type")
+                                    .child(Radio::new("oath-totp").label("TOTP (time based)"))
+                                    .child(Radio::new("oath-hotp").label("HOTP (counter based)"))
+                                    .selected_index(type_index)
+                                    .on_click(type_listener),
+                            )
+                            .child(
+                                RadioGroup::horizontal("oath-algorithm")
+                                    .child(Radio::new("oath-sha1").label("SHA1"))
+                                    .child(Radio::new("oath-sha256").label("SHA256"))
+                                    .child(Radio::new("oath-sha512").label("SHA512"))
+                                    .selected_index(algorithm_index)
+                                    .on_click(algorithm_listener),
+                            )
+                            .child(
+                                RadioGroup::horizontal("oath-digits")
+                                    .child(Radio::new("oath-digits-6").label("6 digits"))
+                                    .child(Radio::new("oath-digits-8").label("8 digits"))
+                                    .selected_index(digits_index)
+                                    .on_click(digits_listener),
+                            )
+                            .when(self.add_type == OathType::Totp, |this| {
+                                this.child(Input::new(&self.period_input))
+                            })
+                            .child(
+                                h_flex().justify_end().child(
+                                    PFButton::new("Add")
+                                        .id("oath-add-manual-btn")
+                                        .disabled(loading)
+                                        .on_click(manual_listener),
+                                ),
+                            ),
+                    )
+                    .child(
+                        Checkbox::new("oath-touch")
+                            .label("Require touch to calculate codes")
+                            .checked(self.add_touch)
+                            .on_click(touch_listener),
+                    ),
+            )
+    }
+
+    fn render_password(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let set_listener = cx.listener(|this, _, window, cx| {
+            this.change_password(false, window, cx);
+        });
+        let clear_listener = cx.listener(|this, _, window, cx| {
+            this.change_password(true, window, cx);
+        });
+
+        let theme = cx.theme();
+
+        Card::new()
+            .title("Access Password")
+            .icon(Icon::default().path("icons/lock.svg"))
+            .description("Protects the accounts from being listed or used without the password")
+            .child(
+                v_flex()
+                    .gap_3()
+                    .child(Input::new(&self.new_password_input))
+                    .child(Input::new(&self.confirm_password_input))
+                    .child(div().text_sm().text_color(theme.muted_foreground).child(
+                        "Enter the current password above the account list first if one is set.",
+                    ))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .justify_end()
+                            .child(
+                                PFButton::new("Remove Password")
+                                    .id("oath-clear-password-btn")
+                                    .disabled(loading)
+                                    .on_click(clear_listener),
+                            )
+                            .child(
+                                PFButton::new("Set Password")
+                                    .id("oath-set-password-btn")
+                                    .disabled(loading)
+                                    .on_click(set_listener),
+                            ),
+                    ),
+            )
+    }
+}
+
+impl Render for OathView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = v_flex()
+            .gap_6()
+            .w_full()
+            .when(self.has_oath(), |this| {
+                this.child(self.render_accounts(cx))
+                    .child(self.render_add(cx))
+                    .child(self.render_password(cx))
+            })
+            .when(!self.has_oath(), |this| {
+                this.child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("Connect a key with the OATH application to manage TOTP and HOTP accounts."),
+                )
+            });
+
+        PageView::build(
+            "Authenticator",
+            "TOTP and HOTP accounts stored on the key.",
+            content,
+            cx.theme(),
+        )
+    }
+}
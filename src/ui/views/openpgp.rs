@@ -0,0 +1,467 @@
+use crate::device::io;
+use crate::device::types::{
+    DeviceCapabilities, OpenPgpCardStatus, OpenPgpKeyInfo, OpenPgpPin, PicoApplet,
+};
+use crate::ui::components::{button::PFButton, card::Card, page_view::PageView};
+use gpui::prelude::*;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme, Icon, StyledExt, Theme,
+    badge::Badge,
+    h_flex,
+    input::{Input, InputState},
+    radio::{Radio, RadioGroup},
+    v_flex,
+};
+use time::OffsetDateTime;
+
+const PINS: [OpenPgpPin; 2] = [OpenPgpPin::User, OpenPgpPin::Admin];
+
+pub struct OpenPgpView {
+    capabilities: Option<DeviceCapabilities>,
+    status: Option<OpenPgpCardStatus>,
+    pin: OpenPgpPin,
+    current_pin_input: Entity<InputState>,
+    new_pin_input: Entity<InputState>,
+    confirm_pin_input: Entity<InputState>,
+    loading: bool,
+
+    _task: Option<Task<()>>,
+}
+
+pub enum OpenPgpEvent {
+    Notification(String),
+}
+
+impl EventEmitter<OpenPgpEvent> for OpenPgpView {}
+
+impl OpenPgpView {
+    pub fn new(
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        capabilities: Option<DeviceCapabilities>,
+    ) -> Self {
+        let current_pin_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Current PIN")
+                .masked(true)
+        });
+        let new_pin_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("New PIN")
+                .masked(true)
+        });
+        let confirm_pin_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Confirm new PIN")
+                .masked(true)
+        });
+
+        Self {
+            capabilities,
+            status: None,
+            pin: OpenPgpPin::User,
+            current_pin_input,
+            new_pin_input,
+            confirm_pin_input,
+            loading: false,
+            _task: None,
+        }
+    }
+
+    pub fn update_capabilities(
+        &mut self,
+        capabilities: Option<DeviceCapabilities>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.capabilities == capabilities {
+            return;
+        }
+        self.capabilities = capabilities;
+        self.status = None;
+        cx.notify();
+    }
+
+    fn has_openpgp(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_some_and(|caps| caps.has(PicoApplet::OpenPgp))
+    }
+
+    fn load_status(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::openpgp_status() })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(status) => this.status = Some(status),
+                    Err(e) => cx.emit(OpenPgpEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn change_pin(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let current = self.current_pin_input.read(cx).text().to_string();
+        let new = self.new_pin_input.read(cx).text().to_string();
+        let confirm = self.confirm_pin_input.read(cx).text().to_string();
+
+        if current.is_empty() || new.is_empty() {
+            cx.emit(OpenPgpEvent::Notification(
+                "Enter the current and the new PIN".to_string(),
+            ));
+            return;
+        }
+        if new != confirm {
+            cx.emit(OpenPgpEvent::Notification(
+                "The new PINs do not match".to_string(),
+            ));
+            return;
+        }
+
+        for input in [
+            &self.current_pin_input,
+            &self.new_pin_input,
+            &self.confirm_pin_input,
+        ] {
+            input.update(cx, |input, cx| input.set_value("", window, cx));
+        }
+
+        let pin = self.pin;
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::openpgp_change_pin(pin, current, new) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                cx.emit(OpenPgpEvent::Notification(match result {
+                    Ok(msg) => msg,
+                    Err(e) => format!("Error: {}", e),
+                }));
+                // The retry counters changed either way
+                this.load_status(cx);
+            });
+        }));
+    }
+
+    fn render_kv(label: &str, value: impl IntoElement, theme: &Theme) -> impl IntoElement {
+        h_flex()
+            .justify_between()
+            .gap_4()
+            .text_sm()
+            .child(
+                div()
+                    .text_color(theme.muted_foreground)
+                    .child(label.to_string()),
+            )
+            .child(
+                div()
+                    .font_weight(FontWeight::MEDIUM)
+                    .text_color(theme.foreground)
+                    .child(value),
+            )
+    }
+
+    /// Groups a fingerprint in blocks of four, as gpg prints it.
+    fn format_fingerprint(fingerprint: &str) -> String {
+        fingerprint
+            .as_bytes()
+            .chunks(4)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn format_date(timestamp: u32) -> String {
+        OffsetDateTime::from_unix_timestamp(timestamp as i64)
+            .map(|t| t.date().to_string())
+            .unwrap_or_else(|_| timestamp.to_string())
+    }
+
+    fn render_card_info(&self, status: &OpenPgpCardStatus, theme: &Theme) -> impl IntoElement {
+        let not_set = || "Not set".to_string();
+
+        v_flex()
+            .gap_3()
+            .child(Self::render_kv(
+                "Specification",
+                format!("OpenPGP card {}", status.version),
+                theme,
+            ))
+            .child(Self::render_kv(
+                "Serial",
+                div().font_family("Mono").child(status.serial.clone()),
+                theme,
+            ))
+            .child(Self::render_kv(
+                "Manufacturer",
+                format!("{:04X}", status.manufacturer),
+                theme,
+            ))
+            .child(Self::render_kv(
+                "Cardholder",
+                status.cardholder_name.clone().unwrap_or_else(not_set),
+                theme,
+            ))
+            .child(Self::render_kv(
+                "Language",
+                status.language.clone().unwrap_or_else(not_set),
+                theme,
+            ))
+            .child(Self::render_kv(
+                "Public key URL",
+                status.url.clone().unwrap_or_else(not_set),
+                theme,
+            ))
+            .child(Self::render_kv(
+                "Signatures made",
+                status.signature_counter.to_string(),
+                theme,
+            ))
+            .child(
+                div()
+                    .text_xs()
+                    .font_family("Mono")
+                    .text_color(theme.muted_foreground)
+                    .child(status.aid.clone()),
+            )
+    }
+
+    fn render_key(key: &OpenPgpKeyInfo, theme: &Theme) -> impl IntoElement {
+        v_flex()
+            .gap_2()
+            .p_4()
+            .border_1()
+            .border_color(theme.border)
+            .rounded_lg()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(div().font_medium().child(key.slot.name()))
+                    .child(
+                        Badge::new()
+                            .child(if key.fingerprint.is_some() {
+                                "Key present"
+                            } else {
+                                "Empty"
+                            })
+                            .color(if key.fingerprint.is_some() {
+                                theme.primary
+                            } else {
+                                theme.secondary
+                            }),
+                    ),
+            )
+            .child(Self::render_kv("Algorithm", key.algorithm.clone(), theme))
+            .children(
+                key.created
+                    .map(|created| Self::render_kv("Created", Self::format_date(created), theme)),
+            )
+            .children(key.fingerprint.as_ref().map(|fingerprint| {
+                div()
+                    .text_xs()
+                    .font_family("Mono")
+                    .text_color(theme.muted_foreground)
+                    .child(Self::format_fingerprint(fingerprint))
+            }))
+    }
+
+    fn render_status(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let load_listener = cx.listener(|this, _, _, cx| {
+            this.load_status(cx);
+        });
+        let theme = cx.theme();
+
+        let card = Card::new()
+            .title("Card")
+            .icon(Icon::default().path("icons/lock.svg"))
+            .description("Application Related Data of the OpenPGP applet")
+            .header_right(
+                PFButton::new(if self.status.is_some() {
+                    "Refresh"
+                } else {
+                    "Read Card"
+                })
+                .id("openpgp-load-btn")
+                .small()
+                .disabled(loading)
+                .on_click(load_listener),
+            );
+
+        match &self.status {
+            Some(status) => card.child(self.render_card_info(status, theme)),
+            None => card.child(
+                div()
+                    .text_sm()
+                    .text_color(theme.muted_foreground)
+                    .child("Read the card to see its keys and PIN counters."),
+            ),
+        }
+    }
+
+    fn render_keys(&self, status: &OpenPgpCardStatus, theme: &Theme) -> impl IntoElement {
+        let mut list = v_flex().gap_3();
+        for key in &status.keys {
+            list = list.child(Self::render_key(key, theme));
+        }
+
+        Card::new()
+            .title("Keys")
+            .icon(Icon::default().path("icons/key-round.svg"))
+            .description("Manage the keys themselves with gpg --card-edit")
+            .child(list)
+    }
+
+    fn render_pins(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let pin_index = PINS.iter().position(|p| *p == self.pin);
+
+        let pin_listener = cx.listener(|this, index: &usize, _, cx| {
+            if let Some(pin) = PINS.get(*index) {
+                this.pin = *pin;
+            }
+            cx.notify();
+        });
+        let change_listener = cx.listener(|this, _, window, cx| {
+            this.change_pin(window, cx);
+        });
+
+        let theme = cx.theme();
+
+        let retries = self.status.as_ref().map(|status| {
+            let pins = &status.pins;
+            v_flex()
+                .gap_3()
+                .child(Self::render_kv(
+                    "User PIN attempts left",
+                    pins.user_retries.to_string(),
+                    theme,
+                ))
+                .child(Self::render_kv(
+                    "Reset code attempts left",
+                    pins.reset_code_retries.to_string(),
+                    theme,
+                ))
+                .child(Self::render_kv(
+                    "Admin PIN attempts left",
+                    pins.admin_retries.to_string(),
+                    theme,
+                ))
+                .child(Self::render_kv(
+                    "User PIN per signature",
+                    if pins.user_pin_multiple_signatures {
+                        "No, once per session"
+                    } else {
+                        "Yes"
+                    },
+                    theme,
+                ))
+        });
+
+        Card::new()
+            .title("PINs")
+            .icon(Icon::default().path("icons/shield-check.svg"))
+            .description("The user PIN unlocks the keys, the admin PIN changes the card")
+            .child(
+                v_flex()
+                    .gap_4()
+                    .children(retries)
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(div().font_medium().child("Change PIN"))
+                            .child(
+                                RadioGroup::horizontal("openpgp-pin")
+                                    .child(Radio::new("openpgp-pin-user").label("User PIN"))
+                                    .child(Radio::new("openpgp-pin-admin").label("Admin PIN"))
+                                    .selected_index(pin_index)
+                                    .on_click(pin_listener),
+                            )
+                            .child(Input::new(&self.current_pin_input))
+                            .child(Input::new(&self.new_pin_input))
+                            .child(Input::new(&self.confirm_pin_input))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(theme.muted_foreground)
+                                    .child(
+                                        "User PINs need at least 6 characters, admin PINs at least 8. The factory defaults are 123456 and 12345678.",
+                                    ),
+                            )
+                            .child(
+                                h_flex().justify_end().child(
+                                    PFButton::new("Change PIN")
+                                        .id("openpgp-change-pin-btn")
+                                        .disabled(loading)
+                                        .on_click(change_listener),
+                                ),
+                            ),
+                    ),
+            )
+    }
+}
+
+impl Render for OpenPgpView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = if self.has_openpgp() {
+            let status_card = self.render_status(cx).into_any_element();
+            let pins_card = self.render_pins(cx).into_any_element();
+            let keys_card = self
+                .status
+                .as_ref()
+                .map(|status| self.render_keys(status, cx.theme()).into_any_element());
+
+            let is_wide = window.bounds().size.width > px(1100.0);
+            v_flex().gap_6().w_full().child(
+                div()
+                    .grid()
+                    .grid_cols(if is_wide { 2 } else { 1 })
+                    .gap_6()
+                    .child(status_card)
+                    .child(pins_card)
+                    .children(keys_card),
+            )
+        } else {
+            v_flex().w_full().child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("Connect a key with the OpenPGP application to see its card status."),
+            )
+        };
+
+        PageView::build(
+            "OpenPGP",
+            "Inspect the OpenPGP card and change its PINs.",
+            content,
+            cx.theme(),
+        )
+    }
+}
@@ -0,0 +1,726 @@
+use crate::device::io;
+use crate::device::otp::{self, OtpSlot, OtpSlotConfig};
+use crate::device::types::{DeviceCapabilities, OtpSlotStatus, OtpStatus, PicoApplet};
+use crate::ui::components::{button::PFButton, card::Card, page_view::PageView};
+use gpui::prelude::*;
+use gpui::*;
+use gpui_component::button::ButtonVariant;
+use gpui_component::{
+    ActiveTheme, Icon, StyledExt, Theme, WindowExt,
+    badge::Badge,
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputState},
+    radio::{Radio, RadioGroup},
+    v_flex,
+};
+use rand::RngExt;
+
+const OTP_SLOTS: [OtpSlot; 2] = [OtpSlot::One, OtpSlot::Two];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OtpMode {
+    YubicoOtp,
+    StaticPassword,
+    ChallengeResponse,
+}
+
+const OTP_MODES: [OtpMode; 3] = [
+    OtpMode::YubicoOtp,
+    OtpMode::StaticPassword,
+    OtpMode::ChallengeResponse,
+];
+
+pub struct OtpView {
+    capabilities: Option<DeviceCapabilities>,
+    status: Option<OtpStatus>,
+    access_code_input: Entity<InputState>,
+    program_slot: OtpSlot,
+    mode: OtpMode,
+    public_id_input: Entity<InputState>,
+    private_id_input: Entity<InputState>,
+    aes_key_input: Entity<InputState>,
+    password_input: Entity<InputState>,
+    hmac_key_input: Entity<InputState>,
+    require_touch: bool,
+    test_slot: OtpSlot,
+    challenge_input: Entity<InputState>,
+    response: Option<String>,
+    loading: bool,
+
+    _task: Option<Task<()>>,
+}
+
+pub enum OtpEvent {
+    Notification(String),
+}
+
+impl EventEmitter<OtpEvent> for OtpView {}
+
+impl OtpView {
+    pub fn new(
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        capabilities: Option<DeviceCapabilities>,
+    ) -> Self {
+        let access_code_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Access code (12 hex characters, only if the slot has one)")
+                .masked(true)
+        });
+        let public_id_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Public ID (modhex)"));
+        let private_id_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Private ID (12 hex characters)"));
+        let aes_key_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("AES key (32 hex characters)")
+                .masked(true)
+        });
+        let password_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Password (US keyboard layout)")
+                .masked(true)
+        });
+        let hmac_key_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Secret key (up to 40 hex characters)")
+                .masked(true)
+        });
+        let challenge_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Challenge (hex, up to 64 bytes)"));
+
+        Self {
+            capabilities,
+            status: None,
+            access_code_input,
+            program_slot: OtpSlot::Two,
+            mode: OtpMode::ChallengeResponse,
+            public_id_input,
+            private_id_input,
+            aes_key_input,
+            password_input,
+            hmac_key_input,
+            require_touch: false,
+            test_slot: OtpSlot::Two,
+            challenge_input,
+            response: None,
+            loading: false,
+            _task: None,
+        }
+    }
+
+    pub fn update_capabilities(
+        &mut self,
+        capabilities: Option<DeviceCapabilities>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.capabilities == capabilities {
+            return;
+        }
+        self.capabilities = capabilities;
+        self.status = None;
+        self.response = None;
+        cx.notify();
+    }
+
+    fn has_otp(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_some_and(|caps| caps.has(PicoApplet::Otp))
+    }
+
+    fn slot_status(&self, slot: OtpSlot) -> Option<OtpSlotStatus> {
+        self.status.as_ref().map(|status| match slot {
+            OtpSlot::One => status.slot1,
+            OtpSlot::Two => status.slot2,
+        })
+    }
+
+    fn read_input(input: &Entity<InputState>, cx: &App) -> Option<String> {
+        let text = input.read(cx).text().to_string();
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn access_code(&self, cx: &mut Context<Self>) -> Option<Option<[u8; 6]>> {
+        let input = self.access_code_input.read(cx).text().to_string();
+        match io::parse_otp_access_code(&input) {
+            Ok(code) => Some(code),
+            Err(e) => {
+                cx.emit(OtpEvent::Notification(e.to_string()));
+                None
+            }
+        }
+    }
+
+    fn load_status(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::otp_status() })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(status) => this.status = Some(status),
+                    Err(e) => cx.emit(OtpEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Runs a slot change in the background, then reads the status again.
+    fn run_change(
+        &mut self,
+        change: impl FnOnce() -> Result<String, String> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { change() })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                cx.emit(OtpEvent::Notification(match result {
+                    Ok(msg) => msg,
+                    Err(e) => format!("Error: {}", e),
+                }));
+                this.load_status(cx);
+            });
+        }));
+    }
+
+    fn generate_yubico_otp(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (public_id, private_id, key) = otp::generate_yubico_otp();
+        self.public_id_input.update(cx, |input, cx| {
+            input.set_value(otp::modhex_encode(&public_id), window, cx)
+        });
+        self.private_id_input.update(cx, |input, cx| {
+            input.set_value(hex::encode(private_id), window, cx)
+        });
+        self.aes_key_input.update(cx, |input, cx| {
+            input.set_value(hex::encode(key), window, cx)
+        });
+        cx.emit(OtpEvent::Notification(
+            "Generated a new Yubico OTP credential. Upload it to your validation server before use."
+                .to_string(),
+        ));
+    }
+
+    fn generate_hmac_key(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let mut key = [0u8; 20];
+        rand::rng().fill(&mut key);
+        self.hmac_key_input.update(cx, |input, cx| {
+            input.set_value(hex::encode(key), window, cx)
+        });
+    }
+
+    fn read_slot_config(&self, cx: &App) -> Result<OtpSlotConfig, String> {
+        let hex_field = |input: &Entity<InputState>, name: &str| {
+            Self::read_input(input, cx)
+                .ok_or_else(|| format!("Enter the {}", name))
+                .and_then(|text| hex::decode(text).map_err(|_| format!("The {} must be hex", name)))
+        };
+
+        match self.mode {
+            OtpMode::YubicoOtp => {
+                let public_id = Self::read_input(&self.public_id_input, cx)
+                    .map(|id| otp::modhex_decode(&id).map_err(|e| e.to_string()))
+                    .transpose()?
+                    .unwrap_or_default();
+                let private_id = hex_field(&self.private_id_input, "private ID")?
+                    .try_into()
+                    .map_err(|_| "The private ID must be 6 bytes".to_string())?;
+                let key = hex_field(&self.aes_key_input, "AES key")?
+                    .try_into()
+                    .map_err(|_| "The AES key must be 16 bytes".to_string())?;
+                Ok(OtpSlotConfig::YubicoOtp {
+                    public_id,
+                    private_id,
+                    key,
+                })
+            }
+            OtpMode::StaticPassword => {
+                let password = self.password_input.read(cx).text().to_string();
+                if password.is_empty() {
+                    return Err("Enter the password".to_string());
+                }
+                Ok(OtpSlotConfig::StaticPassword { password })
+            }
+            OtpMode::ChallengeResponse => Ok(OtpSlotConfig::ChallengeResponse {
+                key: hex_field(&self.hmac_key_input, "secret key")?,
+                require_touch: self.require_touch,
+            }),
+        }
+    }
+
+    fn program(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let config = match self.read_slot_config(cx) {
+            Ok(config) => config,
+            Err(e) => {
+                cx.emit(OtpEvent::Notification(e));
+                return;
+            }
+        };
+        let Some(access_code) = self.access_code(cx) else {
+            return;
+        };
+        let slot = self.program_slot;
+
+        let run = move |this: &mut Self, cx: &mut Context<Self>| {
+            let config = config.clone();
+            this.run_change(
+                move || io::otp_program_slot(slot, config, access_code).map_err(|e| e.to_string()),
+                cx,
+            );
+        };
+
+        if !self.slot_status(slot).is_some_and(|s| s.configured) {
+            run(self, cx);
+            return;
+        }
+        let view_handle = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view_handle = view_handle.clone();
+            let run = run.clone();
+
+            dialog
+                .confirm()
+                .title("Overwrite Slot")
+                .child(format!(
+                    "{} is already programmed. Its current credential is lost when it is overwritten.",
+                    slot.label()
+                ))
+                .on_ok(move |_, _, cx| {
+                    let _ = view_handle.update(cx, |this, cx| run(this, cx));
+                    true
+                })
+                .on_cancel(|_, _, _| true)
+                .button_props(
+                    gpui_component::dialog::DialogButtonProps::default()
+                        .ok_text("Overwrite")
+                        .ok_variant(ButtonVariant::Danger),
+                )
+        });
+    }
+
+    fn open_delete_dialog(&mut self, slot: OtpSlot, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(access_code) = self.access_code(cx) else {
+            return;
+        };
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view_handle = view_handle.clone();
+
+            dialog
+                .confirm()
+                .title("Delete Slot")
+                .child(format!(
+                    "Delete the configuration in {}? This cannot be undone.",
+                    slot.label()
+                ))
+                .on_ok(move |_, _, cx| {
+                    let _ = view_handle.update(cx, |this, cx| {
+                        this.run_change(
+                            move || {
+                                io::otp_delete_slot(slot, access_code).map_err(|e| e.to_string())
+                            },
+                            cx,
+                        );
+                    });
+                    true
+                })
+                .on_cancel(|_, _, _| true)
+                .button_props(
+                    gpui_component::dialog::DialogButtonProps::default()
+                        .ok_text("Delete")
+                        .ok_variant(ButtonVariant::Danger),
+                )
+        });
+    }
+
+    fn test_challenge(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let challenge = match Self::read_input(&self.challenge_input, cx)
+            .ok_or_else(|| "Enter a challenge".to_string())
+            .and_then(|text| hex::decode(text).map_err(|_| "The challenge must be hex".to_string()))
+        {
+            Ok(challenge) => challenge,
+            Err(e) => {
+                cx.emit(OtpEvent::Notification(e));
+                return;
+            }
+        };
+        let slot = self.test_slot;
+
+        self.loading = true;
+        self.response = None;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::otp_challenge_response(slot, challenge) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(response) => this.response = Some(response),
+                    Err(e) => cx.emit(OtpEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn render_slot_row(
+        &self,
+        slot: OtpSlot,
+        theme: &Theme,
+        delete_listener: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+    ) -> impl IntoElement {
+        let status = self.slot_status(slot);
+        let configured = status.is_some_and(|s| s.configured);
+        let kind = match status {
+            Some(s) if s.configured && s.touch_triggered => "Keyboard output on touch",
+            Some(s) if s.configured => "Challenge-response",
+            _ => "Empty",
+        };
+
+        h_flex()
+            .justify_between()
+            .items_center()
+            .gap_4()
+            .child(
+                v_flex()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(div().font_medium().child(slot.label()))
+                            .child(
+                                Badge::new()
+                                    .child(if configured { "Configured" } else { "Empty" })
+                                    .color(if configured {
+                                        theme.primary
+                                    } else {
+                                        theme.secondary
+                                    }),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.muted_foreground)
+                            .child(kind),
+                    ),
+            )
+            .child(
+                PFButton::new("Delete")
+                    .id(SharedString::from(format!("otp-delete-{:?}", slot)))
+                    .small()
+                    .disabled(!configured || self.loading)
+                    .on_click(delete_listener),
+            )
+    }
+
+    fn render_slots(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let load_listener = cx.listener(|this, _, _, cx| {
+            this.load_status(cx);
+        });
+        let swap_listener = cx.listener(|this, _, _, cx| {
+            this.run_change(|| io::otp_swap_slots().map_err(|e| e.to_string()), cx);
+        });
+        let delete_1 = cx.listener(|this, _, window, cx| {
+            this.open_delete_dialog(OtpSlot::One, window, cx);
+        });
+        let delete_2 = cx.listener(|this, _, window, cx| {
+            this.open_delete_dialog(OtpSlot::Two, window, cx);
+        });
+
+        let theme = cx.theme();
+
+        Card::new()
+            .title("Slots")
+            .icon(Icon::default().path("icons/key.svg"))
+            .description(match &self.status {
+                Some(status) => format!("OTP application {}", status.firmware_version),
+                None => "Read the slots to see how they are programmed".to_string(),
+            })
+            .header_right(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        PFButton::new("Swap Slots")
+                            .id("otp-swap-btn")
+                            .small()
+                            .disabled(self.status.is_none() || loading)
+                            .on_click(swap_listener),
+                    )
+                    .child(
+                        PFButton::new(if self.status.is_some() {
+                            "Refresh"
+                        } else {
+                            "Read Slots"
+                        })
+                        .id("otp-load-btn")
+                        .small()
+                        .disabled(loading)
+                        .on_click(load_listener),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .gap_4()
+                    .when(self.status.is_some(), |this| {
+                        this.child(self.render_slot_row(OtpSlot::One, theme, delete_1))
+                            .child(self.render_slot_row(OtpSlot::Two, theme, delete_2))
+                    })
+                    .child(Input::new(&self.access_code_input)),
+            )
+    }
+
+    fn render_program(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let slot_index = OTP_SLOTS.iter().position(|s| *s == self.program_slot);
+        let mode_index = OTP_MODES.iter().position(|m| *m == self.mode);
+
+        let slot_listener = cx.listener(|this, index: &usize, _, cx| {
+            if let Some(slot) = OTP_SLOTS.get(*index) {
+                this.program_slot = *slot;
+            }
+            cx.notify();
+        });
+        let mode_listener = cx.listener(|this, index: &usize, _, cx| {
+            if let Some(mode) = OTP_MODES.get(*index) {
+                this.mode = *mode;
+            }
+            cx.notify();
+        });
+        let touch_listener = cx.listener(|this, checked: &bool, _, cx| {
+            this.require_touch = *checked;
+            cx.notify();
+        });
+        let generate_otp_listener = cx.listener(|this, _, window, cx| {
+            this.generate_yubico_otp(window, cx);
+        });
+        let generate_hmac_listener = cx.listener(|this, _, window, cx| {
+            this.generate_hmac_key(window, cx);
+        });
+        let program_listener = cx.listener(|this, _, window, cx| {
+            this.program(window, cx);
+        });
+
+        let theme = cx.theme();
+
+        let fields = match self.mode {
+            OtpMode::YubicoOtp => v_flex()
+                .gap_3()
+                .child(Input::new(&self.public_id_input))
+                .child(Input::new(&self.private_id_input))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(div().flex_1().child(Input::new(&self.aes_key_input)))
+                        .child(
+                            PFButton::new("Generate")
+                                .id("otp-generate-yubico-btn")
+                                .on_click(generate_otp_listener),
+                        ),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child("The OTPs only validate once the public ID, private ID and AES key are registered with your validation server."),
+                ),
+            OtpMode::StaticPassword => v_flex()
+                .gap_3()
+                .child(Input::new(&self.password_input))
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child("Up to 38 characters, typed as a US keyboard would. Hosts with another layout will see different characters."),
+                ),
+            OtpMode::ChallengeResponse => v_flex()
+                .gap_3()
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(div().flex_1().child(Input::new(&self.hmac_key_input)))
+                        .child(
+                            PFButton::new("Generate")
+                                .id("otp-generate-hmac-btn")
+                                .on_click(generate_hmac_listener),
+                        ),
+                )
+                .child(
+                    Checkbox::new("otp-require-touch")
+                        .label("Require touch for every response")
+                        .checked(self.require_touch)
+                        .on_click(touch_listener),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child("Keep a copy of the secret key if you want to program a backup key with it."),
+                ),
+        };
+
+        Card::new()
+            .title("Program Slot")
+            .icon(Icon::default().path("icons/settings-2.svg"))
+            .description("Yubico OTP, a static password or HMAC-SHA1 challenge-response")
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        RadioGroup::horizontal("otp-program-slot")
+                            .child(Radio::new("otp-program-slot-1").label(OtpSlot::One.label()))
+                            .child(Radio::new("otp-program-slot-2").label(OtpSlot::Two.label()))
+                            .selected_index(slot_index)
+                            .on_click(slot_listener),
+                    )
+                    .child(
+                        RadioGroup::horizontal("otp-mode")
+                            .child(Radio::new("otp-mode-yubico").label("Yubico OTP"))
+                            .child(Radio::new("otp-mode-static").label("Static Password"))
+                            .child(Radio::new("otp-mode-hmac").label("Challenge-Response"))
+                            .selected_index(mode_index)
+                            .on_click(mode_listener),
+                    )
+                    .child(
+                        div()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(fields),
+                    )
+                    .child(
+                        h_flex().justify_end().child(
+                            PFButton::new("Program")
+                                .id("otp-program-btn")
+                                .disabled(loading)
+                                .on_click(program_listener),
+                        ),
+                    ),
+            )
+    }
+
+    fn render_test(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let slot_index = OTP_SLOTS.iter().position(|s| *s == self.test_slot);
+
+        let slot_listener = cx.listener(|this, index: &usize, _, cx| {
+            if let Some(slot) = OTP_SLOTS.get(*index) {
+                this.test_slot = *slot;
+            }
+            cx.notify();
+        });
+        let send_listener = cx.listener(|this, _, _, cx| {
+            this.test_challenge(cx);
+        });
+
+        let theme = cx.theme();
+
+        Card::new()
+            .title("Test Challenge-Response")
+            .icon(Icon::default().path("icons/square-terminal.svg"))
+            .description("Send the HMAC-SHA1 challenge KeePassXC or ykchalresp would send")
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        RadioGroup::horizontal("otp-test-slot")
+                            .child(Radio::new("otp-test-slot-1").label(OtpSlot::One.label()))
+                            .child(Radio::new("otp-test-slot-2").label(OtpSlot::Two.label()))
+                            .selected_index(slot_index)
+                            .on_click(slot_listener),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(div().flex_1().child(Input::new(&self.challenge_input)))
+                            .child(
+                                PFButton::new("Send")
+                                    .id("otp-test-btn")
+                                    .disabled(loading)
+                                    .on_click(send_listener),
+                            ),
+                    )
+                    .children(self.response.clone().map(|response| {
+                        div()
+                            .p_3()
+                            .rounded_md()
+                            .bg(theme.muted)
+                            .font_family("monospace")
+                            .text_xs()
+                            .child(response)
+                    }))
+                    .when(loading, |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child("Touch your key if it blinks."),
+                        )
+                    }),
+            )
+    }
+}
+
+impl Render for OtpView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = v_flex()
+            .gap_6()
+            .w_full()
+            .when(self.has_otp(), |this| {
+                this.child(self.render_slots(cx))
+                    .child(self.render_program(cx))
+                    .child(self.render_test(cx))
+            })
+            .when(!self.has_otp(), |this| {
+                this.child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("Connect a key with the OTP application to program its slots."),
+                )
+            });
+
+        PageView::build(
+            "OTP",
+            "Program the two YubiKey-compatible OTP slots over the smart card (CCID) interface. The HID keyboard interface is not supported.",
+            content,
+            cx.theme(),
+        )
+    }
+}
@@ -1,20 +1,73 @@
+use crate::device::export::ExportFormat;
 use crate::device::io;
-use crate::device::types::{FidoDeviceInfo, FullDeviceStatus, StoredCredential};
+use crate::device::types::{
+    CredentialCapacity, FidoDeviceInfo, FullDeviceStatus, LargeBlobArray, PinRetries,
+    StoredCredential,
+};
 use crate::ui::components::{
     button::{PFButton, PFIconButton},
+    capacity_bar::CapacityBar,
     card::Card,
     page_view::PageView,
+    pin_warning::{PIN_RETRY_WARNING_THRESHOLD, PinRetryWarning},
 };
+use crate::ui::ui_types::{CredProtectFilter, PasskeySortOrder, ResetStage};
+use gpui::prelude::*;
 use gpui::*;
 use gpui_component::button::{Button, ButtonVariant, ButtonVariants};
 use gpui_component::{
-    ActiveTheme, Icon, Sizable, StyledExt, Theme, WindowExt,
+    ActiveTheme, Disableable, Icon, IconName, Sizable, StyledExt, Theme, WindowExt,
     badge::Badge,
+    checkbox::Checkbox,
     h_flex,
-    input::{Input, InputState},
+    input::{Input, InputEvent, InputState},
+    select::{Select, SelectEvent, SelectItem, SelectState},
     slider::{Slider, SliderState},
     v_flex,
 };
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const RESET_UNPLUG_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+const RESET_REPLUG_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Clone, PartialEq)]
+struct SortSelectOption {
+    order: PasskeySortOrder,
+    label: SharedString,
+}
+
+impl SelectItem for SortSelectOption {
+    type Value = PasskeySortOrder;
+
+    fn title(&self) -> SharedString {
+        self.label.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.order
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct CredProtectSelectOption {
+    filter: CredProtectFilter,
+    label: SharedString,
+}
+
+impl SelectItem for CredProtectSelectOption {
+    type Value = CredProtectFilter;
+
+    fn title(&self) -> SharedString {
+        self.label.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.filter
+    }
+}
 
 struct SliderLabel {
     slider: Entity<SliderState>,
@@ -31,35 +84,133 @@ pub struct PasskeysView {
     device_status: Option<FullDeviceStatus>,
     fido_info: Option<FidoDeviceInfo>,
     credentials: Vec<StoredCredential>,
+    capacity: Option<CredentialCapacity>,
+    large_blobs: Option<LargeBlobArray>,
     unlocked: bool,
     cached_pin: Option<String>,
+    selected_credential: Option<String>,
+    checked_credentials: HashSet<String>,
+    /// Per-item outcome of the last batch delete: (label, error)
+    batch_results: Vec<(String, Option<String>)>,
+    search_input: Entity<InputState>,
+    sort_select: Entity<SelectState<Vec<SortSelectOption>>>,
+    cred_protect_select: Entity<SelectState<Vec<CredProtectSelectOption>>>,
+    sort_order: PasskeySortOrder,
+    cred_protect_filter: CredProtectFilter,
+    large_blob_only: bool,
+    export_redact: bool,
+    min_pin_force_change: bool,
+    reset_stage: Option<ResetStage>,
+    /// Aborts the running reset, see [`Self::cancel_reset`]
+    reset_cancel: Option<Arc<AtomicBool>>,
+    pin_retries: Option<PinRetries>,
     loading: bool,
 
     _task: Option<Task<()>>,
+    _retries_task: Option<Task<()>>,
 }
 
 pub enum PasskeysEvent {
     Notification(String),
     CloseDialog,
+    /// The FIDO application was reset; cached device state is stale.
+    DeviceReset,
 }
 
 impl EventEmitter<PasskeysEvent> for PasskeysView {}
 
 impl PasskeysView {
     pub fn new(
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
         device_status: Option<FullDeviceStatus>,
         fido_info: Option<FidoDeviceInfo>,
     ) -> Self {
+        let search_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("Search by site, account or display name")
+        });
+        cx.subscribe_in(&search_input, window, |_, _, event, _, cx| {
+            if let InputEvent::Change = event {
+                cx.notify();
+            }
+        })
+        .detach();
+
+        let sort_options: Vec<SortSelectOption> = PasskeySortOrder::all()
+            .iter()
+            .map(|order| SortSelectOption {
+                order: *order,
+                label: order.label(),
+            })
+            .collect();
+        let sort_select = cx.new(|cx| {
+            SelectState::new(
+                sort_options,
+                Some(gpui_component::IndexPath::default()),
+                window,
+                cx,
+            )
+        });
+        cx.subscribe_in(&sort_select, window, |this: &mut Self, _, event, _, cx| {
+            if let SelectEvent::Confirm(Some(order)) = event {
+                this.sort_order = *order;
+                cx.notify();
+            }
+        })
+        .detach();
+
+        let cred_protect_options: Vec<CredProtectSelectOption> = CredProtectFilter::all()
+            .iter()
+            .map(|filter| CredProtectSelectOption {
+                filter: *filter,
+                label: filter.label(),
+            })
+            .collect();
+        let cred_protect_select = cx.new(|cx| {
+            SelectState::new(
+                cred_protect_options,
+                Some(gpui_component::IndexPath::default()),
+                window,
+                cx,
+            )
+        });
+        cx.subscribe_in(
+            &cred_protect_select,
+            window,
+            |this: &mut Self, _, event, _, cx| {
+                if let SelectEvent::Confirm(Some(filter)) = event {
+                    this.cred_protect_filter = *filter;
+                    cx.notify();
+                }
+            },
+        )
+        .detach();
+
         Self {
             device_status,
             fido_info,
             credentials: Vec::new(),
+            capacity: None,
+            large_blobs: None,
             unlocked: false,
             cached_pin: None,
+            selected_credential: None,
+            checked_credentials: HashSet::new(),
+            batch_results: Vec::new(),
+            search_input,
+            sort_select,
+            cred_protect_select,
+            sort_order: PasskeySortOrder::RelyingParty,
+            cred_protect_filter: CredProtectFilter::Any,
+            large_blob_only: false,
+            export_redact: false,
+            min_pin_force_change: false,
+            reset_stage: None,
+            reset_cancel: None,
+            pin_retries: None,
             loading: false,
             _task: None,
+            _retries_task: None,
         }
     }
 
@@ -74,9 +225,82 @@ impl PasskeysView {
         }
         self.device_status = status;
         self.fido_info = fido_info;
+        self.refresh_pin_retries(cx);
         cx.notify();
     }
 
+    fn pin_set(&self) -> bool {
+        self.fido_info
+            .as_ref()
+            .and_then(|f| f.options.get("clientPin").copied())
+            .unwrap_or(false)
+    }
+
+    fn pin_blocked(&self) -> bool {
+        self.pin_retries.is_some_and(|r| r.pin_retries == 0)
+    }
+
+    /// Re-reads the retry counters in the background. They are only meaningful with a PIN set.
+    fn refresh_pin_retries(&mut self, cx: &mut Context<Self>) {
+        if !self.pin_set() {
+            self.pin_retries = None;
+            self._retries_task = None;
+            return;
+        }
+        let entity = cx.entity().downgrade();
+
+        self._retries_task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::get_pin_retries() })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                match result {
+                    Ok(retries) => this.pin_retries = Some(retries),
+                    Err(e) => log::warn!("Could not read PIN retries: {}", e),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Shown instead of a PIN prompt once the PIN is blocked.
+    fn open_pin_blocked_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view_handle.clone();
+
+            dialog
+                .title("PIN Blocked")
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .child("The wrong PIN was entered too many times and the key no longer accepts any PIN. Passkeys that need the PIN cannot be used.")
+                        .child("The only way to use the key again is a factory reset of the FIDO application. It erases all passkeys, after which you can set a new PIN."),
+                )
+                .footer(move |_, _, _, _| {
+                    let view = view.clone();
+
+                    vec![
+                        Button::new("close")
+                            .label("Close")
+                            .on_click(|_, window, cx| window.close_dialog(cx)),
+                        Button::new("open-reset")
+                            .danger()
+                            .label("Factory Reset...")
+                            .on_click(move |_, window, cx| {
+                                window.close_dialog(cx);
+                                let _ = view.update(cx, |this, cx| {
+                                    this.open_reset_dialog(window, cx);
+                                });
+                            }),
+                    ]
+                })
+        });
+    }
+
     fn unlock_storage(&mut self, pin: String, cx: &mut Context<Self>) {
         if self.loading {
             return;
@@ -88,20 +312,27 @@ impl PasskeysView {
 
         self._task = Some(cx.spawn(async move |_, cx| {
             let pin_for_bg = pin.clone();
-            // let result = cx
-            //     .background_executor()
-            //     .spawn(async move { io::get_credentials(pin_for_bg) })
-            //     .await;
-
-            let result = io::get_credentials(pin_for_bg).await;
+            let (result, capacity) = cx
+                .background_executor()
+                .spawn(async move {
+                    let result = io::get_credentials(pin_for_bg.clone());
+                    let capacity = result
+                        .as_ref()
+                        .ok()
+                        .and_then(|_| Self::read_capacity(pin_for_bg));
+                    (result, capacity)
+                })
+                .await;
 
             let _ = entity.update(cx, |this, cx| {
                 this.loading = false;
+                this.refresh_pin_retries(cx);
                 match result {
                     Ok(creds) => {
                         this.unlocked = true;
                         this.cached_pin = Some(pin);
                         this.credentials = creds;
+                        this.capacity = capacity;
                         cx.emit(PasskeysEvent::CloseDialog);
                     }
                     Err(e) => {
@@ -117,10 +348,158 @@ impl PasskeysView {
     fn lock_storage(&mut self, cx: &mut Context<Self>) {
         self.unlocked = false;
         self.cached_pin = None;
+        self.selected_credential = None;
+        self.checked_credentials.clear();
+        self.batch_results.clear();
         self.credentials.clear();
+        self.capacity = None;
+        self.large_blobs = None;
+        cx.notify();
+    }
+
+    fn start_reset(&mut self, cx: &mut Context<Self>) {
+        if self.loading || self.reset_stage.is_some() {
+            return;
+        }
+        self.reset_stage = Some(ResetStage::Unplug);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.reset_cancel = Some(cancel.clone());
+        cx.notify();
+        cx.refresh_windows();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let set_stage = |stage: ResetStage, cx: &mut AsyncApp| {
+                let _ = entity.update(cx, |this, cx| {
+                    this.reset_stage = Some(stage);
+                    cx.notify();
+                    cx.refresh_windows();
+                });
+            };
+
+            let result = async {
+                let flag = cancel.clone();
+                let others = cx
+                    .background_executor()
+                    .spawn(async move { io::wait_for_device_removal(RESET_UNPLUG_TIMEOUT, &flag) })
+                    .await?;
+                set_stage(ResetStage::Replug, cx);
+                let flag = cancel.clone();
+                let path = cx
+                    .background_executor()
+                    .spawn(async move {
+                        io::wait_for_device_arrival(&others, RESET_REPLUG_TIMEOUT, &flag)
+                    })
+                    .await?;
+                set_stage(ResetStage::Touch, cx);
+                let flag = cancel.clone();
+                cx.background_executor()
+                    .spawn(async move { io::reset_fido_device(&path, &flag) })
+                    .await
+            }
+            .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.reset_stage = None;
+                this.reset_cancel = None;
+                match result {
+                    Ok(msg) => {
+                        this.lock_storage(cx);
+                        this.fido_info = None;
+                        this.pin_retries = None;
+                        cx.emit(PasskeysEvent::CloseDialog);
+                        cx.emit(PasskeysEvent::Notification(msg));
+                        cx.emit(PasskeysEvent::DeviceReset);
+                    }
+                    Err(e) => {
+                        cx.emit(PasskeysEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+                cx.refresh_windows();
+            });
+        }));
+    }
+
+    /// Asks the running reset to stop. The task keeps running until the key has confirmed the
+    /// cancel, so a touch that already got through is still reported.
+    fn cancel_reset(&mut self, cx: &mut Context<Self>) {
+        if let Some(cancel) = &self.reset_cancel {
+            cancel.store(true, Ordering::Relaxed);
+        }
         cx.notify();
     }
 
+    fn open_reset_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, cx| {
+            let view = view_handle.clone();
+            let stage = view.upgrade().and_then(|v| v.read(cx).reset_stage);
+
+            let steps = [
+                ResetStage::Unplug,
+                ResetStage::Replug,
+                ResetStage::Touch,
+            ];
+            let current = stage.and_then(|stage| steps.iter().position(|s| *s == stage));
+
+            dialog
+                .title("Factory Reset FIDO")
+                .close_button(false)
+                .overlay_closable(false)
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .child("This erases every passkey, the PIN, large blobs and the authenticator options on the key. Other applications on the key are not affected.")
+                        .child("The key only accepts a reset within a few seconds of being plugged in. After you start, unplug the key, plug it back in and touch it as soon as it blinks.")
+                        .child(
+                            v_flex()
+                                .gap_2()
+                                .children(steps.iter().enumerate().map(|(i, step)| {
+                                    let (icon, active) = match current {
+                                        Some(c) if i < c => ("icons/circle-check.svg", false),
+                                        Some(c) if i == c => ("icons/circle-dot.svg", true),
+                                        _ => ("icons/circle.svg", false),
+                                    };
+                                    h_flex()
+                                        .gap_2()
+                                        .items_center()
+                                        .when(!active, |this| this.opacity(0.6))
+                                        .child(Icon::default().path(icon).size_4())
+                                        .child(
+                                            div()
+                                                .when(active, |this| this.font_semibold())
+                                                .child(format!("{}. {}", i + 1, step.label())),
+                                        )
+                                })),
+                        ),
+                )
+                .footer(move |_, _, _, _| {
+                    let view_cancel = view.clone();
+                    let view_start = view.clone();
+
+                    vec![
+                        Button::new("cancel").label("Cancel").on_click(move |_, window, cx| {
+                            let _ = view_cancel.update(cx, |this, cx| this.cancel_reset(cx));
+                            window.close_dialog(cx);
+                        }),
+                        Button::new("start-reset")
+                            .danger()
+                            .label(if stage.is_some() {
+                                "Resetting..."
+                            } else {
+                                "Start Reset"
+                            })
+                            .disabled(stage.is_some())
+                            .on_click(move |_, _, cx| {
+                                let _ = view_start.update(cx, |this, cx| this.start_reset(cx));
+                            }),
+                    ]
+                })
+        });
+    }
+
     fn execute_delete(&mut self, credential_id: String, pin: String, cx: &mut Context<Self>) {
         if self.loading {
             return;
@@ -155,14 +534,134 @@ impl PasskeysView {
         }));
     }
 
+    fn execute_batch_delete(
+        &mut self,
+        credential_ids: Vec<String>,
+        pin: String,
+        cx: &mut Context<Self>,
+    ) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        self.batch_results.clear();
+        cx.notify();
+
+        let labels: Vec<String> = credential_ids
+            .iter()
+            .map(|id| {
+                self.credentials
+                    .iter()
+                    .find(|c| &c.credential_id == id)
+                    .map(|c| format!("{} ({})", c.rp_id, c.user_name))
+                    .unwrap_or_else(|| id.clone())
+            })
+            .collect();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let pin_for_bg = pin.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::delete_credentials(pin_for_bg, credential_ids) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| match result {
+                Ok(results) => {
+                    let total = results.len();
+                    let deleted = results.iter().filter(|r| r.error.is_none()).count();
+                    this.batch_results = labels
+                        .into_iter()
+                        .zip(results)
+                        .map(|(label, r)| (label, r.error))
+                        .collect();
+                    this.refresh_credentials(pin, cx);
+                    cx.emit(PasskeysEvent::CloseDialog);
+                    cx.emit(PasskeysEvent::Notification(format!(
+                        "Deleted {} of {} credentials",
+                        deleted, total
+                    )));
+                }
+                Err(e) => {
+                    this.loading = false;
+                    cx.emit(PasskeysEvent::Notification(format!(
+                        "Error deleting: {}",
+                        e
+                    )));
+                    cx.notify();
+                }
+            });
+        }));
+    }
+
+    fn execute_update_user(
+        &mut self,
+        cred: StoredCredential,
+        user_name: String,
+        display_name: String,
+        pin: String,
+        cx: &mut Context<Self>,
+    ) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let pin_for_bg = pin.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    io::update_user_information(
+                        pin_for_bg,
+                        cred.credential_id,
+                        cred.user_id,
+                        user_name,
+                        display_name,
+                    )
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| match result {
+                Ok(msg) => {
+                    this.refresh_credentials(pin, cx);
+                    cx.emit(PasskeysEvent::CloseDialog);
+                    cx.emit(PasskeysEvent::Notification(msg));
+                }
+                Err(e) => {
+                    this.loading = false;
+                    cx.emit(PasskeysEvent::Notification(format!(
+                        "Error updating: {}",
+                        e
+                    )));
+                    cx.notify();
+                }
+            });
+        }));
+    }
+
     fn refresh_credentials(&mut self, pin: String, cx: &mut Context<Self>) {
         let entity = cx.entity().downgrade();
         self._task = Some(cx.spawn(async move |_, cx| {
-            let result = io::get_credentials(pin).await;
+            let (result, capacity) = cx
+                .background_executor()
+                .spawn(async move {
+                    let result = io::get_credentials(pin.clone());
+                    (result, Self::read_capacity(pin))
+                })
+                .await;
 
             let _ = entity.update(cx, |this, cx| {
                 this.loading = false;
+                if capacity.is_some() {
+                    this.capacity = capacity;
+                }
                 if let Ok(creds) = result {
+                    this.checked_credentials
+                        .retain(|id| creds.iter().any(|c| &c.credential_id == id));
                     this.credentials = creds;
                 }
                 cx.notify();
@@ -170,7 +669,25 @@ impl PasskeysView {
         }));
     }
 
+    /// Capacity is informational, so a failed getCredsMetadata is only logged.
+    fn read_capacity(pin: String) -> Option<CredentialCapacity> {
+        io::get_creds_metadata(pin)
+            .inspect_err(|e| log::warn!("Could not read credential metadata: {}", e))
+            .ok()
+    }
+
     fn open_unlock_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.pin_blocked() {
+            self.open_pin_blocked_dialog(window, cx);
+            return;
+        }
+        let retries = self.pin_retries;
+
+        if self.fido_info.as_ref().is_some_and(|f| f.force_pin_change) {
+            self.open_forced_pin_change_dialog(window, cx);
+            return;
+        }
+
         let pin_input = cx.new(|cx| {
             InputState::new(window, cx)
                 .placeholder("Enter FIDO PIN")
@@ -186,6 +703,7 @@ impl PasskeysView {
                 .title("Unlock Storage")
                 .child(
                     v_flex()
+                        .children(PinRetryWarning::new(retries))
                         .gap_4()
                         .child("Enter your device PIN to view saved passkeys")
                         .child(Input::new(&pin_input)),
@@ -225,6 +743,7 @@ impl PasskeysView {
         let cred_id = cred.credential_id.clone();
         let pin_str = pin.clone();
         let name = cred.rp_id.clone();
+        let retries = self.pin_retries;
         let view_handle = cx.entity().downgrade();
 
         window.open_dialog(cx, move |dialog, _, _| {
@@ -235,10 +754,15 @@ impl PasskeysView {
             dialog
                 .confirm()
                 .title("Delete Passkey")
-                .child(format!(
-                    "Are you sure you want to delete the passkey for {}?",
-                    name
-                ))
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .children(PinRetryWarning::new(retries))
+                        .child(format!(
+                            "Are you sure you want to delete the passkey for {}?",
+                            name
+                        )),
+                )
                 .on_ok(move |_, _, cx| {
                     let _ = view_handle.update(cx, |this, cx| {
                         this.execute_delete(cred_id.clone(), pin_str.clone(), cx);
@@ -254,36 +778,461 @@ impl PasskeysView {
         });
     }
 
-    fn open_change_pin_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        let current_pin = cx.new(|cx| {
-            InputState::new(window, cx)
-                .placeholder("Enter current PIN")
-                .masked(true)
-        });
-        let new_pin = cx.new(|cx| {
-            InputState::new(window, cx)
-                .placeholder("Enter new PIN")
-                .masked(true)
-        });
-        let confirm_pin = cx.new(|cx| {
-            InputState::new(window, cx)
-                .placeholder("Confirm new PIN")
-                .masked(true)
-        });
+    fn read_large_blobs(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
 
-        let view_handle = cx.entity().downgrade();
+        let credentials = self.credentials.clone();
+        let entity = cx.entity().downgrade();
 
-        window.open_dialog(cx, move |dialog, _, _| {
-            let view = view_handle.clone();
-            let current = current_pin.clone();
-            let new = new_pin.clone();
-            let confirm = confirm_pin.clone();
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::read_large_blobs(&credentials) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(blobs) => this.large_blobs = Some(blobs),
+                    Err(e) => cx.emit(PasskeysEvent::Notification(format!(
+                        "Failed to read large blobs: {}",
+                        e
+                    ))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Runs a large-blob write with the cached PIN, then reads the array back.
+    fn execute_large_blob_write(
+        &mut self,
+        write: impl FnOnce(String) -> Result<String, String> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        if self.loading {
+            return;
+        }
+        let Some(pin) = self.cached_pin.clone() else {
+            self.lock_storage(cx);
+            return;
+        };
+        self.loading = true;
+        cx.notify();
+
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { write(pin) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(msg) => {
+                        cx.emit(PasskeysEvent::CloseDialog);
+                        cx.emit(PasskeysEvent::Notification(msg));
+                        this.read_large_blobs(cx);
+                    }
+                    Err(e) => {
+                        cx.emit(PasskeysEvent::Notification(format!(
+                            "Large blob update failed: {}",
+                            e
+                        )));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn open_large_blob_dialog(
+        &mut self,
+        cred: StoredCredential,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(key) = cred.large_blob_key.clone() else {
+            return;
+        };
+        if !self.large_blobs_supported() {
+            return;
+        }
+        let current = self
+            .large_blob_data(&cred.credential_id)
+            .map(|d| d.to_vec());
+        // Binary blobs are edited as hex, everything else as text
+        let hex_mode = current
+            .as_ref()
+            .is_some_and(|data| std::str::from_utf8(data).is_err());
+        let initial = match &current {
+            Some(data) if hex_mode => hex::encode(data),
+            Some(data) => String::from_utf8_lossy(data).to_string(),
+            None => String::new(),
+        };
+
+        let blob_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .rows(6)
+                .placeholder(if hex_mode {
+                    "Blob contents (hex)"
+                } else {
+                    "Blob contents"
+                })
+                .default_value(initial)
+        });
+        let retries = self.pin_retries;
+        let view_handle = cx.entity().downgrade();
+        let rp = cred.rp_id.clone();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view_handle.clone();
+            let input = blob_input.clone();
+            let key = key.clone();
+
+            dialog
+                .title("Edit Large Blob")
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .children(PinRetryWarning::new(retries))
+                        .child(format!(
+                            "The blob for {} is encrypted with the credential's largeBlobKey before it is written. Saving replaces the existing blob.",
+                            rp
+                        ))
+                        .child(Input::new(&blob_input)),
+                )
+                .footer(move |_, _, _, _| {
+                    let view = view.clone();
+                    let input = input.clone();
+                    let key = key.clone();
+
+                    vec![
+                        Button::new("cancel")
+                            .label("Cancel")
+                            .on_click(|_, window, cx| window.close_dialog(cx)),
+                        Button::new("save").primary().label("Save").on_click(
+                            move |_, _, cx| {
+                                let text = input.read(cx).text().to_string();
+                                let data = if hex_mode {
+                                    match hex::decode(text.trim()) {
+                                        Ok(data) => data,
+                                        Err(_) => {
+                                            let _ = view.update(cx, |_, cx| {
+                                                cx.emit(PasskeysEvent::Notification(
+                                                    "Blob contents are not valid hex".to_string(),
+                                                ));
+                                            });
+                                            return;
+                                        }
+                                    }
+                                } else {
+                                    text.into_bytes()
+                                };
+                                let key = key.clone();
+                                let _ = view.update(cx, |this, cx| {
+                                    this.execute_large_blob_write(
+                                        move |pin| io::write_large_blob(pin, key, Some(data)),
+                                        cx,
+                                    );
+                                });
+                            },
+                        ),
+                    ]
+                })
+        });
+    }
+
+    /// Whether getInfo advertises the largeBlobs option.
+    fn large_blobs_supported(&self) -> bool {
+        self.fido_info
+            .as_ref()
+            .and_then(|f| f.options.get("largeBlobs").copied())
+            .unwrap_or(false)
+    }
+
+    fn large_blob_data(&self, credential_id: &str) -> Option<&[u8]> {
+        self.large_blobs
+            .as_ref()?
+            .entries
+            .iter()
+            .find(|e| e.credential_id.as_deref() == Some(credential_id))?
+            .data
+            .as_deref()
+    }
+
+    fn open_batch_delete_dialog(
+        &mut self,
+        credential_ids: Vec<String>,
+        description: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pin) = self.cached_pin.clone() else {
+            window.push_notification("Session expired, please unlock again.", cx);
+            self.lock_storage(cx);
+            return;
+        };
+        if credential_ids.is_empty() {
+            return;
+        }
+        let retries = self.pin_retries;
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view_handle = view_handle.clone();
+            let credential_ids = credential_ids.clone();
+            let pin = pin.clone();
+
+            dialog
+                .confirm()
+                .title("Delete Passkeys")
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .children(PinRetryWarning::new(retries))
+                        .child(format!(
+                            "Are you sure you want to delete {}? This cannot be undone.",
+                            description
+                        )),
+                )
+                .on_ok(move |_, _, cx| {
+                    let _ = view_handle.update(cx, |this, cx| {
+                        this.execute_batch_delete(credential_ids.clone(), pin.clone(), cx);
+                    });
+                    false
+                })
+                .on_cancel(|_, _, _| true)
+                .button_props(
+                    gpui_component::dialog::DialogButtonProps::default()
+                        .ok_text("Delete")
+                        .ok_variant(ButtonVariant::Danger),
+                )
+        });
+    }
+
+    fn execute_export(&mut self, path: PathBuf, format: ExportFormat, cx: &mut Context<Self>) {
+        let path = if path.extension().is_none() {
+            path.with_extension(format.extension())
+        } else {
+            path
+        };
+        match io::save_credential_export(&path, &self.credentials, format, self.export_redact) {
+            Ok(()) => {
+                cx.emit(PasskeysEvent::CloseDialog);
+                cx.emit(PasskeysEvent::Notification(format!(
+                    "Exported {} credentials to {}",
+                    self.credentials.len(),
+                    path.display()
+                )));
+            }
+            Err(e) => {
+                cx.emit(PasskeysEvent::Notification(format!("Export failed: {}", e)));
+            }
+        }
+    }
+
+    fn open_export_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let default_path = directories::UserDirs::new()
+            .map(|dirs| dirs.home_dir().join("passkeys"))
+            .unwrap_or_else(|| PathBuf::from("passkeys"));
+        let path_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("File to write")
+                .default_value(default_path.display().to_string())
+        });
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, cx| {
+            let view = view_handle.clone();
+            let input = path_input.clone();
+            let redact = view
+                .upgrade()
+                .map(|v| v.read(cx).export_redact)
+                .unwrap_or(false);
+            let view_for_check = view.clone();
+
+            dialog
+                .title("Export Passkeys")
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .child("Export the relying party, user, credential ID (hex and base64url) and algorithm of every stored passkey. The file extension is added from the format if missing.")
+                        .child(Input::new(&path_input))
+                        .child(
+                            Checkbox::new("export-redact")
+                                .label("Leave out user IDs and names")
+                                .checked(redact)
+                                .on_click(move |checked, window, cx| {
+                                    let _ = view_for_check.update(cx, |this, _| {
+                                        this.export_redact = *checked;
+                                    });
+                                    window.refresh();
+                                }),
+                        ),
+                )
+                .footer(move |_, _, _, _| {
+                    let view_csv = view.clone();
+                    let view_json = view.clone();
+                    let input_csv = input.clone();
+                    let input_json = input.clone();
+
+                    vec![
+                        Button::new("cancel")
+                            .label("Cancel")
+                            .on_click(|_, window, cx| window.close_dialog(cx)),
+                        Button::new("export-csv").label("Export CSV").on_click(
+                            move |_, _, cx| {
+                                let path = input_csv.read(cx).text().to_string().trim().to_string();
+                                if path.is_empty() {
+                                    return;
+                                }
+                                let _ = view_csv.update(cx, |this, cx| {
+                                    this.execute_export(PathBuf::from(path), ExportFormat::Csv, cx);
+                                });
+                            },
+                        ),
+                        Button::new("export-json").primary().label("Export JSON").on_click(
+                            move |_, _, cx| {
+                                let path =
+                                    input_json.read(cx).text().to_string().trim().to_string();
+                                if path.is_empty() {
+                                    return;
+                                }
+                                let _ = view_json.update(cx, |this, cx| {
+                                    this.execute_export(PathBuf::from(path), ExportFormat::Json, cx);
+                                });
+                            },
+                        ),
+                    ]
+                })
+        });
+    }
+
+    fn open_edit_dialog(
+        &mut self,
+        cred: StoredCredential,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pin) = self.cached_pin.clone() else {
+            window.push_notification("Session expired, please unlock again.", cx);
+            self.lock_storage(cx);
+            return;
+        };
+
+        let name_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("User name")
+                .default_value(cred.user_name.clone())
+        });
+        let display_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Display name")
+                .default_value(cred.user_display_name.clone())
+        });
+        let retries = self.pin_retries;
+        let view_handle = cx.entity().downgrade();
+        let rp = cred.rp_id.clone();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view_handle.clone();
+            let name = name_input.clone();
+            let display = display_input.clone();
+            let cred = cred.clone();
+            let pin = pin.clone();
+
+            dialog
+                .title("Edit Passkey")
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .children(PinRetryWarning::new(retries))
+                        .child(format!(
+                            "Update the account details stored with the passkey for {}. The website is not notified of this change.",
+                            rp
+                        ))
+                        .child("User Name")
+                        .child(Input::new(&name_input))
+                        .child("Display Name")
+                        .child(Input::new(&display_input)),
+                )
+                .footer(move |_, _, _, _| {
+                    let view = view.clone();
+                    let name = name.clone();
+                    let display = display.clone();
+                    let cred = cred.clone();
+                    let pin = pin.clone();
+
+                    vec![
+                        Button::new("cancel")
+                            .label("Cancel")
+                            .on_click(|_, window, cx| window.close_dialog(cx)),
+                        Button::new("save").primary().label("Save").on_click(
+                            move |_, _, cx| {
+                                let user_name = name.read(cx).text().to_string().trim().to_string();
+                                let display_name =
+                                    display.read(cx).text().to_string().trim().to_string();
+                                let _ = view.update(cx, |this, cx| {
+                                    this.execute_update_user(
+                                        cred.clone(),
+                                        user_name,
+                                        display_name,
+                                        pin.clone(),
+                                        cx,
+                                    );
+                                });
+                            },
+                        ),
+                    ]
+                })
+        });
+    }
+
+    fn open_change_pin_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.pin_blocked() {
+            self.open_pin_blocked_dialog(window, cx);
+            return;
+        }
+        let retries = self.pin_retries;
+
+        let current_pin = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Enter current PIN")
+                .masked(true)
+        });
+        let new_pin = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Enter new PIN")
+                .masked(true)
+        });
+        let confirm_pin = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Confirm new PIN")
+                .masked(true)
+        });
+
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view_handle.clone();
+            let current = current_pin.clone();
+            let new = new_pin.clone();
+            let confirm = confirm_pin.clone();
 
             dialog
                 .title("Change PIN")
                 .child("Enter your current PIN and choose a new one.")
                 .child(
                     v_flex()
+                        .children(PinRetryWarning::new(retries))
                         .gap_4()
                         .child("Current PIN")
                         .child(Input::new(&current))
@@ -342,12 +1291,124 @@ impl PasskeysView {
         });
     }
 
+    /// Shown instead of the unlock dialog while getInfo reports forcePINChange: the key refuses
+    /// PIN tokens until the PIN has been changed.
+    fn open_forced_pin_change_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.pin_blocked() {
+            self.open_pin_blocked_dialog(window, cx);
+            return;
+        }
+        let retries = self.pin_retries;
+
+        let min_len = self
+            .fido_info
+            .as_ref()
+            .map(|f| f.min_pin_length)
+            .unwrap_or(4) as usize;
+
+        let current_pin = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Enter current PIN")
+                .masked(true)
+        });
+        let new_pin = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Enter new PIN")
+                .masked(true)
+        });
+        let confirm_pin = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Confirm new PIN")
+                .masked(true)
+        });
+
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view_handle.clone();
+            let current = current_pin.clone();
+            let new = new_pin.clone();
+            let confirm = confirm_pin.clone();
+
+            dialog
+                .title("PIN Change Required")
+                .close_button(false)
+                .overlay_closable(false)
+                .child(format!(
+                    "This key requires a new PIN before it can be used again. Choose a PIN of at least {} characters that differs from the current one.",
+                    min_len
+                ))
+                .child(
+                    v_flex()
+                        .children(PinRetryWarning::new(retries))
+                        .gap_4()
+                        .child("Current PIN")
+                        .child(Input::new(&current))
+                        .child("New PIN")
+                        .child(Input::new(&new))
+                        .child("Confirm New PIN")
+                        .child(Input::new(&confirm)),
+                )
+                .footer(move |_, _window, _cx, _| {
+                    let view = view.clone();
+                    let current = current.clone();
+                    let new = new.clone();
+                    let confirm = confirm.clone();
+
+                    vec![
+                        Button::new("cancel")
+                            .label("Cancel")
+                            .on_click(|_, window, cx| window.close_dialog(cx)),
+                        Button::new("confirm")
+                            .primary()
+                            .label("Change PIN and Unlock")
+                            .on_click(move |_, _, cx| {
+                                let current_val = current.read(cx).text().to_string();
+                                let new_val = new.read(cx).text().to_string();
+                                let confirm_val = confirm.read(cx).text().to_string();
+
+                                if current_val.is_empty() {
+                                    return;
+                                }
+
+                                let error = if new_val != confirm_val {
+                                    Some("PINs do not match".to_string())
+                                } else if new_val.len() < min_len {
+                                    Some(format!("PIN must be at least {} characters", min_len))
+                                } else if new_val == current_val {
+                                    Some("The new PIN must differ from the current one".to_string())
+                                } else {
+                                    None
+                                };
+
+                                let _ = view.update(cx, |this, cx| match error {
+                                    Some(msg) => cx.emit(PasskeysEvent::Notification(msg)),
+                                    None => this.forced_pin_change(current_val, new_val, cx),
+                                });
+                            }),
+                    ]
+                })
+        });
+    }
+
     fn open_min_pin_length_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.pin_blocked() {
+            self.open_pin_blocked_dialog(window, cx);
+            return;
+        }
+        let retries = self.pin_retries;
+
         let current_min = self
             .fido_info
             .as_ref()
             .map(|f| f.min_pin_length)
             .unwrap_or(4);
+        let max_rp_ids = self
+            .fido_info
+            .as_ref()
+            .map(|f| f.max_rpids_for_min_pin_length)
+            .unwrap_or(0) as usize;
+        self.min_pin_force_change = false;
 
         let slider = cx.new(|_| {
             SliderState::new()
@@ -372,6 +1433,12 @@ impl PasskeysView {
                 .placeholder("Confirm new PIN")
                 .masked(true)
         });
+        let rp_ids_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .rows(3)
+                .placeholder("example.com (one RP ID per line)")
+        });
 
         // Create the label view
         let label_view = cx.new(|_cx| SliderLabel {
@@ -380,12 +1447,18 @@ impl PasskeysView {
 
         let view_handle = cx.entity().downgrade();
 
-        window.open_dialog(cx, move |dialog, _, _| {
+        window.open_dialog(cx, move |dialog, _, cx| {
             let view = view_handle.clone();
             let current = current_pin.clone();
             let new = new_pin.clone();
             let confirm = confirm_pin.clone();
             let slider_handle = slider.clone();
+            let rp_ids = rp_ids_input.clone();
+            let force_change = view
+                .upgrade()
+                .map(|v| v.read(cx).min_pin_force_change)
+                .unwrap_or(false);
+            let view_for_check = view.clone();
 
             dialog
                 .title("Update Minimum PIN Length")
@@ -394,6 +1467,7 @@ impl PasskeysView {
                 )
                 .child(
                     v_flex()
+                        .children(PinRetryWarning::new(retries))
                         .gap_4()
                         .child(
                              v_flex()
@@ -410,7 +1484,34 @@ impl PasskeysView {
                                  .child(Input::new(&new))
                         )
                         .child("Confirm New PIN")
-                        .child(Input::new(&confirm)),
+                        .child(Input::new(&confirm))
+                        .when(max_rp_ids > 0, |this| {
+                            this.child(
+                                v_flex()
+                                    .gap_2()
+                                    .child(format!(
+                                        "RP IDs allowed to read the minimum PIN length (up to {})",
+                                        max_rp_ids
+                                    ))
+                                    .child(Input::new(&rp_ids_input))
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .child("The device cannot report the current list. Entering RP IDs replaces it; leave empty to keep it unchanged."),
+                                    ),
+                            )
+                        })
+                        .child(
+                            Checkbox::new("force-pin-change")
+                                .label("Require a PIN change before the key is used again")
+                                .checked(force_change)
+                                .on_click(move |checked, window, cx| {
+                                    let _ = view_for_check.update(cx, |this, _| {
+                                        this.min_pin_force_change = *checked;
+                                    });
+                                    window.refresh();
+                                }),
+                        ),
                 )
                 .footer(move |_, _window, _cx, _| {
                     let view = view.clone();
@@ -418,6 +1519,7 @@ impl PasskeysView {
                     let new = new.clone();
                     let confirm = confirm.clone();
                     let slider = slider_handle.clone();
+                    let rp_ids = rp_ids.clone();
 
                     vec![
                         Button::new("cancel")
@@ -431,11 +1533,38 @@ impl PasskeysView {
                                 let new_val = new.read(cx).text().to_string();
                                 let confirm_val = confirm.read(cx).text().to_string();
                                 let min_len = slider.read(cx).value().start() as u8;
+                                let rp_id_list: Vec<String> = rp_ids
+                                    .read(cx)
+                                    .text()
+                                    .to_string()
+                                    .lines()
+                                    .map(str::trim)
+                                    .filter(|line| !line.is_empty())
+                                    .map(String::from)
+                                    .collect();
+                                let force_change = view
+                                    .upgrade()
+                                    .map(|v| v.read(cx).min_pin_force_change)
+                                    .unwrap_or(false);
 
                                 if current_val.is_empty() {
                                     return;
                                 }
 
+                                if rp_id_list.len() > max_rp_ids {
+                                    let _ = view.update(cx, |_, cx| {
+                                        cx.emit(PasskeysEvent::Notification(format!("The device accepts at most {} RP IDs", max_rp_ids)));
+                                    });
+                                    return;
+                                }
+
+                                if force_change && !new_val.is_empty() {
+                                    let _ = view.update(cx, |_, cx| {
+                                        cx.emit(PasskeysEvent::Notification("Leave the new PIN empty when requiring a PIN change".to_string()));
+                                    });
+                                    return;
+                                }
+
                                 if !new_val.is_empty() {
                                     if new_val != confirm_val {
                                         let _ = view.update(cx, |_, cx| {
@@ -451,7 +1580,14 @@ impl PasskeysView {
                                     }
                                 }
                                 let _ = view.update(cx, |this, cx| {
-                                    this.update_min_length(current_val, min_len, new_val, cx);
+                                    this.update_min_length(
+                                        current_val,
+                                        min_len,
+                                        rp_id_list,
+                                        force_change,
+                                        new_val,
+                                        cx,
+                                    );
                                 });
                             }),
                     ]
@@ -475,6 +1611,7 @@ impl PasskeysView {
 
             let _ = entity.update(cx, |this, cx| {
                 this.loading = false;
+                this.refresh_pin_retries(cx);
                 match result {
                     Ok(msg) => {
                         cx.emit(PasskeysEvent::CloseDialog);
@@ -493,10 +1630,53 @@ impl PasskeysView {
         }));
     }
 
+    fn forced_pin_change(&mut self, current: String, new: String, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let new_for_bg = new.clone();
+            let (result, info) = cx
+                .background_executor()
+                .spawn(async move {
+                    let result = io::change_fido_pin(Some(current), new_for_bg);
+                    // forcePINChange is cleared once the PIN changed
+                    let info = result.is_ok().then(io::get_fido_info).and_then(Result::ok);
+                    (result, info)
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                this.refresh_pin_retries(cx);
+                match result {
+                    Ok(msg) => {
+                        cx.emit(PasskeysEvent::CloseDialog);
+                        cx.emit(PasskeysEvent::Notification(msg));
+                        if let Some(info) = info {
+                            this.fido_info = Some(info);
+                        }
+                        this.unlock_storage(new, cx);
+                    }
+                    Err(e) => {
+                        cx.emit(PasskeysEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
     fn update_min_length(
         &mut self,
         current: String,
         min_len: u8,
+        rp_ids: Vec<String>,
+        force_change: bool,
         new_pin: String,
         cx: &mut Context<Self>,
     ) {
@@ -512,20 +1692,26 @@ impl PasskeysView {
             let current_for_bg = current.clone();
             let res_len = cx
                 .background_executor()
-                .spawn(async move { io::set_min_pin_length(current_for_bg, min_len) })
+                .spawn(async move {
+                    io::set_min_pin_length(current_for_bg, min_len, rp_ids, force_change)
+                })
                 .await;
 
-            if let Err(e) = res_len {
-                let _ = entity.update(cx, |this, cx| {
-                    this.loading = false;
-                    cx.emit(PasskeysEvent::Notification(format!(
-                        "Failed to set length: {}",
-                        e
-                    )));
-                    cx.notify();
-                });
-                return;
-            }
+            let len_msg = match res_len {
+                Ok(msg) => msg,
+                Err(e) => {
+                    let _ = entity.update(cx, |this, cx| {
+                        this.loading = false;
+                        this.refresh_pin_retries(cx);
+                        cx.emit(PasskeysEvent::Notification(format!(
+                            "Failed to set length: {}",
+                            e
+                        )));
+                        cx.notify();
+                    });
+                    return;
+                }
+            };
 
             if !new_pin.is_empty() {
                 let res_pin = cx
@@ -534,6 +1720,7 @@ impl PasskeysView {
                     .await;
                 let _ = entity.update(cx, |this, cx| {
                     this.loading = false;
+                    this.refresh_pin_retries(cx);
                     match res_pin {
                         Ok(_) => {
                             cx.emit(PasskeysEvent::CloseDialog);
@@ -556,11 +1743,9 @@ impl PasskeysView {
             } else {
                 let _ = entity.update(cx, |this, cx| {
                     this.loading = false;
+                    this.refresh_pin_retries(cx);
                     cx.emit(PasskeysEvent::CloseDialog);
-                    cx.emit(PasskeysEvent::Notification(format!(
-                        "Minimum length updated to {}",
-                        min_len
-                    )));
+                    cx.emit(PasskeysEvent::Notification(len_msg));
                     if let Ok(info) = io::get_fido_info() {
                         this.fido_info = Some(info);
                     }
@@ -616,18 +1801,43 @@ impl PasskeysView {
     }
 
     fn render_pin_status_row(&self, cx: &mut Context<Self>) -> impl IntoElement {
-        let pin_set = self
-            .fido_info
-            .as_ref()
-            .and_then(|f| f.options.get("clientPin").copied())
-            .unwrap_or(false);
+        let pin_set = self.pin_set();
+        let blocked = self.pin_blocked();
+        let force_change = self.fido_info.as_ref().is_some_and(|f| f.force_pin_change);
+        let retries = self.pin_retries;
 
         let listener = cx.listener(|this, _, window, cx| {
             this.open_change_pin_dialog(window, cx);
         });
+        let reset_listener = cx.listener(|this, _, window, cx| {
+            this.open_reset_dialog(window, cx);
+        });
 
         let theme = cx.theme();
 
+        let retries_line = retries.filter(|_| !blocked).map(|r| {
+            let low = r.pin_retries <= PIN_RETRY_WARNING_THRESHOLD;
+            h_flex()
+                .gap_2()
+                .items_center()
+                .text_sm()
+                .text_color(if low {
+                    theme.danger
+                } else {
+                    theme.muted_foreground
+                })
+                .child(match r.uv_retries {
+                    Some(uv) => format!(
+                        "{} PIN attempts left, {} fingerprint attempts left",
+                        r.pin_retries, uv
+                    ),
+                    None => format!("{} PIN attempts left", r.pin_retries),
+                })
+                .when(r.power_cycle_required, |this| {
+                    this.child(Badge::new().child("Replug Required").color(gpui::yellow()))
+                })
+        });
+
         div()
             .flex()
             .items_center()
@@ -643,18 +1853,32 @@ impl PasskeysView {
                         div()
                             .text_sm()
                             .text_color(theme.muted_foreground)
-                            .child(if pin_set {
+                            .when(force_change, |this| this.text_color(theme.warning))
+                            .when(blocked, |this| this.text_color(theme.danger))
+                            .child(if blocked {
+                                "PIN blocked. Reset the key to use it again"
+                            } else if force_change {
+                                "PIN change required before the key can be used"
+                            } else if pin_set {
                                 "PIN is set"
                             } else {
                                 "No PIN configured"
                             }),
-                    ),
+                    )
+                    .children(retries_line),
             )
-            .child(
+            .child(if blocked {
+                Button::new("pin-blocked-reset-btn")
+                    .danger()
+                    .label("Factory Reset...")
+                    .on_click(reset_listener)
+                    .into_any_element()
+            } else {
                 PFButton::new(if pin_set { "Change PIN" } else { "Set PIN" })
                     .id("change-pin-btn")
-                    .on_click(listener),
-            )
+                    .on_click(listener)
+                    .into_any_element()
+            })
     }
 
     fn render_min_pin_length_row(&self, cx: &mut Context<Self>) -> impl IntoElement {
@@ -699,6 +1923,37 @@ impl PasskeysView {
             )
     }
 
+    fn render_factory_reset(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let busy = self.loading || self.reset_stage.is_some();
+        let theme = cx.theme();
+
+        Card::new()
+            .title("Factory Reset")
+            .icon(Icon::default().path("icons/trash-2.svg"))
+            .description("Erase all FIDO data on the key")
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .gap_4()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.muted_foreground)
+                            .child("Deletes every passkey and the PIN, and turns off enterprise attestation and alwaysUv. This is also the only way to lower the minimum PIN length."),
+                    )
+                    .child(
+                        Button::new("factory-reset-btn")
+                            .danger()
+                            .label("Reset...")
+                            .disabled(busy)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.open_reset_dialog(window, cx);
+                            })),
+                    ),
+            )
+    }
+
     fn render_stored_passkeys(&self, cx: &mut Context<Self>) -> impl IntoElement {
         if !self.unlocked {
             self.render_locked_state(cx).into_any_element()
@@ -755,16 +2010,167 @@ impl PasskeysView {
             )
     }
 
-    fn render_unlocked_state(&self, cx: &mut Context<Self>) -> impl IntoElement {
-        let creds_len = self.credentials.len();
-        let lock_listener = cx.listener(|this, _, _, cx| {
-            this.lock_storage(cx);
+    /// Applies the search text, filters and sort order to the cached credential list.
+    fn visible_credentials(&self, cx: &App) -> Vec<&StoredCredential> {
+        let query = self.search_input.read(cx).text().to_string().to_lowercase();
+        let query = query.trim();
+
+        let mut creds: Vec<&StoredCredential> = self
+            .credentials
+            .iter()
+            .filter(|c| {
+                query.is_empty()
+                    || [&c.rp_id, &c.rp_name, &c.user_name, &c.user_display_name]
+                        .iter()
+                        .any(|field| field.to_lowercase().contains(query))
+            })
+            .filter(|c| self.cred_protect_filter.matches(c.cred_protect))
+            .filter(|c| !self.large_blob_only || c.large_blob_key.is_some())
+            .collect();
+
+        let user_key = |c: &StoredCredential| {
+            if c.user_name.is_empty() {
+                c.user_display_name.to_lowercase()
+            } else {
+                c.user_name.to_lowercase()
+            }
+        };
+        match self.sort_order {
+            PasskeySortOrder::RelyingParty => {
+                creds.sort_by_key(|c| (c.rp_id.to_lowercase(), user_key(c)))
+            }
+            PasskeySortOrder::User => creds.sort_by_key(|c| (user_key(c), c.rp_id.to_lowercase())),
+        }
+        creds
+    }
+
+    fn render_list_controls(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let large_blob_listener = cx.listener(|this, checked: &bool, _, cx| {
+            this.large_blob_only = *checked;
+            cx.notify();
+        });
+
+        h_flex()
+            .gap_3()
+            .items_center()
+            .child(
+                div().flex_1().child(
+                    Input::new(&self.search_input)
+                        .prefix(Icon::default().path("icons/search.svg").size_4())
+                        .cleanable(true),
+                ),
+            )
+            .child(div().w(px(220.)).child(Select::new(&self.sort_select)))
+            .child(
+                div()
+                    .w(px(300.))
+                    .child(Select::new(&self.cred_protect_select)),
+            )
+            .child(
+                Checkbox::new("large-blob-filter")
+                    .label("Has large blob key")
+                    .checked(self.large_blob_only)
+                    .on_click(large_blob_listener),
+            )
+    }
+
+    fn render_unlocked_state(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let creds_len = self.credentials.len();
+        let lock_listener = cx.listener(|this, _, _, cx| {
+            this.lock_storage(cx);
+        });
+        let export_listener = cx.listener(|this, _, window, cx| {
+            this.open_export_dialog(window, cx);
+        });
+
+        let visible = self.visible_credentials(cx);
+        let visible_len = visible.len();
+        let list = match self.sort_order {
+            PasskeySortOrder::RelyingParty => {
+                let mut groups: BTreeMap<String, Vec<&StoredCredential>> = BTreeMap::new();
+                for cred in visible {
+                    groups
+                        .entry(cred.rp_id.to_lowercase())
+                        .or_default()
+                        .push(cred);
+                }
+                let groups: Vec<AnyElement> = groups
+                    .into_values()
+                    .map(|creds| self.render_rp_group(&creds, cx).into_any_element())
+                    .collect();
+                v_flex().gap_6().children(groups).into_any_element()
+            }
+            PasskeySortOrder::User => {
+                let cards: Vec<AnyElement> = visible
+                    .into_iter()
+                    .map(|cred| self.render_credential_card(cred, cx).into_any_element())
+                    .collect();
+                div()
+                    .grid()
+                    .grid_cols(3)
+                    .gap_4()
+                    .children(cards)
+                    .into_any_element()
+            }
+        };
+        let controls = (!self.credentials.is_empty())
+            .then(|| self.render_list_controls(cx).into_any_element());
+
+        let checked_count = self.checked_credentials.len();
+        let clear_listener = cx.listener(|this, _, _, cx| {
+            this.checked_credentials.clear();
+            cx.notify();
+        });
+        let delete_checked_listener = cx.listener(move |this, _, window, cx| {
+            let ids: Vec<String> = this
+                .credentials
+                .iter()
+                .filter(|c| this.checked_credentials.contains(&c.credential_id))
+                .map(|c| c.credential_id.clone())
+                .collect();
+            let description = format!("{} selected passkeys", ids.len());
+            this.open_batch_delete_dialog(ids, description, window, cx);
+        });
+        let selection_actions = (checked_count > 0).then(|| {
+            h_flex()
+                .gap_2()
+                .items_center()
+                .child(
+                    Button::new("clear-selection-btn")
+                        .ghost()
+                        .small()
+                        .label("Clear Selection")
+                        .on_click(clear_listener),
+                )
+                .child(
+                    Button::new("delete-selected-btn")
+                        .danger()
+                        .small()
+                        .label(format!("Delete Selected ({})", checked_count))
+                        .disabled(self.loading)
+                        .on_click(delete_checked_listener),
+                )
+        });
+
+        let capacity = self.capacity.map(CapacityBar::new).or_else(|| {
+            self.fido_info
+                .as_ref()
+                .and_then(io::estimate_capacity)
+                .map(|capacity| CapacityBar::new(capacity).estimated(true))
         });
 
-        let mut cards = Vec::new();
-        for cred in &self.credentials {
-            cards.push(self.render_credential_card(cred, cx).into_any_element());
-        }
+        let large_blob_storage = self
+            .large_blobs_supported()
+            .then(|| self.render_large_blob_storage(cx).into_any_element());
+
+        let batch_results = (!self.batch_results.is_empty())
+            .then(|| self.render_batch_results(cx).into_any_element());
+
+        let details = self
+            .selected_credential
+            .as_ref()
+            .and_then(|id| self.credentials.iter().find(|c| &c.credential_id == id))
+            .map(|cred| self.render_credential_details(cred, cx).into_any_element());
 
         let theme = cx.theme();
 
@@ -800,33 +2206,569 @@ impl PasskeysView {
                                     )
                                     .child(div().w_px().h_4().bg(theme.border))
                                     .child(
-                                        div()
-                                            .text_sm()
-                                            .text_color(theme.muted_foreground)
-                                            .child(format!("{} credentials stored", creds_len)),
+                                        div().text_sm().text_color(theme.muted_foreground).child(
+                                            if visible_len == creds_len {
+                                                format!("{} credentials stored", creds_len)
+                                            } else {
+                                                format!(
+                                                    "Showing {} of {} credentials",
+                                                    visible_len, creds_len
+                                                )
+                                            },
+                                        ),
                                     ),
                             )
                             .child(
-                                PFIconButton::new(
-                                    Icon::default().path("icons/lock.svg").size_3p5(),
-                                    "Lock Storage",
-                                )
-                                .small()
-                                .on_click(lock_listener),
+                                h_flex()
+                                    .gap_4()
+                                    .items_center()
+                                    .children(selection_actions)
+                                    .child(
+                                        Button::new("export-passkeys-btn")
+                                            .ghost()
+                                            .small()
+                                            .label("Export")
+                                            .disabled(self.credentials.is_empty())
+                                            .on_click(export_listener),
+                                    )
+                                    .child(
+                                        PFIconButton::new(
+                                            Icon::default().path("icons/lock.svg").size_3p5(),
+                                            "Lock Storage",
+                                        )
+                                        .small()
+                                        .on_click(lock_listener),
+                                    ),
                             ),
                     )
+                    .children(capacity)
+                    .children(batch_results)
+                    .children(controls)
                     .child(if self.credentials.is_empty() {
                         self.render_empty_credentials_with_theme(theme)
                             .into_any_element()
-                    } else {
+                    } else if visible_len == 0 {
                         div()
-                            .grid()
-                            .grid_cols(3)
-                            .gap_4()
-                            .children(cards)
+                            .py_8()
+                            .text_center()
+                            .text_sm()
+                            .text_color(theme.muted_foreground)
+                            .child("No passkeys match the current search and filters.")
                             .into_any_element()
+                    } else {
+                        list
+                    })
+                    .children(details)
+                    .children(large_blob_storage),
+            )
+    }
+
+    fn render_rp_group(
+        &self,
+        creds: &[&StoredCredential],
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let first = creds[0];
+        let rp_id = first.rp_id.clone();
+        let title = if !first.rp_name.is_empty() {
+            first.rp_name.clone()
+        } else if !first.rp_id.is_empty() {
+            first.rp_id.clone()
+        } else {
+            "Unknown Service".to_string()
+        };
+        let ids: Vec<String> = creds.iter().map(|c| c.credential_id.clone()).collect();
+        let all_checked = ids.iter().all(|id| self.checked_credentials.contains(id));
+        // "Delete All" covers every credential of the RP, including ones hidden by filters
+        let rp_ids: Vec<String> = self
+            .credentials
+            .iter()
+            .filter(|c| c.rp_id == first.rp_id)
+            .map(|c| c.credential_id.clone())
+            .collect();
+
+        let check_listener = cx.listener(move |this, checked: &bool, _, cx| {
+            for id in &ids {
+                if *checked {
+                    this.checked_credentials.insert(id.clone());
+                } else {
+                    this.checked_credentials.remove(id);
+                }
+            }
+            cx.notify();
+        });
+        let rp_for_delete = rp_id.clone();
+        let delete_all_listener = cx.listener(move |this, _, window, cx| {
+            let description = format!(
+                "all {} passkeys for {}",
+                rp_ids.len(),
+                if rp_for_delete.is_empty() {
+                    "this service"
+                } else {
+                    &rp_for_delete
+                }
+            );
+            this.open_batch_delete_dialog(rp_ids.clone(), description, window, cx);
+        });
+
+        let cards: Vec<AnyElement> = creds
+            .iter()
+            .map(|cred| self.render_credential_card(cred, cx).into_any_element())
+            .collect();
+
+        let theme = cx.theme();
+
+        v_flex()
+            .gap_3()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        h_flex()
+                            .gap_3()
+                            .items_center()
+                            .child(
+                                Checkbox::new(SharedString::from(format!("rp-check-{}", rp_id)))
+                                    .checked(all_checked)
+                                    .on_click(check_listener),
+                            )
+                            .child(div().font_semibold().child(title.clone()))
+                            .when(title != rp_id && !rp_id.is_empty(), |d| {
+                                d.child(
+                                    div()
+                                        .text_sm()
+                                        .text_color(theme.muted_foreground)
+                                        .child(rp_id.clone()),
+                                )
+                            })
+                            .child(Badge::new().child(format!("{}", creds.len()))),
+                    )
+                    .child(
+                        Button::new(SharedString::from(format!("rp-delete-{}", rp_id)))
+                            .ghost()
+                            .small()
+                            .label("Delete All")
+                            .disabled(self.loading)
+                            .on_click(delete_all_listener),
+                    ),
+            )
+            .child(div().grid().grid_cols(3).gap_4().children(cards))
+    }
+
+    fn render_batch_results(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let dismiss_listener = cx.listener(|this, _, _, cx| {
+            this.batch_results.clear();
+            cx.notify();
+        });
+        let failed = self
+            .batch_results
+            .iter()
+            .filter(|(_, error)| error.is_some())
+            .count();
+
+        let theme = cx.theme();
+
+        v_flex()
+            .gap_2()
+            .p_4()
+            .border_1()
+            .border_color(theme.border)
+            .rounded_xl()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(div().font_semibold().child(format!(
+                        "Batch delete: {} deleted, {} failed",
+                        self.batch_results.len() - failed,
+                        failed
+                    )))
+                    .child(
+                        Button::new("dismiss-batch-btn")
+                            .ghost()
+                            .small()
+                            .child(Icon::default().path("icons/close.svg").size_4())
+                            .on_click(dismiss_listener),
+                    ),
+            )
+            .children(self.batch_results.iter().map(|(label, error)| {
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .text_sm()
+                    .child(
+                        Icon::default()
+                            .path(if error.is_none() {
+                                "icons/circle-check.svg"
+                            } else {
+                                "icons/circle-x.svg"
+                            })
+                            .size_4()
+                            .text_color(if error.is_none() {
+                                gpui::green()
+                            } else {
+                                gpui::red()
+                            }),
+                    )
+                    .child(label.clone())
+                    .children(
+                        error
+                            .as_ref()
+                            .map(|e| div().text_color(theme.muted_foreground).child(e.clone())),
+                    )
+            }))
+    }
+
+    fn render_large_blob_storage(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let read_listener = cx.listener(|this, _, _, cx| {
+            this.read_large_blobs(cx);
+        });
+
+        let rows: Vec<AnyElement> = self
+            .large_blobs
+            .iter()
+            .flat_map(|blobs| blobs.entries.iter())
+            .map(|entry| {
+                let index = entry.index;
+                let delete_listener = cx.listener(move |this, _, _, cx| {
+                    this.execute_large_blob_write(
+                        move |pin| io::delete_large_blob_entry(pin, index),
+                        cx,
+                    );
+                });
+                let owner = entry
+                    .credential_id
+                    .as_ref()
+                    .and_then(|id| self.credentials.iter().find(|c| &c.credential_id == id))
+                    .map(|c| format!("{} ({})", c.rp_id, c.user_name))
+                    .unwrap_or_else(|| "Unknown credential".to_string());
+                let theme = cx.theme();
+
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .text_sm()
+                    .child(
+                        h_flex()
+                            .gap_3()
+                            .child(
+                                div()
+                                    .text_color(theme.muted_foreground)
+                                    .child(format!("#{}", index)),
+                            )
+                            .child(owner),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_3()
+                            .items_center()
+                            .child(
+                                div()
+                                    .text_color(theme.muted_foreground)
+                                    .child(format!("{} bytes", entry.stored_size)),
+                            )
+                            .child(
+                                Button::new(SharedString::from(format!("blob-delete-{}", index)))
+                                    .ghost()
+                                    .small()
+                                    .child(
+                                        Icon::default()
+                                            .path("icons/trash-2.svg")
+                                            .size_4()
+                                            .text_color(theme.muted_foreground),
+                                    )
+                                    .disabled(self.loading)
+                                    .on_click(delete_listener),
+                            ),
+                    )
+                    .into_any_element()
+            })
+            .collect();
+
+        let theme = cx.theme();
+        let summary = match &self.large_blobs {
+            Some(blobs) => format!(
+                "{} entries, {} of {} bytes used",
+                blobs.entries.len(),
+                blobs.serialized_size,
+                blobs.max_size
+            ),
+            None => {
+                "Read the large-blob array to view and edit the blobs stored with your passkeys."
+                    .to_string()
+            }
+        };
+
+        v_flex()
+            .gap_3()
+            .p_4()
+            .border_1()
+            .border_color(theme.border)
+            .rounded_xl()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        v_flex()
+                            .child(div().font_semibold().child("Large Blob Storage"))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(theme.muted_foreground)
+                                    .child(summary),
+                            ),
+                    )
+                    .child(
+                        PFButton::new(if self.large_blobs.is_some() {
+                            "Refresh"
+                        } else {
+                            "Read Large Blobs"
+                        })
+                        .id("read-large-blobs-btn")
+                        .small()
+                        .disabled(self.loading)
+                        .on_click(read_listener),
+                    ),
+            )
+            .children(
+                self.large_blobs
+                    .as_ref()
+                    .filter(|blobs| blobs.integrity_failed)
+                    .map(|_| {
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .text_sm()
+                            .text_color(gpui::yellow())
+                            .child(Icon::new(IconName::TriangleAlert).size_4())
+                            .child(
+                                "The stored array failed its integrity check and is shown as empty. The next write replaces it.",
+                            )
                     }),
             )
+            .children(rows)
+    }
+
+    fn render_large_blob_section(
+        &self,
+        cred: &StoredCredential,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let cred_for_edit = cred.clone();
+        let edit_listener = cx.listener(move |this, _, window, cx| {
+            this.open_large_blob_dialog(cred_for_edit.clone(), window, cx);
+        });
+        let key_for_delete = cred.large_blob_key.clone().unwrap_or_default();
+        let delete_listener = cx.listener(move |this, _, _, cx| {
+            let key = key_for_delete.clone();
+            this.execute_large_blob_write(move |pin| io::write_large_blob(pin, key, None), cx);
+        });
+
+        let data = self.large_blob_data(&cred.credential_id);
+        let loaded = self.large_blobs.is_some();
+        let theme = cx.theme();
+
+        let content = match data {
+            Some(data) => match std::str::from_utf8(data) {
+                Ok(text) => text.to_string(),
+                Err(_) => hex::encode_upper(data),
+            },
+            None if loaded => "No blob stored".to_string(),
+            None => "Read large blobs below to view the blob".to_string(),
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.muted_foreground)
+                            .child(match data {
+                                Some(data) => format!("Large Blob ({} bytes)", data.len()),
+                                None => "Large Blob".to_string(),
+                            }),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                PFButton::new(if data.is_some() {
+                                    "Edit Blob"
+                                } else {
+                                    "Add Blob"
+                                })
+                                .id("edit-blob-btn")
+                                .small()
+                                .disabled(!loaded || self.loading)
+                                .on_click(edit_listener),
+                            )
+                            .child(
+                                PFButton::new("Delete Blob")
+                                    .id("delete-blob-btn")
+                                    .small()
+                                    .disabled(data.is_none() || self.loading)
+                                    .on_click(delete_listener),
+                            ),
+                    ),
+            )
+            .child(
+                div()
+                    .p_3()
+                    .rounded_md()
+                    .bg(theme.muted)
+                    .text_sm()
+                    .font_family("Mono")
+                    .child(content),
+            )
+    }
+
+    fn render_credential_details(
+        &self,
+        cred: &StoredCredential,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let key_for_pem = cred.public_key.clone();
+        let key_for_jwk = cred.public_key.clone();
+
+        let pem_listener = cx.listener(move |_, _, _, cx| {
+            let Some(key) = &key_for_pem else { return };
+            match io::credential_public_key_pem(key) {
+                Ok(pem) => {
+                    cx.write_to_clipboard(ClipboardItem::new_string(pem));
+                    cx.emit(PasskeysEvent::Notification(
+                        "Public key (PEM) copied to clipboard".to_string(),
+                    ));
+                }
+                Err(e) => cx.emit(PasskeysEvent::Notification(format!("Export failed: {}", e))),
+            }
+        });
+        let jwk_listener = cx.listener(move |_, _, _, cx| {
+            let Some(key) = &key_for_jwk else { return };
+            match io::credential_public_key_jwk(key) {
+                Ok(jwk) => {
+                    cx.write_to_clipboard(ClipboardItem::new_string(jwk));
+                    cx.emit(PasskeysEvent::Notification(
+                        "Public key (JWK) copied to clipboard".to_string(),
+                    ));
+                }
+                Err(e) => cx.emit(PasskeysEvent::Notification(format!("Export failed: {}", e))),
+            }
+        });
+        let close_listener = cx.listener(|this, _, _, cx| {
+            this.selected_credential = None;
+            cx.notify();
+        });
+
+        let large_blob = (cred.large_blob_key.is_some() && self.large_blobs_supported())
+            .then(|| self.render_large_blob_section(cred, cx).into_any_element());
+
+        let theme = cx.theme();
+
+        let row = |label: &'static str, value: String, mono: bool| {
+            h_flex()
+                .gap_4()
+                .items_start()
+                .child(
+                    div()
+                        .w(px(160.))
+                        .flex_shrink_0()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child(label),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w_0()
+                        .text_sm()
+                        .when(mono, |d| d.font_family("Mono"))
+                        .child(if value.is_empty() {
+                            "-".to_string()
+                        } else {
+                            value
+                        }),
+                )
+        };
+
+        let cred_protect = match cred.cred_protect {
+            Some(1) => "Level 1: UV optional".to_string(),
+            Some(2) => "Level 2: UV optional with credential ID list".to_string(),
+            Some(3) => "Level 3: UV required".to_string(),
+            Some(other) => format!("Unknown ({})", other),
+            None => "Not reported".to_string(),
+        };
+
+        let key_rows = match &cred.public_key {
+            Some(key) => vec![
+                row("Algorithm", io::cose_algorithm_name(key.algorithm), false),
+                row("Curve", io::cose_curve_name(key.curve), false),
+                row("Public Key X", key.x.clone(), true),
+                row("Public Key Y", key.y.clone().unwrap_or_default(), true),
+            ],
+            None => vec![row("Public Key", "Not reported".to_string(), false)],
+        };
+
+        v_flex()
+            .gap_4()
+            .p_4()
+            .border_1()
+            .border_color(theme.border)
+            .rounded_xl()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(div().font_semibold().child("Credential Details"))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                PFButton::new("Copy PEM")
+                                    .id("copy-pem-btn")
+                                    .small()
+                                    .disabled(cred.public_key.is_none())
+                                    .on_click(pem_listener),
+                            )
+                            .child(
+                                PFButton::new("Copy JWK")
+                                    .id("copy-jwk-btn")
+                                    .small()
+                                    .disabled(cred.public_key.is_none())
+                                    .on_click(jwk_listener),
+                            )
+                            .child(
+                                Button::new("close-details-btn")
+                                    .ghost()
+                                    .small()
+                                    .child(Icon::default().path("icons/close.svg").size_4())
+                                    .on_click(close_listener),
+                            ),
+                    ),
+            )
+            .child(row("RP ID", cred.rp_id.clone(), false))
+            .child(row("RP Name", cred.rp_name.clone(), false))
+            .child(row("User Name", cred.user_name.clone(), false))
+            .child(row("Display Name", cred.user_display_name.clone(), false))
+            .child(row("User ID", cred.user_id.clone(), true))
+            .child(row("Credential ID", cred.credential_id.clone(), true))
+            .children(key_rows)
+            .child(row("credProtect", cred_protect, false))
+            .child(row(
+                "Large Blob Key",
+                if cred.large_blob_key.is_some() {
+                    "Present".to_string()
+                } else {
+                    "None".to_string()
+                },
+                false,
+            ))
+            .children(large_blob)
     }
 
     fn render_empty_credentials_with_theme(&self, theme: &Theme) -> impl IntoElement {
@@ -867,19 +2809,53 @@ impl PasskeysView {
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
         let cred_clone = cred.clone();
+        let cred_for_edit = cred.clone();
+        let cred_id = cred.credential_id.clone();
+        let selected = self.selected_credential.as_ref() == Some(&cred.credential_id);
 
         let delete_listener = cx.listener(move |this, _, window, cx| {
+            cx.stop_propagation();
             this.open_ask_delete_pin(cred_clone.clone(), window, cx);
         });
+        let edit_listener = cx.listener(move |this, _, window, cx| {
+            cx.stop_propagation();
+            this.open_edit_dialog(cred_for_edit.clone(), window, cx);
+        });
+        let checked = self.checked_credentials.contains(&cred.credential_id);
+        let cred_id_for_check = cred.credential_id.clone();
+        let check_listener = cx.listener(move |this, checked: &bool, _, cx| {
+            cx.stop_propagation();
+            if *checked {
+                this.checked_credentials.insert(cred_id_for_check.clone());
+            } else {
+                this.checked_credentials.remove(&cred_id_for_check);
+            }
+            cx.notify();
+        });
+        let select_listener = cx.listener(move |this, _, _, cx| {
+            if this.selected_credential.as_ref() == Some(&cred_id) {
+                this.selected_credential = None;
+            } else {
+                this.selected_credential = Some(cred_id.clone());
+            }
+            cx.notify();
+        });
 
         let theme = cx.theme();
 
         div()
+            .id(SharedString::from(format!("cred-{}", cred.credential_id)))
             .border_1()
-            .border_color(theme.border)
+            .border_color(if selected {
+                theme.primary
+            } else {
+                theme.border
+            })
             .rounded_xl()
             .p_4()
+            .cursor_pointer()
             .hover(|s| s.bg(theme.accent).border_color(theme.primary))
+            .on_click(select_listener)
             .child(
                 h_flex()
                     .justify_between()
@@ -890,6 +2866,11 @@ impl PasskeysView {
                             .items_center()
                             .flex_1()
                             .min_w_0()
+                            .child(
+                                Checkbox::new("cred-check")
+                                    .checked(checked)
+                                    .on_click(check_listener),
+                            )
                             .child(
                                 div()
                                     .size_10()
@@ -935,16 +2916,31 @@ impl PasskeysView {
                             ),
                     )
                     .child(
-                        Button::new("delete-cred-btn")
-                            .ghost()
-                            .small()
+                        h_flex()
                             .child(
-                                Icon::default()
-                                    .path("icons/trash-2.svg")
-                                    .size_4()
-                                    .text_color(theme.muted_foreground),
+                                Button::new("edit-cred-btn")
+                                    .ghost()
+                                    .small()
+                                    .child(
+                                        Icon::default()
+                                            .path("icons/pencil.svg")
+                                            .size_4()
+                                            .text_color(theme.muted_foreground),
+                                    )
+                                    .on_click(edit_listener),
                             )
-                            .on_click(delete_listener),
+                            .child(
+                                Button::new("delete-cred-btn")
+                                    .ghost()
+                                    .small()
+                                    .child(
+                                        Icon::default()
+                                            .path("icons/trash-2.svg")
+                                            .size_4()
+                                            .text_color(theme.muted_foreground),
+                                    )
+                                    .on_click(delete_listener),
+                            ),
                     ),
             )
     }
@@ -999,7 +2995,8 @@ impl Render for PasskeysView {
         let content = v_flex()
             .gap_6()
             .child(self.render_pin_management(cx))
-            .child(self.render_stored_passkeys(cx));
+            .child(self.render_stored_passkeys(cx))
+            .child(self.render_factory_reset(cx));
 
         let theme = cx.theme();
 
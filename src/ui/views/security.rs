@@ -1,17 +1,1472 @@
-use crate::ui::components::page_view::PageView;
+use crate::device::inventory;
+use crate::device::io;
+use crate::device::types::{
+    DeviceCapabilities, FidoDeviceInfo, FullDeviceStatus, GenuineCheckResult, GenuineStatus,
+    PinRetries,
+};
+use crate::ui::components::{
+    button::PFButton, card::Card, page_view::PageView, pin_warning::PinRetryWarning,
+};
+use gpui::prelude::*;
 use gpui::*;
 use gpui_component::{
-    ActiveTheme, Disableable, Icon, StyledExt,
-    button::{Button, ButtonCustomVariant, ButtonVariants},
+    ActiveTheme, Disableable, Icon, StyledExt, WindowExt,
+    badge::Badge,
+    button::{Button, ButtonCustomVariant, ButtonVariant, ButtonVariants},
     h_flex,
+    input::{Input, InputState},
     switch::Switch,
     v_flex,
 };
+use std::path::PathBuf;
+
+#[derive(Clone, Copy)]
+enum PinAction {
+    EaUpload,
+    EaEnable,
+    ToggleAlwaysUv,
+    TogglePinComplexity,
+    EnableEncryption,
+    DisableEncryption,
+}
+
+pub struct SecurityView {
+    device_status: Option<FullDeviceStatus>,
+    fido_info: Option<FidoDeviceInfo>,
+    capabilities: Option<DeviceCapabilities>,
+    csr: Option<Vec<u8>>,
+    csr_subject: Option<String>,
+    certificate_uploaded: bool,
+    ca_key_input: Entity<InputState>,
+    ca_cert_input: Entity<InputState>,
+    validity_input: Entity<InputState>,
+    output_dir_input: Entity<InputState>,
+    device_public_key: Option<Vec<u8>>,
+    device_signature: Option<String>,
+    challenge_input: Entity<InputState>,
+    device_cert_input: Entity<InputState>,
+    genuine_check: Option<GenuineCheckResult>,
+    host_secret_stored: bool,
+    /// Read each time a PIN dialog opens
+    pin_retries: Option<PinRetries>,
+    loading: bool,
 
-pub struct SecurityView;
+    _task: Option<Task<()>>,
+    _retries_task: Option<Task<()>>,
+}
+
+pub enum SecurityEvent {
+    Notification(String),
+    CloseDialog,
+}
+
+impl EventEmitter<SecurityEvent> for SecurityView {}
 
 impl SecurityView {
-    pub fn build<V: 'static>(cx: &mut Context<V>) -> impl IntoElement {
+    pub fn new(
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        device_status: Option<FullDeviceStatus>,
+        fido_info: Option<FidoDeviceInfo>,
+        capabilities: Option<DeviceCapabilities>,
+    ) -> Self {
+        let ca_key_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Path to CA private key (PEM)"));
+        let ca_cert_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Path to CA certificate (PEM)"));
+        let validity_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Validity in days")
+                .default_value("3650")
+        });
+        let output_dir_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Directory to save the CSR and certificate (optional)")
+        });
+        let challenge_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Challenge to sign (hex)"));
+        let device_cert_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("Path to device certificate (PEM or DER)")
+        });
+
+        let host_secret_stored = device_status
+            .as_ref()
+            .is_some_and(|s| io::has_host_secret(&s.info.serial));
+
+        Self {
+            device_status,
+            fido_info,
+            capabilities,
+            csr: None,
+            csr_subject: None,
+            certificate_uploaded: false,
+            ca_key_input,
+            ca_cert_input,
+            validity_input,
+            output_dir_input,
+            device_public_key: None,
+            device_signature: None,
+            challenge_input,
+            device_cert_input,
+            genuine_check: None,
+            host_secret_stored,
+            pin_retries: None,
+            loading: false,
+            _task: None,
+            _retries_task: None,
+        }
+    }
+
+    pub fn update_device_status(
+        &mut self,
+        status: Option<FullDeviceStatus>,
+        fido_info: Option<FidoDeviceInfo>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.device_status == status && self.fido_info == fido_info {
+            return;
+        }
+        // A different key invalidates the CSR we are holding.
+        if self.fido_info.as_ref().map(|f| &f.aaguid) != fido_info.as_ref().map(|f| &f.aaguid)
+            || status.is_none()
+        {
+            self.csr = None;
+            self.csr_subject = None;
+            self.certificate_uploaded = false;
+            self.device_public_key = None;
+            self.device_signature = None;
+            self.genuine_check = None;
+        }
+        self.host_secret_stored = status
+            .as_ref()
+            .is_some_and(|s| io::has_host_secret(&s.info.serial));
+        self.device_status = status;
+        self.fido_info = fido_info;
+        cx.notify();
+    }
+
+    pub fn update_capabilities(
+        &mut self,
+        capabilities: Option<DeviceCapabilities>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.capabilities == capabilities {
+            return;
+        }
+        self.capabilities = capabilities;
+        cx.notify();
+    }
+
+    /// The CTAP cards are hidden once probing shows the firmware has no FIDO support.
+    fn fido_available(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_none_or(|caps| caps.fido_available())
+    }
+
+    /// Re-reads the PIN retry counter in the background for the warning in PIN dialogs.
+    fn refresh_pin_retries(&mut self, cx: &mut Context<Self>) {
+        let entity = cx.entity().downgrade();
+
+        self._retries_task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::get_pin_retries() })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                match result {
+                    Ok(retries) => this.pin_retries = Some(retries),
+                    Err(e) => log::warn!("Could not read PIN retries: {}", e),
+                }
+                cx.notify();
+                // The open PIN dialog reads the counter when it renders.
+                cx.refresh_windows();
+            });
+        }));
+    }
+
+    fn serial(&self) -> Option<String> {
+        self.device_status.as_ref().map(|s| s.info.serial.clone())
+    }
+
+    /// Returns `None` when the device does not support enterprise attestation, otherwise whether
+    /// it is currently enabled (getInfo option `ep`).
+    fn ea_state(&self) -> Option<bool> {
+        self.fido_info
+            .as_ref()
+            .and_then(|f| f.options.get("ep").copied())
+    }
+
+    /// Returns `None` when the device does not support alwaysUv, otherwise whether it is on.
+    fn always_uv_state(&self) -> Option<bool> {
+        self.fido_info
+            .as_ref()
+            .and_then(|f| f.options.get("alwaysUv").copied())
+    }
+
+    /// Returns `None` when the device does not report a PIN complexity policy.
+    fn pin_complexity_state(&self) -> Option<bool> {
+        self.fido_info
+            .as_ref()
+            .and_then(|f| f.pin_complexity_policy)
+    }
+
+    fn output_dir(&self, cx: &App) -> Option<PathBuf> {
+        let dir = self.output_dir_input.read(cx).text().to_string();
+        let dir = dir.trim();
+        (!dir.is_empty()).then(|| PathBuf::from(dir))
+    }
+
+    fn generate_csr(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+
+        let output_dir = self.output_dir(cx);
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let csr = io::generate_ea_csr()?;
+                    if let Some(dir) = output_dir {
+                        io::save_pem(&dir.join("ea.csr"), "CERTIFICATE REQUEST", &csr)?;
+                    }
+                    Ok::<_, crate::device::error::PFError>(csr)
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(csr) => {
+                        this.csr_subject = io::csr_common_name(&csr).ok().flatten();
+                        this.csr = Some(csr);
+                        this.certificate_uploaded = false;
+                        cx.emit(SecurityEvent::Notification(
+                            "Certificate signing request generated".to_string(),
+                        ));
+                    }
+                    Err(e) => {
+                        cx.emit(SecurityEvent::Notification(format!(
+                            "Failed to generate CSR: {}",
+                            e
+                        )));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn sign_and_upload(&mut self, pin: String, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let Some(csr) = self.csr.clone() else {
+            return;
+        };
+
+        let ca_key = self
+            .ca_key_input
+            .read(cx)
+            .text()
+            .to_string()
+            .trim()
+            .to_string();
+        let ca_cert = self
+            .ca_cert_input
+            .read(cx)
+            .text()
+            .to_string()
+            .trim()
+            .to_string();
+        if ca_key.is_empty() || ca_cert.is_empty() {
+            cx.emit(SecurityEvent::Notification(
+                "Select the CA private key and certificate first".to_string(),
+            ));
+            return;
+        }
+        let Ok(validity_days) = self
+            .validity_input
+            .read(cx)
+            .text()
+            .to_string()
+            .trim()
+            .parse::<u32>()
+        else {
+            cx.emit(SecurityEvent::Notification(
+                "Validity must be a number of days".to_string(),
+            ));
+            return;
+        };
+
+        let aaguid = self.fido_info.as_ref().map(|f| f.aaguid.clone());
+        let output_dir = self.output_dir(cx);
+
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let cert = io::sign_ea_csr(
+                        &csr,
+                        &PathBuf::from(ca_key),
+                        &PathBuf::from(ca_cert),
+                        validity_days,
+                        aaguid.as_deref(),
+                    )
+                    .map_err(|e| e.to_string())?;
+                    if let Some(dir) = output_dir {
+                        io::save_pem(&dir.join("ea.crt"), "CERTIFICATE", &cert)
+                            .map_err(|e| e.to_string())?;
+                    }
+                    io::upload_ea_certificate(pin, cert)
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(msg) => {
+                        this.certificate_uploaded = true;
+                        cx.emit(SecurityEvent::CloseDialog);
+                        cx.emit(SecurityEvent::Notification(msg));
+                    }
+                    Err(e) => {
+                        cx.emit(SecurityEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn enable_ea(&mut self, pin: String, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let (result, info) = cx
+                .background_executor()
+                .spawn(async move {
+                    let result = io::enable_enterprise_attestation(pin);
+                    // Re-read the options the change affected
+                    let info = result.is_ok().then(io::get_fido_info).and_then(Result::ok);
+                    (result, info)
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(msg) => {
+                        cx.emit(SecurityEvent::CloseDialog);
+                        cx.emit(SecurityEvent::Notification(msg));
+                        if let Some(info) = info {
+                            this.fido_info = Some(info);
+                        }
+                    }
+                    Err(e) => {
+                        cx.emit(SecurityEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn toggle_always_uv(&mut self, pin: String, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let (result, info) = cx
+                .background_executor()
+                .spawn(async move {
+                    let result = io::toggle_always_uv(pin);
+                    // Re-read the options the change affected
+                    let info = result.is_ok().then(io::get_fido_info).and_then(Result::ok);
+                    (result, info)
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(enabled) => {
+                        cx.emit(SecurityEvent::CloseDialog);
+                        cx.emit(SecurityEvent::Notification(if enabled {
+                            "Always require user verification is on".to_string()
+                        } else {
+                            "Always require user verification is off".to_string()
+                        }));
+                        if let Some(info) = info {
+                            this.fido_info = Some(info);
+                        }
+                    }
+                    Err(e) => {
+                        cx.emit(SecurityEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn toggle_pin_complexity(&mut self, pin: String, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let enable = self.pin_complexity_state() != Some(true);
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let (result, info) = cx
+                .background_executor()
+                .spawn(async move {
+                    let result = io::set_pin_complexity_policy(pin, enable);
+                    // Re-read the options the change affected
+                    let info = result.is_ok().then(io::get_fido_info).and_then(Result::ok);
+                    (result, info)
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(enabled) => {
+                        cx.emit(SecurityEvent::CloseDialog);
+                        cx.emit(SecurityEvent::Notification(if enabled {
+                            "PIN complexity policy is enforced".to_string()
+                        } else {
+                            "PIN complexity policy is off".to_string()
+                        }));
+                        if let Some(info) = info {
+                            this.fido_info = Some(info);
+                        }
+                    }
+                    Err(e) => {
+                        cx.emit(SecurityEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn run_encryption_task(
+        &mut self,
+        task: impl FnOnce() -> Result<String, String> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx.background_executor().spawn(async move { task() }).await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                this.host_secret_stored = this
+                    .device_status
+                    .as_ref()
+                    .is_some_and(|s| io::has_host_secret(&s.info.serial));
+                match result {
+                    Ok(msg) => {
+                        cx.emit(SecurityEvent::CloseDialog);
+                        cx.emit(SecurityEvent::Notification(msg));
+                    }
+                    Err(e) => {
+                        cx.emit(SecurityEvent::Notification(format!("Error: {}", e)));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn open_pin_dialog(&mut self, action: PinAction, window: &mut Window, cx: &mut Context<Self>) {
+        let pin_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Enter FIDO PIN")
+                .masked(true)
+        });
+        let view_handle = cx.entity().downgrade();
+        self.refresh_pin_retries(cx);
+
+        let (title, description, ok_label) = match action {
+            PinAction::EaUpload => (
+                "Upload Attestation Certificate",
+                "The CSR will be signed with your CA and the certificate uploaded to the device. Enter your device PIN to continue.",
+                "Sign & Upload",
+            ),
+            PinAction::EaEnable => (
+                "Enable Enterprise Attestation",
+                "Relying parties on the authenticator's enterprise list will receive uniquely identifying attestation. Enter your device PIN to continue.",
+                "Enable",
+            ),
+            PinAction::ToggleAlwaysUv => {
+                if self.always_uv_state() == Some(true) {
+                    (
+                        "Turn Off Always Require UV",
+                        "Relying parties will again be able to use the key with a touch only when they don't ask for user verification. Enter your device PIN to continue.",
+                        "Turn Off",
+                    )
+                } else {
+                    (
+                        "Turn On Always Require UV",
+                        "Every operation, including U2F and touch-only logins, will require your PIN. Enter your device PIN to continue.",
+                        "Turn On",
+                    )
+                }
+            }
+            PinAction::TogglePinComplexity => {
+                if self.pin_complexity_state() == Some(true) {
+                    (
+                        "Stop Enforcing PIN Complexity",
+                        "Trivial PINs such as repeated digits and sequences will be accepted again. Enter your device PIN to continue.",
+                        "Stop Enforcing",
+                    )
+                } else {
+                    (
+                        "Enforce PIN Complexity",
+                        "Future PIN changes must not be trivial. The current PIN stays valid. Enter your device PIN to continue.",
+                        "Enforce",
+                    )
+                }
+            }
+            PinAction::EnableEncryption => (
+                "Enable Encryption at Rest",
+                "A secret is stored on this computer and the key's storage is encrypted with it. After every power-up the key stays locked until PicoForge unlocks it from this computer. Enter your device PIN to continue.",
+                "Enable",
+            ),
+            PinAction::DisableEncryption => (
+                "Disable Encryption at Rest",
+                "The key's storage will no longer depend on the secret stored on this computer. The key must be unlocked first. Enter your device PIN to continue.",
+                "Disable",
+            ),
+        };
+
+        window.open_dialog(cx, move |dialog, _, cx| {
+            let view = view_handle.clone();
+            let input = pin_input.clone();
+            let retries = view.upgrade().and_then(|v| v.read(cx).pin_retries);
+
+            dialog
+                .title(title)
+                .child(
+                    v_flex()
+                        .gap_4()
+                        .children(PinRetryWarning::new(retries))
+                        .child(description)
+                        .child(Input::new(&pin_input)),
+                )
+                .footer(move |_, _, _, _| {
+                    let view = view.clone();
+                    let input = input.clone();
+
+                    vec![
+                        Button::new("cancel")
+                            .label("Cancel")
+                            .on_click(|_, window, cx| window.close_dialog(cx)),
+                        Button::new("confirm").primary().label(ok_label).on_click(
+                            move |_, _, cx| {
+                                let pin = input.read(cx).text().to_string();
+                                if pin.is_empty() {
+                                    return;
+                                }
+                                let _ = view.update(cx, |this, cx| match action {
+                                    PinAction::EaUpload => this.sign_and_upload(pin, cx),
+                                    PinAction::EaEnable => this.enable_ea(pin, cx),
+                                    PinAction::ToggleAlwaysUv => this.toggle_always_uv(pin, cx),
+                                    PinAction::TogglePinComplexity => {
+                                        this.toggle_pin_complexity(pin, cx)
+                                    }
+                                    PinAction::EnableEncryption => {
+                                        let Some(serial) = this.serial() else {
+                                            return;
+                                        };
+                                        this.run_encryption_task(
+                                            move || io::enable_encryption_at_rest(pin, serial),
+                                            cx,
+                                        )
+                                    }
+                                    PinAction::DisableEncryption => this.run_encryption_task(
+                                        move || io::disable_encryption_at_rest(pin),
+                                        cx,
+                                    ),
+                                });
+                            },
+                        ),
+                    ]
+                })
+        });
+    }
+
+    fn render_authenticator_options(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let always_uv = self.always_uv_state();
+        let ea_state = self.ea_state();
+        let loading = self.loading;
+
+        let always_uv_listener = cx.listener(|this, _: &bool, window, cx| {
+            this.open_pin_dialog(PinAction::ToggleAlwaysUv, window, cx);
+        });
+        let ea_listener = cx.listener(|this, checked: &bool, window, cx| {
+            if *checked {
+                this.open_pin_dialog(PinAction::EaEnable, window, cx);
+            }
+        });
+
+        let theme = cx.theme();
+
+        let row = |title: &'static str, description: &'static str, switch: Switch| {
+            h_flex()
+                .justify_between()
+                .items_center()
+                .gap_4()
+                .p_4()
+                .border_1()
+                .border_color(theme.border)
+                .rounded_lg()
+                .child(
+                    v_flex()
+                        .gap_1()
+                        .child(div().font_medium().child(title))
+                        .child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child(description),
+                        ),
+                )
+                .child(switch)
+        };
+
+        Card::new()
+            .title("Authenticator Options")
+            .icon(Icon::default().path("icons/shield-check.svg"))
+            .description("authenticatorConfig options (changes require your PIN)")
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(row(
+                        "Always Require User Verification",
+                        match always_uv {
+                            None => "Not supported by this device",
+                            Some(_) => "Require the PIN for every operation, including U2F and touch-only logins (alwaysUv)",
+                        },
+                        Switch::new("always-uv-switch")
+                            .checked(always_uv.unwrap_or(false))
+                            .disabled(always_uv.is_none() || loading)
+                            .on_click(always_uv_listener),
+                    ))
+                    .child(row(
+                        "Enterprise Attestation",
+                        match ea_state {
+                            None => "Not supported by this device",
+                            Some(true) => "Enabled. It can only be turned off again by resetting the key (ep)",
+                            Some(false) => "Allow uniquely identifying attestation for relying parties on the enterprise list (ep)",
+                        },
+                        Switch::new("ea-switch")
+                            .checked(ea_state.unwrap_or(false))
+                            .disabled(ea_state != Some(false) || loading)
+                            .on_click(ea_listener),
+                    )),
+            )
+    }
+
+    fn render_pin_complexity(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.pin_complexity_state();
+        let policy_url = self
+            .fido_info
+            .as_ref()
+            .and_then(|f| f.pin_complexity_policy_url.clone());
+        let loading = self.loading;
+
+        let listener = cx.listener(|this, _: &bool, window, cx| {
+            this.open_pin_dialog(PinAction::TogglePinComplexity, window, cx);
+        });
+
+        let theme = cx.theme();
+
+        let rule = |example: &'static str, description: &'static str| {
+            h_flex()
+                .gap_3()
+                .items_center()
+                .child(
+                    div()
+                        .w_24()
+                        .font_family("monospace")
+                        .text_color(theme.foreground)
+                        .child(example),
+                )
+                .child(description)
+        };
+
+        Card::new()
+            .title("PIN Complexity Policy")
+            .icon(Icon::default().path("icons/key.svg"))
+            .description("Reject trivial PINs when a PIN is set or changed")
+            .header_right(
+                Badge::new()
+                    .child(match state {
+                        Some(true) => "Enforced",
+                        Some(false) => "Not Enforced",
+                        None => "Not Reported",
+                    })
+                    .color(match state {
+                        Some(true) => gpui::green(),
+                        Some(false) => gpui::yellow(),
+                        None => theme.muted_foreground,
+                    }),
+            )
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .items_center()
+                            .gap_4()
+                            .child(
+                                v_flex()
+                                    .gap_1()
+                                    .child(div().font_medium().child("Enforce PIN Complexity"))
+                                    .child(
+                                        div()
+                                            .text_sm()
+                                            .text_color(theme.muted_foreground)
+                                            .child(if state.is_some() {
+                                                "Existing PINs are not checked again; the policy applies to the next PIN change."
+                                            } else {
+                                                "This firmware does not report pinComplexityPolicy in getInfo."
+                                            }),
+                                    ),
+                            )
+                            .child(
+                                Switch::new("pin-complexity-switch")
+                                    .checked(state.unwrap_or(false))
+                                    .disabled(state.is_none() || loading)
+                                    .on_click(listener),
+                            ),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_2()
+                            .text_sm()
+                            .text_color(theme.muted_foreground)
+                            .child(div().font_medium().child("When enforced, the key rejects:"))
+                            .child(rule("111111", "The same character repeated"))
+                            .child(rule("123456", "Ascending sequences of digits or letters"))
+                            .child(rule("654321", "Descending sequences of digits or letters")),
+                    )
+                    .children(policy_url.map(|url| {
+                        div()
+                            .text_sm()
+                            .text_color(theme.muted_foreground)
+                            .child(format!("Policy details: {}", url))
+                    })),
+            )
+    }
+
+    fn render_encryption_at_rest(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let host_secret_stored = self.host_secret_stored;
+        let secret_path = self
+            .serial()
+            .and_then(|serial| io::host_secret_path(&serial))
+            .map(|path| path.display().to_string());
+        let loading = self.loading;
+
+        let enable_listener = cx.listener(|this, _, window, cx| {
+            this.open_pin_dialog(PinAction::EnableEncryption, window, cx);
+        });
+        let disable_listener = cx.listener(|this, _, window, cx| {
+            this.open_pin_dialog(PinAction::DisableEncryption, window, cx);
+        });
+        let unlock_listener = cx.listener(|this, _, _, cx| {
+            if let Some(serial) = this.serial() {
+                this.run_encryption_task(move || io::unlock_device(serial), cx);
+            }
+        });
+
+        let theme = cx.theme();
+
+        Card::new()
+            .title("Encryption at Rest")
+            .icon(Icon::default().path("icons/lock.svg"))
+            .description("Encrypt the key's storage with a secret kept on this computer")
+            .header_right(
+                Badge::new()
+                    .child(if host_secret_stored {
+                        "Secret Stored"
+                    } else {
+                        "No Secret"
+                    })
+                    .color(if host_secret_stored {
+                        gpui::green()
+                    } else {
+                        theme.muted_foreground
+                    }),
+            )
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.muted_foreground)
+                            .child("After a power-up an encrypted key refuses to operate until the host sends its secret over an MSE-secured channel. PicoForge does this automatically when the key is connected."),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(if host_secret_stored {
+                                theme.muted_foreground
+                            } else {
+                                theme.warning
+                            })
+                            .child(match (&secret_path, host_secret_stored) {
+                                (Some(path), true) => format!("Host secret: {}. Back this file up: losing it means resetting the key.", path),
+                                (None, _) => "The serial number of this key could not be read, so no host secret can be tied to it.".to_string(),
+                                _ => "No host secret for this key on this computer. Enabling creates one.".to_string(),
+                            }),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                PFButton::new("Enable")
+                                    .id("encryption-enable-btn")
+                                    .disabled(secret_path.is_none() || loading)
+                                    .on_click(enable_listener),
+                            )
+                            .child(
+                                PFButton::new("Unlock Now")
+                                    .id("encryption-unlock-btn")
+                                    .disabled(!host_secret_stored || loading)
+                                    .on_click(unlock_listener),
+                            )
+                            .child(
+                                PFButton::new("Disable")
+                                    .id("encryption-disable-btn")
+                                    .disabled(loading)
+                                    .on_click(disable_listener),
+                            ),
+                    ),
+            )
+    }
+
+    fn render_enterprise_attestation(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let ea_state = self.ea_state();
+        let has_csr = self.csr.is_some();
+        let loading = self.loading;
+
+        let generate_listener = cx.listener(|this, _, _, cx| {
+            this.generate_csr(cx);
+        });
+        let upload_listener = cx.listener(|this, _, window, cx| {
+            this.open_pin_dialog(PinAction::EaUpload, window, cx);
+        });
+        let enable_listener = cx.listener(|this, _, window, cx| {
+            this.open_pin_dialog(PinAction::EaEnable, window, cx);
+        });
+
+        let theme = cx.theme();
+
+        let (status_text, status_color) = match ea_state {
+            None => ("Not Supported", theme.muted_foreground),
+            Some(false) => ("Disabled", rgb(0xf59e0b).into()),
+            Some(true) => ("Enabled", gpui::green()),
+        };
+
+        let csr_text = match (&self.csr, &self.csr_subject) {
+            (Some(csr), Some(cn)) => format!("CSR ready for \"{}\" ({} bytes)", cn, csr.len()),
+            (Some(csr), None) => format!("CSR ready ({} bytes)", csr.len()),
+            (None, _) => "No CSR requested yet".to_string(),
+        };
+
+        let row = |label: &'static str, input: &Entity<InputState>| {
+            v_flex()
+                .gap_2()
+                .child(div().text_sm().font_medium().child(label))
+                .child(Input::new(input))
+        };
+
+        Card::new()
+            .title("Enterprise Attestation")
+            .icon(Icon::default().path("icons/building-2.svg"))
+            .description("Issue the device's enterprise attestation certificate from your own CA")
+            .header_right(
+                Badge::new()
+                    .child(status_text)
+                    .color(status_color),
+            )
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_between()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(
+                                v_flex()
+                                    .child(div().font_medium().child("1. Request CSR"))
+                                    .child(
+                                        div()
+                                            .text_sm()
+                                            .text_color(theme.muted_foreground)
+                                            .child(csr_text),
+                                    ),
+                            )
+                            .child(
+                                PFButton::new("Generate CSR")
+                                    .id("ea-generate-csr-btn")
+                                    .disabled(ea_state.is_none() || loading)
+                                    .on_click(generate_listener),
+                            ),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_4()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(
+                                h_flex()
+                                    .justify_between()
+                                    .items_center()
+                                    .child(
+                                        v_flex()
+                                            .child(div().font_medium().child("2. Sign & Upload"))
+                                            .child(
+                                                div()
+                                                    .text_sm()
+                                                    .text_color(theme.muted_foreground)
+                                                    .child(if self.certificate_uploaded {
+                                                        "Certificate uploaded"
+                                                    } else {
+                                                        "Sign the CSR with a local CA and upload the certificate"
+                                                    }),
+                                            ),
+                                    )
+                                    .child(
+                                        PFButton::new("Sign & Upload")
+                                            .id("ea-upload-btn")
+                                            .disabled(!has_csr || loading)
+                                            .on_click(upload_listener),
+                                    ),
+                            )
+                            .child(row("CA Private Key", &self.ca_key_input))
+                            .child(row("CA Certificate", &self.ca_cert_input))
+                            .child(
+                                h_flex()
+                                    .gap_4()
+                                    .child(div().w(px(160.)).child(row(
+                                        "Validity (days)",
+                                        &self.validity_input,
+                                    )))
+                                    .child(
+                                        div()
+                                            .flex_1()
+                                            .child(row("Save To", &self.output_dir_input)),
+                                    ),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_between()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(
+                                v_flex()
+                                    .child(div().font_medium().child("3. Enable"))
+                                    .child(
+                                        div()
+                                            .text_sm()
+                                            .text_color(theme.muted_foreground)
+                                            .child(match ea_state {
+                                                Some(true) => "Enterprise attestation is enabled",
+                                                _ => "Turn on enterprise attestation (requires PIN)",
+                                            }),
+                                    ),
+                            )
+                            .child(
+                                PFButton::new("Enable")
+                                    .id("ea-enable-btn")
+                                    .disabled(ea_state != Some(false) || loading)
+                                    .on_click(enable_listener),
+                            ),
+                    ),
+            )
+    }
+
+    fn read_device_key(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::get_device_public_key() })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(key) => this.device_public_key = Some(key),
+                    Err(e) => cx.emit(SecurityEvent::Notification(format!(
+                        "Failed to read device key: {}",
+                        e
+                    ))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn sign_challenge(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let challenge = self.challenge_input.read(cx).text().to_string();
+        let Ok(challenge) = hex::decode(challenge.trim()) else {
+            cx.emit(SecurityEvent::Notification(
+                "Challenge must be a hex string".to_string(),
+            ));
+            return;
+        };
+
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::sign_device_challenge(&challenge) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(signature) => this.device_signature = Some(hex::encode_upper(signature)),
+                    Err(e) => {
+                        this.device_signature = None;
+                        cx.emit(SecurityEvent::Notification(format!(
+                            "Signing failed: {}",
+                            e
+                        )));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn upload_device_certificate(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let path = self.device_cert_input.read(cx).text().to_string();
+        let path = PathBuf::from(path.trim());
+        if path.as_os_str().is_empty() {
+            return;
+        }
+
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let data = std::fs::read(&path).map_err(|e| {
+                        crate::device::error::PFError::Io(format!(
+                            "Could not read {}: {}",
+                            path.display(),
+                            e
+                        ))
+                    })?;
+                    io::upload_device_certificate(&data)
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(msg) => cx.emit(SecurityEvent::Notification(msg)),
+                    Err(e) => cx.emit(SecurityEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn enroll_device(&mut self, force: bool, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::enroll_device(&inventory::default_path(), None, force) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(msg) => {
+                        this.genuine_check = None;
+                        cx.emit(SecurityEvent::Notification(msg));
+                    }
+                    Err(e) => cx.emit(SecurityEvent::Notification(format!(
+                        "Enrollment failed: {}",
+                        e
+                    ))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn open_reenroll_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let view_handle = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view_handle = view_handle.clone();
+
+            dialog
+                .confirm()
+                .title("Re-enroll Device")
+                .child(
+                    "This serial number is enrolled with a different device key. Replace it only if you regenerated the device key yourself; a cloned or swapped key looks exactly like this.",
+                )
+                .on_ok(move |_, _, cx| {
+                    let _ = view_handle.update(cx, |this, cx| this.enroll_device(true, cx));
+                    true
+                })
+                .on_cancel(|_, _, _| true)
+                .button_props(
+                    gpui_component::dialog::DialogButtonProps::default()
+                        .ok_text("Re-enroll")
+                        .ok_variant(ButtonVariant::Danger),
+                )
+        });
+    }
+
+    fn verify_device(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::verify_genuine_device(&inventory::default_path()) })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(check) => this.genuine_check = Some(check),
+                    Err(e) => {
+                        this.genuine_check = None;
+                        cx.emit(SecurityEvent::Notification(format!(
+                            "Verification failed: {}",
+                            e
+                        )));
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn render_genuine_check(&self, is_rescue: bool, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let enroll_listener = cx.listener(|this, _, _, cx| {
+            this.enroll_device(false, cx);
+        });
+        let reenroll_listener = cx.listener(|this, _, window, cx| {
+            this.open_reenroll_dialog(window, cx);
+        });
+        let mismatch = self
+            .genuine_check
+            .as_ref()
+            .is_some_and(|check| check.status == GenuineStatus::PossibleClone);
+        let verify_listener = cx.listener(|this, _, _, cx| {
+            this.verify_device(cx);
+        });
+
+        let theme = cx.theme();
+
+        let result = self.genuine_check.as_ref().map(|check| {
+            let (text, color) = match check.status {
+                GenuineStatus::Genuine => ("Genuine", gpui::green()),
+                GenuineStatus::NotEnrolled => ("Not Enrolled", theme.muted_foreground),
+                GenuineStatus::PossibleClone => ("Possible Clone", rgb(0xef4444).into()),
+            };
+            let serial = match &check.label {
+                Some(label) => format!("Serial {} ({})", check.serial, label),
+                None => format!("Serial {}", check.serial),
+            };
+
+            v_flex()
+                .gap_1()
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .child(Badge::new().child(text).color(color))
+                        .child(div().text_sm().child(serial)),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child(check.detail.clone()),
+                )
+        });
+
+        v_flex()
+            .gap_3()
+            .p_4()
+            .border_1()
+            .border_color(theme.border)
+            .rounded_lg()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        v_flex()
+                            .child(div().font_medium().child("Genuine Device Check"))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(theme.muted_foreground)
+                                    .child("Compare this key against the local device inventory"),
+                            ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .when(mismatch, |this| {
+                                this.child(
+                                    PFButton::new("Re-enroll")
+                                        .id("genuine-reenroll-btn")
+                                        .disabled(!is_rescue || loading)
+                                        .on_click(reenroll_listener),
+                                )
+                            })
+                            .when(!mismatch, |this| {
+                                this.child(
+                                    PFButton::new("Enroll")
+                                        .id("genuine-enroll-btn")
+                                        .disabled(!is_rescue || loading)
+                                        .on_click(enroll_listener),
+                                )
+                            })
+                            .child(
+                                PFButton::new("Verify")
+                                    .id("genuine-verify-btn")
+                                    .disabled(!is_rescue || loading)
+                                    .on_click(verify_listener),
+                            ),
+                    ),
+            )
+            .children(result)
+    }
+
+    fn render_device_key(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let is_rescue = self
+            .device_status
+            .as_ref()
+            .map(|s| s.method == crate::device::types::DeviceMethod::Rescue)
+            .unwrap_or(false);
+
+        let read_listener = cx.listener(|this, _, _, cx| {
+            this.read_device_key(cx);
+        });
+        let copy_listener = cx.listener(|this, _, _, cx| {
+            if let Some(key) = &this.device_public_key {
+                cx.write_to_clipboard(ClipboardItem::new_string(io::device_public_key_pem(key)));
+                cx.emit(SecurityEvent::Notification(
+                    "Public key copied to clipboard".to_string(),
+                ));
+            }
+        });
+        let sign_listener = cx.listener(|this, _, _, cx| {
+            this.sign_challenge(cx);
+        });
+        let upload_listener = cx.listener(|this, _, _, cx| {
+            this.upload_device_certificate(cx);
+        });
+        let genuine_check = self.render_genuine_check(is_rescue, cx).into_any_element();
+
+        let theme = cx.theme();
+
+        let mono_block = |text: String| {
+            div()
+                .p_3()
+                .rounded_md()
+                .bg(theme.muted)
+                .font_family("monospace")
+                .text_xs()
+                .child(text)
+        };
+
+        Card::new()
+            .title("Device Key")
+            .icon(Icon::default().path("icons/key.svg"))
+            .description("Enroll the device key in your PKI through the rescue interface")
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(
+                                h_flex()
+                                    .justify_between()
+                                    .items_center()
+                                    .child(
+                                        v_flex()
+                                            .child(div().font_medium().child("Public Key"))
+                                            .child(
+                                                div()
+                                                    .text_sm()
+                                                    .text_color(theme.muted_foreground)
+                                                    .child("P-256 key unique to this device"),
+                                            ),
+                                    )
+                                    .child(
+                                        h_flex()
+                                            .gap_2()
+                                            .when(self.device_public_key.is_some(), |this| {
+                                                this.child(
+                                                    PFButton::new("Copy PEM")
+                                                        .id("device-key-copy-btn")
+                                                        .on_click(copy_listener),
+                                                )
+                                            })
+                                            .child(
+                                                PFButton::new("Read Public Key")
+                                                    .id("device-key-read-btn")
+                                                    .disabled(!is_rescue || loading)
+                                                    .on_click(read_listener),
+                                            ),
+                                    ),
+                            )
+                            .children(
+                                self.device_public_key
+                                    .as_ref()
+                                    .map(|key| mono_block(hex::encode_upper(key))),
+                            ),
+                    )
+                    .child(genuine_check)
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(div().font_medium().child("Sign Challenge"))
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(div().flex_1().child(Input::new(&self.challenge_input)))
+                                    .child(
+                                        PFButton::new("Sign")
+                                            .id("device-key-sign-btn")
+                                            .disabled(!is_rescue || loading)
+                                            .on_click(sign_listener),
+                                    ),
+                            )
+                            .children(self.device_signature.clone().map(mono_block)),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(div().font_medium().child("Device Certificate"))
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(div().flex_1().child(Input::new(&self.device_cert_input)))
+                                    .child(
+                                        PFButton::new("Upload")
+                                            .id("device-key-upload-btn")
+                                            .disabled(!is_rescue || loading)
+                                            .on_click(upload_listener),
+                                    ),
+                            ),
+                    )
+                    .when(!is_rescue, |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child("The device key is only reachable through the rescue interface (PC/SC)."),
+                        )
+                    }),
+            )
+    }
+
+    fn render_secure_boot(&self, cx: &mut Context<Self>) -> impl IntoElement {
         let (fg, muted_fg, border) = {
             let theme = cx.theme();
             (theme.foreground, theme.muted_foreground, theme.border)
@@ -23,7 +1478,7 @@ impl SecurityView {
         let destructive_border = rgba(0xef44444d);
         let destructive_bg_muted = rgba(0xef44441a);
 
-        let content = v_flex()
+        v_flex()
             .gap_6()
             .w_full()
             .child(
@@ -176,11 +1631,32 @@ impl SecurityView {
                                     ),
                             ),
                     ),
-            );
+            )
+    }
+}
+
+impl Render for SecurityView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = v_flex()
+            .gap_6()
+            .w_full()
+            .when(
+                self.device_status.is_some() && self.fido_available(),
+                |this| {
+                    this.child(self.render_authenticator_options(cx))
+                        .child(self.render_pin_complexity(cx))
+                        .child(self.render_encryption_at_rest(cx))
+                        .child(self.render_enterprise_attestation(cx))
+                },
+            )
+            .when(self.device_status.is_some(), |this| {
+                this.child(self.render_device_key(cx))
+            })
+            .child(self.render_secure_boot(cx));
 
         PageView::build(
-            "Secure Boot",
-            "Permanently lock this device to the current firmware vendor.",
+            "Security",
+            "Manage attestation and permanently lock this device to the current firmware vendor.",
             content,
             cx.theme(),
         )
@@ -0,0 +1,832 @@
+use crate::device::fido::{constants::CoseAlgorithm, ssh::SshKeyOptions};
+use crate::device::io;
+use crate::device::ssh::{self, SshSkKey, SshSkKeyType};
+use crate::device::types::{FidoDeviceInfo, FullDeviceStatus, HmacSecretOutput, PinRetries};
+use crate::ui::components::{
+    button::PFButton, card::Card, page_view::PageView, pin_warning::PinRetryWarning,
+};
+use gpui::prelude::*;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme, Icon, StyledExt, Theme,
+    badge::Badge,
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputState},
+    radio::{Radio, RadioGroup},
+    v_flex,
+};
+use std::path::PathBuf;
+
+const SSH_KEY_TYPES: [SshSkKeyType; 2] = [SshSkKeyType::EcdsaSk, SshSkKeyType::Ed25519Sk];
+
+pub struct ToolsView {
+    device_status: Option<FullDeviceStatus>,
+    fido_info: Option<FidoDeviceInfo>,
+    hmac_rp_id_input: Entity<InputState>,
+    hmac_credential_input: Entity<InputState>,
+    hmac_salt1_input: Entity<InputState>,
+    hmac_salt2_input: Entity<InputState>,
+    hmac_pin_input: Entity<InputState>,
+    hmac_resident: bool,
+    hmac_output: Option<HmacSecretOutput>,
+    ssh_key_type: SshSkKeyType,
+    ssh_resident: bool,
+    ssh_verify_required: bool,
+    ssh_no_touch: bool,
+    ssh_application_input: Entity<InputState>,
+    ssh_user_input: Entity<InputState>,
+    ssh_comment_input: Entity<InputState>,
+    ssh_path_input: Entity<InputState>,
+    ssh_pin_input: Entity<InputState>,
+    ssh_generated: Option<SshSkKey>,
+    /// Resident `ssh:` keys, once loaded with the PIN
+    ssh_resident_keys: Option<Vec<SshSkKey>>,
+    pin_retries: Option<PinRetries>,
+    loading: bool,
+
+    _task: Option<Task<()>>,
+    _retries_task: Option<Task<()>>,
+}
+
+pub enum ToolsEvent {
+    Notification(String),
+}
+
+impl EventEmitter<ToolsEvent> for ToolsView {}
+
+impl ToolsView {
+    pub fn new(
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        device_status: Option<FullDeviceStatus>,
+        fido_info: Option<FidoDeviceInfo>,
+    ) -> Self {
+        let hmac_rp_id_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Relying party ID")
+                .default_value("hmac-secret.picoforge.invalid")
+        });
+        let hmac_credential_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Credential ID (hex, empty to use a resident credential)")
+        });
+        let hmac_salt1_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("Salt 1 (64 hex characters or any text)")
+        });
+        let hmac_salt2_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Salt 2 (optional)"));
+        let hmac_pin_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("FIDO PIN (optional)")
+                .masked(true)
+        });
+
+        let ssh_application_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Application")
+                .default_value(ssh::SSH_APPLICATION_PREFIX)
+        });
+        let ssh_user_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("User name for resident keys (optional)")
+        });
+        let ssh_comment_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Comment (optional)"));
+        let ssh_path_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder(format!(
+                "Key file (default {})",
+                ssh::default_ssh_dir().join("id_*_sk").display()
+            ))
+        });
+        let ssh_pin_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("FIDO PIN")
+                .masked(true)
+        });
+
+        let mut this = Self {
+            device_status,
+            fido_info,
+            hmac_rp_id_input,
+            hmac_credential_input,
+            hmac_salt1_input,
+            hmac_salt2_input,
+            hmac_pin_input,
+            hmac_resident: false,
+            hmac_output: None,
+            ssh_key_type: SshSkKeyType::EcdsaSk,
+            ssh_resident: false,
+            ssh_verify_required: false,
+            ssh_no_touch: false,
+            ssh_application_input,
+            ssh_user_input,
+            ssh_comment_input,
+            ssh_path_input,
+            ssh_pin_input,
+            ssh_generated: None,
+            ssh_resident_keys: None,
+            pin_retries: None,
+            loading: false,
+            _task: None,
+            _retries_task: None,
+        };
+        this.refresh_pin_retries(cx);
+        this
+    }
+
+    pub fn update_device_status(
+        &mut self,
+        status: Option<FullDeviceStatus>,
+        fido_info: Option<FidoDeviceInfo>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.device_status == status && self.fido_info == fido_info {
+            return;
+        }
+        // Outputs are specific to the key that derived them.
+        if self.fido_info.as_ref().map(|f| &f.aaguid) != fido_info.as_ref().map(|f| &f.aaguid)
+            || status.is_none()
+        {
+            self.hmac_output = None;
+            self.ssh_generated = None;
+            self.ssh_resident_keys = None;
+        }
+        self.device_status = status;
+        self.fido_info = fido_info;
+        self.refresh_pin_retries(cx);
+        cx.notify();
+    }
+
+    /// Re-reads the retry counter in the background. It is only meaningful with a PIN set.
+    fn refresh_pin_retries(&mut self, cx: &mut Context<Self>) {
+        let pin_set = self
+            .fido_info
+            .as_ref()
+            .and_then(|f| f.options.get("clientPin").copied())
+            .unwrap_or(false);
+        if !pin_set {
+            self.pin_retries = None;
+            self._retries_task = None;
+            return;
+        }
+        let entity = cx.entity().downgrade();
+
+        self._retries_task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::get_pin_retries() })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                match result {
+                    Ok(retries) => this.pin_retries = Some(retries),
+                    Err(e) => log::warn!("Could not read PIN retries: {}", e),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn supports_hmac_secret(&self) -> bool {
+        self.fido_info
+            .as_ref()
+            .is_some_and(|info| info.extensions.iter().any(|e| e == "hmac-secret"))
+    }
+
+    fn supports_algorithm(&self, algorithm: CoseAlgorithm) -> bool {
+        self.fido_info
+            .as_ref()
+            .is_some_and(|info| info.algorithms.contains(&(algorithm as i32)))
+    }
+
+    fn read_input(input: &Entity<InputState>, cx: &App) -> Option<String> {
+        let text = input.read(cx).text().to_string();
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn create_hmac_credential(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let Some(rp_id) = Self::read_input(&self.hmac_rp_id_input, cx) else {
+            cx.emit(ToolsEvent::Notification(
+                "Enter a relying party ID".to_string(),
+            ));
+            return;
+        };
+        let pin = Self::read_input(&self.hmac_pin_input, cx);
+        let resident = self.hmac_resident;
+
+        self.loading = true;
+        self.hmac_output = None;
+        cx.notify();
+
+        self._task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { io::create_hmac_secret_credential(rp_id, pin, resident) })
+                .await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                this.loading = false;
+                this.refresh_pin_retries(cx);
+                match result {
+                    Ok(credential) => {
+                        this.hmac_credential_input.update(cx, |input, cx| {
+                            input.set_value(credential.credential_id.clone(), window, cx)
+                        });
+                        cx.emit(ToolsEvent::Notification(format!(
+                            "Created {} credential for {}",
+                            if credential.resident {
+                                "resident"
+                            } else {
+                                "non-resident"
+                            },
+                            credential.rp_id
+                        )));
+                    }
+                    Err(e) => cx.emit(ToolsEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn derive_hmac_secret(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let Some(rp_id) = Self::read_input(&self.hmac_rp_id_input, cx) else {
+            cx.emit(ToolsEvent::Notification(
+                "Enter a relying party ID".to_string(),
+            ));
+            return;
+        };
+        let credential_id = match Self::read_input(&self.hmac_credential_input, cx)
+            .map(hex::decode)
+            .transpose()
+        {
+            Ok(id) => id,
+            Err(_) => {
+                cx.emit(ToolsEvent::Notification(
+                    "Credential ID must be a hex string".to_string(),
+                ));
+                return;
+            }
+        };
+        let Some(salt1) = Self::read_input(&self.hmac_salt1_input, cx) else {
+            cx.emit(ToolsEvent::Notification("Enter a salt".to_string()));
+            return;
+        };
+        let salts = io::parse_hmac_salt(&salt1).and_then(|salt1| {
+            Self::read_input(&self.hmac_salt2_input, cx)
+                .as_deref()
+                .map(io::parse_hmac_salt)
+                .transpose()
+                .map(|salt2| (salt1, salt2))
+        });
+        let (salt1, salt2) = match salts {
+            Ok(salts) => salts,
+            Err(e) => {
+                cx.emit(ToolsEvent::Notification(e));
+                return;
+            }
+        };
+        let pin = Self::read_input(&self.hmac_pin_input, cx);
+
+        self.loading = true;
+        self.hmac_output = None;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(
+                    async move { io::derive_hmac_secret(rp_id, credential_id, salt1, salt2, pin) },
+                )
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                this.refresh_pin_retries(cx);
+                match result {
+                    Ok(output) => this.hmac_output = Some(output),
+                    Err(e) => cx.emit(ToolsEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn generate_ssh_key(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let key_type = self.ssh_key_type;
+        let application = Self::read_input(&self.ssh_application_input, cx)
+            .unwrap_or_else(|| ssh::SSH_APPLICATION_PREFIX.to_string());
+        let path = Self::read_input(&self.ssh_path_input, cx)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| ssh::default_ssh_dir().join(key_type.file_stem()));
+        if let Err(e) = ssh::ensure_key_path_free(&path) {
+            cx.emit(ToolsEvent::Notification(e.to_string()));
+            return;
+        }
+        let options = SshKeyOptions {
+            key_type,
+            application,
+            resident: self.ssh_resident,
+            verify_required: self.ssh_verify_required,
+            no_touch: self.ssh_no_touch,
+            user: Self::read_input(&self.ssh_user_input, cx).filter(|_| self.ssh_resident),
+            comment: Self::read_input(&self.ssh_comment_input, cx).unwrap_or_default(),
+        };
+        let pin = Self::read_input(&self.ssh_pin_input, cx);
+
+        self.loading = true;
+        self.ssh_generated = None;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let key = io::generate_ssh_key(options, pin)?;
+                    let written = io::write_ssh_key(&key, &path).map_err(|e| e.to_string());
+                    Ok::<_, String>((key, written))
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                this.refresh_pin_retries(cx);
+                match result {
+                    Ok((key, written)) => {
+                        cx.emit(ToolsEvent::Notification(match written {
+                            Ok((private, _)) => format!("Saved SSH key to {}", private.display()),
+                            Err(e) => format!("Key created but not saved: {}", e),
+                        }));
+                        this.ssh_generated = Some(key);
+                    }
+                    Err(e) => cx.emit(ToolsEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn load_ssh_resident_keys(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let Some(pin) = Self::read_input(&self.ssh_pin_input, cx) else {
+            cx.emit(ToolsEvent::Notification(
+                "Enter the FIDO PIN to list resident keys".to_string(),
+            ));
+            return;
+        };
+
+        self.loading = true;
+        cx.notify();
+        let entity = cx.entity().downgrade();
+
+        self._task = Some(cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    io::get_credentials(pin).map(|creds| io::ssh_resident_keys(&creds))
+                })
+                .await;
+
+            let _ = entity.update(cx, |this, cx| {
+                this.loading = false;
+                this.refresh_pin_retries(cx);
+                match result {
+                    Ok(keys) => this.ssh_resident_keys = Some(keys),
+                    Err(e) => cx.emit(ToolsEvent::Notification(format!("Error: {}", e))),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn export_ssh_resident_key(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(key) = self.ssh_resident_keys.as_ref().and_then(|k| k.get(index)) else {
+            return;
+        };
+        let path = ssh::default_ssh_dir().join(key.resident_file_stem());
+        cx.emit(ToolsEvent::Notification(
+            match io::write_ssh_key(key, &path) {
+                Ok((private, _)) => format!("Saved SSH key to {}", private.display()),
+                Err(e) => format!("Error: {}", e),
+            },
+        ));
+    }
+
+    fn render_hmac_output(output: &HmacSecretOutput, theme: &Theme) -> impl IntoElement {
+        let row = |label: &'static str, value: String| {
+            v_flex()
+                .gap_1()
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child(label),
+                )
+                .child(
+                    div()
+                        .p_3()
+                        .rounded_md()
+                        .bg(theme.muted)
+                        .font_family("monospace")
+                        .text_xs()
+                        .child(value),
+                )
+        };
+
+        v_flex()
+            .gap_3()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(div().font_medium().child("Derived Secret"))
+                    .child(
+                        Badge::new()
+                            .child(if output.user_verified { "UV" } else { "No UV" })
+                            .color(if output.user_verified {
+                                gpui::green()
+                            } else {
+                                theme.warning
+                            }),
+                    ),
+            )
+            .child(row("Credential ID", output.credential_id.clone()))
+            .child(row("Output 1", output.output1.clone()))
+            .children(output.output2.clone().map(|out| row("Output 2", out)))
+            .when(output.matching_credentials > 1, |this| {
+                this.child(div().text_sm().text_color(theme.warning).child(format!(
+                    "{} resident credentials match this RP; the first one was used",
+                    output.matching_credentials
+                )))
+            })
+    }
+
+    fn render_hmac_secret(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let supported = self.supports_hmac_secret();
+
+        let create_listener = cx.listener(|this, _, window, cx| {
+            this.create_hmac_credential(window, cx);
+        });
+        let derive_listener = cx.listener(|this, _, _, cx| {
+            this.derive_hmac_secret(cx);
+        });
+        let resident_listener = cx.listener(|this, checked: &bool, _, cx| {
+            this.hmac_resident = *checked;
+            cx.notify();
+        });
+
+        let theme = cx.theme();
+
+        Card::new()
+            .title("hmac-secret")
+            .icon(Icon::default().path("icons/key-round.svg"))
+            .description("Check the secret a credential derives for disk unlocking or password managers")
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(div().font_medium().child("Credential"))
+                            .child(Input::new(&self.hmac_rp_id_input))
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(
+                                        div()
+                                            .flex_1()
+                                            .child(Input::new(&self.hmac_credential_input)),
+                                    )
+                                    .child(
+                                        PFButton::new("Create")
+                                            .id("hmac-create-btn")
+                                            .disabled(!supported || loading)
+                                            .on_click(create_listener),
+                                    ),
+                            )
+                            .child(
+                                Checkbox::new("hmac-resident")
+                                    .label("Create as resident credential")
+                                    .checked(self.hmac_resident)
+                                    .on_click(resident_listener),
+                            ),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(div().font_medium().child("Challenge"))
+                            .child(Input::new(&self.hmac_salt1_input))
+                            .child(Input::new(&self.hmac_salt2_input))
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(div().flex_1().child(Input::new(&self.hmac_pin_input)))
+                                    .child(
+                                        PFButton::new("Derive")
+                                            .id("hmac-derive-btn")
+                                            .disabled(!supported || loading)
+                                            .on_click(derive_listener),
+                                    ),
+                            )
+                            .children(PinRetryWarning::new(self.pin_retries))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(theme.muted_foreground)
+                                    .child("Salts that are not 64 hex characters are hashed with SHA-256. The key derives different outputs with and without the PIN, so use the same setting as your unlock tooling."),
+                            ),
+                    )
+                    .children(
+                        self.hmac_output
+                            .as_ref()
+                            .map(|output| Self::render_hmac_output(output, theme)),
+                    )
+                    .when(loading, |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child("Touch your key when it blinks."),
+                        )
+                    })
+                    .when(self.fido_info.is_some() && !supported, |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child("This key does not advertise the hmac-secret extension."),
+                        )
+                    }),
+            )
+    }
+}
+
+impl ToolsView {
+    fn render_ssh_keys(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading;
+        let ed25519_supported = self.supports_algorithm(SshSkKeyType::Ed25519Sk.algorithm());
+        let key_type_index = SSH_KEY_TYPES.iter().position(|t| *t == self.ssh_key_type);
+
+        let type_listener = cx.listener(|this, index: &usize, _, cx| {
+            if let Some(key_type) = SSH_KEY_TYPES.get(*index) {
+                this.ssh_key_type = *key_type;
+            }
+            cx.notify();
+        });
+        let resident_listener = cx.listener(|this, checked: &bool, _, cx| {
+            this.ssh_resident = *checked;
+            cx.notify();
+        });
+        let verify_listener = cx.listener(|this, checked: &bool, _, cx| {
+            this.ssh_verify_required = *checked;
+            cx.notify();
+        });
+        let no_touch_listener = cx.listener(|this, checked: &bool, _, cx| {
+            this.ssh_no_touch = *checked;
+            cx.notify();
+        });
+        let generate_listener = cx.listener(|this, _, _, cx| {
+            this.generate_ssh_key(cx);
+        });
+        let load_listener = cx.listener(|this, _, _, cx| {
+            this.load_ssh_resident_keys(cx);
+        });
+        let copy_listener = cx.listener(|this, _, _, cx| {
+            if let Some(key) = &this.ssh_generated {
+                cx.write_to_clipboard(ClipboardItem::new_string(key.public_key_line()));
+                cx.emit(ToolsEvent::Notification(
+                    "Public key copied to clipboard".to_string(),
+                ));
+            }
+        });
+
+        let resident_rows = self.ssh_resident_keys.as_ref().map(|keys| {
+            if keys.is_empty() {
+                return div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("No resident SSH keys on this key.")
+                    .into_any_element();
+            }
+            v_flex()
+                .gap_2()
+                .children(keys.iter().enumerate().map(|(i, key)| {
+                    let export_listener = cx.listener(move |this, _, _, cx| {
+                        this.export_ssh_resident_key(i, cx);
+                    });
+                    let line = key.public_key_line();
+                    h_flex()
+                        .justify_between()
+                        .items_center()
+                        .gap_4()
+                        .child(
+                            v_flex()
+                                .child(div().font_medium().child(key.application.clone()))
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .child(if key.comment.is_empty() {
+                                            key.key_type.name().to_string()
+                                        } else {
+                                            format!("{} ({})", key.key_type.name(), key.comment)
+                                        }),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    PFButton::new("Copy")
+                                        .id(SharedString::from(format!("ssh-rk-copy-{}", i)))
+                                        .small()
+                                        .on_click(move |_, _, cx| {
+                                            cx.write_to_clipboard(ClipboardItem::new_string(
+                                                line.clone(),
+                                            ));
+                                        }),
+                                )
+                                .child(
+                                    PFButton::new("Save to ~/.ssh")
+                                        .id(SharedString::from(format!("ssh-rk-save-{}", i)))
+                                        .small()
+                                        .on_click(export_listener),
+                                ),
+                        )
+                }))
+                .into_any_element()
+        });
+
+        let theme = cx.theme();
+
+        Card::new()
+            .title("SSH Keys")
+            .icon(Icon::default().path("icons/terminal.svg"))
+            .description("Create OpenSSH keys that live on this key (sk-ecdsa / sk-ed25519)")
+            .child(
+                v_flex()
+                    .gap_4()
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(div().font_medium().child("Generate"))
+                            .child(
+                                RadioGroup::horizontal("ssh-key-type")
+                                    .child(Radio::new("ssh-ecdsa").label("ECDSA P-256 (ES256)"))
+                                    .child(
+                                        Radio::new("ssh-ed25519")
+                                            .label("Ed25519 (EdDSA)")
+                                            .disabled(!ed25519_supported),
+                                    )
+                                    .selected_index(key_type_index)
+                                    .on_click(type_listener),
+                            )
+                            .child(
+                                h_flex()
+                                    .gap_4()
+                                    .child(
+                                        Checkbox::new("ssh-resident")
+                                            .label("Resident")
+                                            .checked(self.ssh_resident)
+                                            .on_click(resident_listener),
+                                    )
+                                    .child(
+                                        Checkbox::new("ssh-verify-required")
+                                            .label("Verify required (PIN)")
+                                            .checked(self.ssh_verify_required)
+                                            .on_click(verify_listener),
+                                    )
+                                    .child(
+                                        Checkbox::new("ssh-no-touch")
+                                            .label("No touch required")
+                                            .checked(self.ssh_no_touch)
+                                            .on_click(no_touch_listener),
+                                    ),
+                            )
+                            .child(Input::new(&self.ssh_application_input))
+                            .when(self.ssh_resident, |this| {
+                                this.child(Input::new(&self.ssh_user_input))
+                            })
+                            .child(Input::new(&self.ssh_comment_input))
+                            .child(Input::new(&self.ssh_path_input))
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(div().flex_1().child(Input::new(&self.ssh_pin_input)))
+                                    .child(
+                                        PFButton::new("Generate")
+                                            .id("ssh-generate-btn")
+                                            .disabled(loading)
+                                            .on_click(generate_listener),
+                                    ),
+                            )
+                            .children(PinRetryWarning::new(self.pin_retries))
+                            .children(self.ssh_generated.as_ref().map(|key| {
+                                v_flex()
+                                    .gap_2()
+                                    .child(
+                                        div()
+                                            .p_3()
+                                            .rounded_md()
+                                            .bg(theme.muted)
+                                            .font_family("monospace")
+                                            .text_xs()
+                                            .child(key.public_key_line()),
+                                    )
+                                    .child(
+                                        h_flex().child(
+                                            PFButton::new("Copy Public Key")
+                                                .id("ssh-copy-btn")
+                                                .small()
+                                                .on_click(copy_listener),
+                                        ),
+                                    )
+                            })),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_3()
+                            .p_4()
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_lg()
+                            .child(
+                                h_flex()
+                                    .justify_between()
+                                    .items_center()
+                                    .child(
+                                        v_flex()
+                                            .child(div().font_medium().child("Resident Keys"))
+                                            .child(
+                                                div()
+                                                    .text_sm()
+                                                    .text_color(theme.muted_foreground)
+                                                    .child("Recover key files on a new machine, like ssh-keygen -K"),
+                                            ),
+                                    )
+                                    .child(
+                                        PFButton::new("Load")
+                                            .id("ssh-load-btn")
+                                            .disabled(loading)
+                                            .on_click(load_listener),
+                                    ),
+                            )
+                            .children(resident_rows),
+                    ),
+            )
+    }
+}
+
+impl Render for ToolsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = v_flex()
+            .gap_6()
+            .w_full()
+            .when(self.fido_info.is_some(), |this| {
+                this.child(self.render_ssh_keys(cx))
+                    .child(self.render_hmac_secret(cx))
+            })
+            .when(self.fido_info.is_none(), |this| {
+                this.child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("Connect a key with the FIDO application to use these tools."),
+                )
+            });
+
+        PageView::build(
+            "Tools",
+            "Test how the key behaves with the tools that rely on it.",
+            content,
+            cx.theme(),
+        )
+    }
+}